@@ -1,4 +1,5 @@
-use crate::app::state::ViewMode;
+use crate::app::state::{TypeFilter, ViewMode};
+use crate::cli::FileSortKey;
 use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 
 pub enum Action {
@@ -10,13 +11,38 @@ pub enum Action {
     MoveDown,
     PageUp,
     PageDown,
+    /// Jump to the first item (`g` / Home).
+    MoveTop,
+    /// Jump to the last item (`G` / End).
+    MoveBottom,
     Select,
     Back,
     NextTab,
     PrevTab,
     Refresh,
+    RefreshDetail,
     YankSelected,
+    /// Copy just the highlighted file's path.
+    CopyPath,
+    /// Open the highlighted regular file in `$PAGER` / `$EDITOR`.
+    OpenInPager,
+    /// Open the export format menu (`Ctrl+E`).
     ExportProcess,
+    /// Export in the highlighted format (`Enter` in the export menu).
+    ExportConfirm,
+    /// Close the export menu (`Esc`).
+    ExportCancel,
+    ToggleTypeFilter(TypeFilter),
+    ToggleFullCommand,
+    CycleColumns,
+    /// Sort the OpenFiles table by a column (`1`-`5`; again to reverse).
+    SortFiles(FileSortKey),
+    StartFilter,
+    FilterInput(char),
+    FilterBackspace,
+    FilterClear,
+    FilterDone,
+    FilterCancel,
 }
 
 /// Map a key event to an action based on the current view mode and search state.
@@ -28,7 +54,9 @@ pub fn map_key_to_action(key: KeyEvent, mode: &ViewMode, search_empty: bool) ->
 
     match mode {
         ViewMode::Search => map_search_key(key, search_empty),
+        ViewMode::FilterInput => map_filter_key(key),
         ViewMode::Detail => map_detail_key(key),
+        ViewMode::ExportMenu => map_export_key(key),
     }
 }
 
@@ -37,6 +65,7 @@ fn map_search_key(key: KeyEvent, search_empty: bool) -> Option<Action> {
     if key.modifiers.contains(KeyModifiers::CONTROL) {
         return match key.code {
             KeyCode::Char('u') => Some(Action::SearchClear),
+            KeyCode::Char('a') => Some(Action::ToggleFullCommand),
             KeyCode::Char('c') => Some(Action::Quit),
             _ => None,
         };
@@ -56,15 +85,42 @@ fn map_search_key(key: KeyEvent, search_empty: bool) -> Option<Action> {
         KeyCode::Down => Some(Action::MoveDown),
         KeyCode::PageUp => Some(Action::PageUp),
         KeyCode::PageDown => Some(Action::PageDown),
+        KeyCode::Home => Some(Action::MoveTop),
+        KeyCode::End => Some(Action::MoveBottom),
         KeyCode::F(5) => Some(Action::Refresh),
         KeyCode::Char('k') if search_empty => Some(Action::MoveUp),
         KeyCode::Char('j') if search_empty => Some(Action::MoveDown),
+        KeyCode::Char('g') if search_empty => Some(Action::MoveTop),
+        KeyCode::Char('G') if search_empty => Some(Action::MoveBottom),
         KeyCode::Char('q') if search_empty => Some(Action::Quit),
+        KeyCode::Char(':') if search_empty => Some(Action::StartFilter),
         KeyCode::Char(c) => Some(Action::SearchInput(c)),
         _ => None,
     }
 }
 
+fn map_filter_key(key: KeyEvent) -> Option<Action> {
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        return match key.code {
+            KeyCode::Char('u') => Some(Action::FilterClear),
+            KeyCode::Char('c') => Some(Action::Quit),
+            _ => None,
+        };
+    }
+
+    match key.code {
+        KeyCode::Esc => Some(Action::FilterCancel),
+        KeyCode::Enter => Some(Action::FilterDone),
+        KeyCode::Backspace => Some(Action::FilterBackspace),
+        KeyCode::Up => Some(Action::MoveUp),
+        KeyCode::Down => Some(Action::MoveDown),
+        KeyCode::PageUp => Some(Action::PageUp),
+        KeyCode::PageDown => Some(Action::PageDown),
+        KeyCode::Char(c) => Some(Action::FilterInput(c)),
+        _ => None,
+    }
+}
+
 fn map_detail_key(key: KeyEvent) -> Option<Action> {
     // Check for Ctrl modifiers first
     if key.modifiers.contains(KeyModifiers::CONTROL) {
@@ -72,6 +128,7 @@ fn map_detail_key(key: KeyEvent) -> Option<Action> {
             KeyCode::Char('c') => Some(Action::Quit),
             KeyCode::Char('y') => Some(Action::YankSelected),
             KeyCode::Char('e') => Some(Action::ExportProcess),
+            KeyCode::Char('a') => Some(Action::ToggleFullCommand),
             _ => None,
         };
     }
@@ -85,7 +142,42 @@ fn map_detail_key(key: KeyEvent) -> Option<Action> {
         KeyCode::Down | KeyCode::Char('j') => Some(Action::MoveDown),
         KeyCode::PageUp => Some(Action::PageUp),
         KeyCode::PageDown => Some(Action::PageDown),
+        KeyCode::Home | KeyCode::Char('g') => Some(Action::MoveTop),
+        KeyCode::End | KeyCode::Char('G') => Some(Action::MoveBottom),
         KeyCode::F(5) => Some(Action::Refresh),
+        KeyCode::Char('r') => Some(Action::RefreshDetail),
+        KeyCode::Char('f') => Some(Action::ToggleTypeFilter(TypeFilter::Regular)),
+        KeyCode::Char('d') => Some(Action::ToggleTypeFilter(TypeFilter::Directory)),
+        KeyCode::Char('s') => Some(Action::ToggleTypeFilter(TypeFilter::Socket)),
+        KeyCode::Char('n') => Some(Action::ToggleTypeFilter(TypeFilter::Network)),
+        KeyCode::Char('a') => Some(Action::ToggleFullCommand),
+        KeyCode::Char('c') => Some(Action::CycleColumns),
+        KeyCode::Char('o') => Some(Action::OpenInPager),
+        KeyCode::Char('y') => Some(Action::CopyPath),
+        KeyCode::Char('1') => Some(Action::SortFiles(FileSortKey::Fd)),
+        KeyCode::Char('2') => Some(Action::SortFiles(FileSortKey::Type)),
+        KeyCode::Char('3') => Some(Action::SortFiles(FileSortKey::Size)),
+        KeyCode::Char('4') => Some(Action::SortFiles(FileSortKey::Node)),
+        KeyCode::Char('5') => Some(Action::SortFiles(FileSortKey::Name)),
+        _ => None,
+    }
+}
+
+fn map_export_key(key: KeyEvent) -> Option<Action> {
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        return match key.code {
+            KeyCode::Char('c') => Some(Action::Quit),
+            _ => None,
+        };
+    }
+
+    match key.code {
+        KeyCode::Esc => Some(Action::ExportCancel),
+        KeyCode::Enter => Some(Action::ExportConfirm),
+        KeyCode::Up | KeyCode::Char('k') => Some(Action::MoveUp),
+        KeyCode::Down | KeyCode::Char('j') => Some(Action::MoveDown),
+        KeyCode::Home | KeyCode::Char('g') => Some(Action::MoveTop),
+        KeyCode::End | KeyCode::Char('G') => Some(Action::MoveBottom),
         _ => None,
     }
 }
@@ -1,12 +1,36 @@
-use crate::model::{OpenFileInfo, ProcessInfo};
+use crate::cli::FileSortKey;
+use crate::filter::FilterConfig;
+use crate::model::{FileType, OpenFileInfo, ProcessInfo};
+use crate::output::OutputFormatter;
 use crate::platform::PlatformProvider;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 use ratatui::widgets::{ListState, TableState};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long the highlighted row must stay put before the preview is fetched.
+const PREVIEW_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// How long a status toast stays on screen.
+const STATUS_TTL: Duration = Duration::from_secs(3);
+
+/// Severity of a status toast, which picks its color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatusKind {
+    Info,
+    Warn,
+    Error,
+}
 
 pub enum ViewMode {
     Search,
+    /// Editing the structured filter expression (`:` in the search view).
+    FilterInput,
     Detail,
+    /// Choosing an export format (`Ctrl+E` in the detail view).
+    ExportMenu,
 }
 
 pub enum DetailTab {
@@ -16,12 +40,149 @@ pub enum DetailTab {
     Summary,
 }
 
+/// The OpenFiles table order (keys `1`-`5`): a key and its direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileSort {
+    pub key: FileSortKey,
+    pub descending: bool,
+}
+
+/// How `Ctrl+E` writes the selected process to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Plain,
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    /// Menu order.
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Plain, ExportFormat::Json, ExportFormat::Csv];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Plain => "Plain text",
+            ExportFormat::Json => "JSON",
+            ExportFormat::Csv => "CSV",
+        }
+    }
+}
+
+/// Transient FD-type filter for the detail OpenFiles tab.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TypeFilter {
+    Regular,
+    Directory,
+    Socket,
+    Network,
+}
+
+impl TypeFilter {
+    /// Whether a file of type `ft` passes this filter.
+    pub fn matches(&self, ft: &FileType) -> bool {
+        match self {
+            TypeFilter::Regular => *ft == FileType::Reg,
+            TypeFilter::Directory => *ft == FileType::Dir,
+            TypeFilter::Socket => matches!(
+                ft,
+                FileType::Sock | FileType::Unix | FileType::IPv4 | FileType::IPv6
+            ),
+            TypeFilter::Network => matches!(ft, FileType::IPv4 | FileType::IPv6),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TypeFilter::Regular => "regular",
+            TypeFilter::Directory => "dir",
+            TypeFilter::Socket => "sockets",
+            TypeFilter::Network => "network",
+        }
+    }
+}
+
+/// Optional columns of the detail OpenFiles table, as a bitset. FD, TYPE
+/// and NAME are always shown; `c` cycles through the presets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileColumns(u8);
+
+impl FileColumns {
+    pub const DEVICE: u8 = 1;
+    pub const SIZE: u8 = 1 << 1;
+    pub const NODE: u8 = 1 << 2;
+
+    pub const ALL: FileColumns = FileColumns(Self::DEVICE | Self::SIZE | Self::NODE);
+    /// Hide DEVICE and NODE, keeping SIZE/OFF next to a wide NAME.
+    pub const NAME_FOCUSED: FileColumns = FileColumns(Self::SIZE);
+    pub const MINIMAL: FileColumns = FileColumns(0);
+
+    pub fn shows(self, column: u8) -> bool {
+        self.0 & column != 0
+    }
+
+    /// The preset after this one: all -> name-focused -> minimal -> all.
+    pub fn next_preset(self) -> FileColumns {
+        match self {
+            Self::ALL => Self::NAME_FOCUSED,
+            Self::NAME_FOCUSED => Self::MINIMAL,
+            _ => Self::ALL,
+        }
+    }
+}
+
+/// Quick summary of the highlighted process shown in the search view.
+#[derive(Debug, Clone)]
+pub struct ProcessPreview {
+    pub pid: u32,
+    pub user: String,
+    pub ppid: Option<u32>,
+    pub exe: Option<String>,
+    pub fd_count: Option<usize>,
+    pub fd_limit: Option<(u64, u64)>,
+}
+
+/// Delays an action until the requested key has been stable for `delay`.
+pub struct Debounce {
+    delay: Duration,
+    pending: Option<(u32, Instant)>,
+}
+
+impl Debounce {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            pending: None,
+        }
+    }
+
+    /// Record a new request, restarting the delay.
+    pub fn request(&mut self, key: u32, now: Instant) {
+        self.pending = Some((key, now));
+    }
+
+    /// Return the pending key once its delay has elapsed, clearing it.
+    pub fn poll(&mut self, now: Instant) -> Option<u32> {
+        match self.pending {
+            Some((key, since)) if now.duration_since(since) >= self.delay => {
+                self.pending = None;
+                Some(key)
+            }
+            _ => None,
+        }
+    }
+}
+
 pub struct AppState {
     pub mode: ViewMode,
     pub search_input: String,
     pub all_processes: Vec<ProcessInfo>,
     pub filtered_indices: Vec<usize>,
     pub list_state: ListState,
+    /// The process shown in the detail view. This is a copy, not an index
+    /// into `all_processes`: it is taken on `select_current`, its
+    /// `open_files` are filled in separately, and `refresh` re-syncs the
+    /// rest of it by PID through `sync_selected`.
     pub selected_process: Option<ProcessInfo>,
     pub detail_tab: DetailTab,
     pub file_table_state: TableState,
@@ -32,6 +193,30 @@ pub struct AppState {
     pub match_count: usize,
     pub total_count: usize,
     pub export_data: Option<String>,
+    /// Format highlighted in the export menu, used when it is confirmed.
+    pub export_format: ExportFormat,
+    pub preview: Option<ProcessPreview>,
+    preview_gate: Debounce,
+    pub type_filter: Option<TypeFilter>,
+    /// Order of the OpenFiles table; `None` keeps scan order.
+    pub file_sort: Option<FileSort>,
+    /// Notice shown in the detail view, e.g. when the process has exited.
+    pub detail_banner: Option<String>,
+    /// Show full argv instead of `comm` in the process list and detail header.
+    pub show_full_command: bool,
+    /// Which optional columns the detail file table shows.
+    pub visible_columns: FileColumns,
+    /// Structured filter expression, e.g. `user:root port:443`.
+    pub filter_expr: String,
+    /// Parse error for `filter_expr`; the last valid filter stays applied.
+    pub filter_error: Option<String>,
+    active_filter: Option<FilterConfig>,
+    /// Whether `all_processes` carry their open files, which file-level
+    /// filter keys need.
+    files_loaded: bool,
+    /// Transient feedback for the last action and when it was set; cleared
+    /// on a tick once `STATUS_TTL` has passed.
+    pub status_message: Option<(String, Instant, StatusKind)>,
 }
 
 impl AppState {
@@ -45,7 +230,7 @@ impl AppState {
             list_state.select(Some(0));
         }
 
-        Self {
+        let mut state = Self {
             mode: ViewMode::Search,
             search_input: String::new(),
             all_processes: processes,
@@ -60,12 +245,29 @@ impl AppState {
             match_count,
             total_count,
             export_data: None,
-        }
+            export_format: ExportFormat::default(),
+            preview: None,
+            preview_gate: Debounce::new(PREVIEW_DEBOUNCE),
+            type_filter: None,
+            file_sort: None,
+            detail_banner: None,
+            show_full_command: false,
+            visible_columns: FileColumns::ALL,
+            filter_expr: String::new(),
+            filter_error: None,
+            active_filter: None,
+            files_loaded: false,
+            status_message: None,
+        };
+        state.schedule_preview();
+        state
     }
     /// Apply fuzzy search to the process list based on current search_input.
     pub fn update_filter(&mut self) {
         if self.search_input.is_empty() {
-            self.filtered_indices = (0..self.all_processes.len()).collect();
+            self.filtered_indices = (0..self.all_processes.len())
+                .filter(|&i| self.passes_filter(&self.all_processes[i]))
+                .collect();
         } else {
             let matcher = SkimMatcherV2::default();
             let query = &self.search_input;
@@ -74,6 +276,7 @@ impl AppState {
                 .all_processes
                 .iter()
                 .enumerate()
+                .filter(|(_, p)| self.passes_filter(p))
                 .filter_map(|(i, p)| {
                     let haystack = format!("{} {} {}", p.pid, p.comm, p.user);
                     matcher
@@ -82,8 +285,14 @@ impl AppState {
                 })
                 .collect();
 
-            // Sort by score descending (best matches first)
-            scored.sort_by(|a, b| b.1.cmp(&a.1));
+            // Sort by score descending (best matches first). Ties fall back to
+            // PID, then comm, so the highlighted row doesn't jump on refresh.
+            let procs = &self.all_processes;
+            scored.sort_by(|&(a, sa), &(b, sb)| {
+                sb.cmp(&sa)
+                    .then(procs[a].pid.cmp(&procs[b].pid))
+                    .then_with(|| procs[a].comm.cmp(&procs[b].comm))
+            });
             self.filtered_indices = scored.into_iter().map(|(i, _)| i).collect();
         }
 
@@ -95,17 +304,150 @@ impl AppState {
         } else {
             self.list_state.select(Some(0));
         }
+        self.schedule_preview();
+    }
+
+    /// Guidance shown in place of the process list when the provider
+    /// returned no processes at all (e.g. a heavily restricted container).
+    pub fn empty_message(&self) -> Option<&'static str> {
+        self.all_processes
+            .is_empty()
+            .then_some("No processes visible — try running with elevated privileges")
+    }
+
+    /// The process under the cursor in the search list, if any.
+    pub fn highlighted_process(&self) -> Option<&ProcessInfo> {
+        let selected = self.list_state.selected()?;
+        let &idx = self.filtered_indices.get(selected)?;
+        self.all_processes.get(idx)
+    }
+
+    /// Queue a preview fetch for the highlighted process.
+    fn schedule_preview(&mut self) {
+        match self.highlighted_process().map(|p| p.pid) {
+            Some(pid) => self.preview_gate.request(pid, Instant::now()),
+            None => self.preview = None,
+        }
+    }
+
+    /// Fetch the preview once the highlighted row has settled.
+    pub fn poll_preview(&mut self, provider: &dyn PlatformProvider) {
+        let pid = match self.preview_gate.poll(Instant::now()) {
+            Some(pid) => pid,
+            None => return,
+        };
+        if self.preview.as_ref().is_some_and(|p| p.pid == pid) {
+            return;
+        }
+        let proc = match self.all_processes.iter().find(|p| p.pid == pid) {
+            Some(p) => p,
+            None => return,
+        };
+        self.preview = Some(ProcessPreview {
+            pid,
+            user: proc.user.clone(),
+            ppid: proc.ppid,
+            exe: provider.exe_path(pid),
+            fd_count: provider.fd_count_hint(pid),
+            fd_limit: proc.fd_limit,
+        });
     }
 
     /// Reload the process list from the platform provider.
     pub fn refresh(&mut self, provider: &dyn PlatformProvider) {
-        if let Ok(processes) = provider.list_processes() {
-            self.total_count = processes.len();
-            self.all_processes = processes;
-            self.update_filter();
+        match provider.list_processes() {
+            Ok(processes) => {
+                self.total_count = processes.len();
+                self.all_processes = processes;
+                self.files_loaded = false;
+                self.load_files_if_needed(provider);
+                self.update_filter();
+                self.sync_selected();
+                self.set_status(
+                    format!("Refreshed {} processes", self.total_count),
+                    StatusKind::Info,
+                );
+            }
+            Err(e) => self.set_status(format!("Refresh failed: {}", e), StatusKind::Error),
         }
     }
 
+    /// Show `message` as a toast until `STATUS_TTL` has passed.
+    pub fn set_status(&mut self, message: impl Into<String>, kind: StatusKind) {
+        self.status_message = Some((message.into(), Instant::now(), kind));
+    }
+
+    /// Clear the toast once it has been shown for `STATUS_TTL` (called on
+    /// every tick).
+    pub fn expire_status(&mut self, now: Instant) {
+        if let Some((_, set_at, _)) = &self.status_message {
+            if now.duration_since(*set_at) >= STATUS_TTL {
+                self.status_message = None;
+            }
+        }
+    }
+
+    /// Start editing the filter expression.
+    pub fn start_filter_input(&mut self) {
+        self.mode = ViewMode::FilterInput;
+    }
+
+    /// Leave filter editing, keeping the applied filter.
+    pub fn finish_filter_input(&mut self) {
+        self.mode = ViewMode::Search;
+    }
+
+    /// Leave filter editing and drop the filter entirely.
+    pub fn cancel_filter_input(&mut self) {
+        self.filter_expr.clear();
+        self.filter_error = None;
+        self.active_filter = None;
+        self.mode = ViewMode::Search;
+        self.update_filter();
+    }
+
+    /// Re-parse `filter_expr` and apply it to the list. On a parse error the
+    /// previous filter stays in effect and the error is kept for display.
+    pub fn apply_filter_expr(&mut self, provider: &dyn PlatformProvider) {
+        match FilterConfig::parse_expr(&self.filter_expr) {
+            Ok(config) => {
+                self.filter_error = None;
+                self.active_filter = (!config.is_empty()).then_some(config);
+                self.load_files_if_needed(provider);
+                self.update_filter();
+            }
+            Err(e) => self.filter_error = Some(e.to_string()),
+        }
+    }
+
+    /// Read every process's open files once, the first time a file-level
+    /// filter key is used.
+    fn load_files_if_needed(&mut self, provider: &dyn PlatformProvider) {
+        let needs_files = self
+            .active_filter
+            .as_ref()
+            .is_some_and(|f| f.has_file_filters());
+        if !needs_files || self.files_loaded {
+            return;
+        }
+        for proc in &mut self.all_processes {
+            if let Ok(files) = provider.list_open_files(proc.pid) {
+                proc.open_files = files;
+            }
+        }
+        self.files_loaded = true;
+    }
+
+    /// Whether `proc` passes the structured filter, if one is applied.
+    fn passes_filter(&self, proc: &ProcessInfo) -> bool {
+        let Some(filter) = &self.active_filter else {
+            return true;
+        };
+        filter.matches_process(proc)
+            && (!filter.has_file_filters()
+                || proc.open_files.iter().any(|f| filter.matches_file(f)))
+    }
+
     /// Enter detail view for the currently selected process.
     pub fn select_current(&mut self) {
         if let Some(selected) = self.list_state.selected() {
@@ -114,6 +456,8 @@ impl AppState {
                     self.selected_process = Some(proc.clone());
                     self.mode = ViewMode::Detail;
                     self.detail_tab = DetailTab::OpenFiles;
+                    self.type_filter = None;
+                    self.detail_banner = None;
                     self.file_table_state = TableState::default();
                     self.file_table_state.select(Some(0));
                     self.tree_list_state = ListState::default();
@@ -123,12 +467,142 @@ impl AppState {
         }
     }
 
+    /// Bring `selected_process` up to date with `all_processes` after a
+    /// refresh, matching by PID. Its open files are kept unless the fresh
+    /// list has them loaded; if the PID is gone the copy stays and the
+    /// banner says so.
+    fn sync_selected(&mut self) {
+        let Some(ref mut selected) = self.selected_process else {
+            return;
+        };
+        match self.all_processes.iter().find(|p| p.pid == selected.pid) {
+            Some(fresh) => {
+                let files = std::mem::take(&mut selected.open_files);
+                *selected = fresh.clone();
+                if selected.open_files.is_empty() {
+                    selected.open_files = files;
+                }
+            }
+            None => {
+                self.detail_banner = Some(format!("Process {} has exited", selected.pid));
+            }
+        }
+    }
+
     /// Return to search view from detail view.
     pub fn go_back(&mut self) {
         self.mode = ViewMode::Search;
         self.selected_process = None;
     }
 
+    /// Re-read open files for the process shown in the detail view only,
+    /// keeping the current tab and row (clamped if the list shrank).
+    pub fn refresh_selected(&mut self, provider: &dyn PlatformProvider) {
+        let pid = match &self.selected_process {
+            Some(p) => p.pid,
+            None => return,
+        };
+        match provider.list_open_files(pid) {
+            Ok(files) => {
+                if let Some(ref mut proc) = self.selected_process {
+                    proc.open_files = files;
+                }
+                self.detail_banner = None;
+                let count = self.detail_item_count();
+                let state = match self.detail_tab {
+                    DetailTab::FileTree => self.tree_list_state.selected_mut(),
+                    _ => self.file_table_state.selected_mut(),
+                };
+                *state = match (*state, count) {
+                    (_, 0) => None,
+                    (Some(sel), _) => Some(sel.min(count - 1)),
+                    (None, _) => Some(0),
+                };
+            }
+            Err(e) => {
+                self.detail_banner = Some(if provider.start_time(pid).is_none() {
+                    format!("Process {} has exited", pid)
+                } else {
+                    format!("Cannot refresh PID {}: {}", pid, e)
+                });
+            }
+        }
+    }
+
+    /// Switch between showing `comm` and the full command line.
+    pub fn toggle_full_command(&mut self) {
+        self.show_full_command = !self.show_full_command;
+    }
+
+    /// Advance the detail file table to the next column preset.
+    pub fn cycle_columns(&mut self) {
+        self.visible_columns = self.visible_columns.next_preset();
+    }
+
+    /// The command text to render for `proc` under the current toggle.
+    pub fn display_command<'a>(&self, proc: &'a ProcessInfo) -> &'a str {
+        if self.show_full_command {
+            &proc.command
+        } else {
+            &proc.comm
+        }
+    }
+
+    /// Toggle the OpenFiles type filter; selecting the active one clears it.
+    pub fn toggle_type_filter(&mut self, filter: TypeFilter) {
+        if !matches!(self.detail_tab, DetailTab::OpenFiles) {
+            return;
+        }
+        self.type_filter = if self.type_filter == Some(filter) {
+            None
+        } else {
+            Some(filter)
+        };
+        self.reset_detail_scroll();
+    }
+
+    /// Open files that pass the active type filter, in display order.
+    pub fn filtered_files<'a>(&self, files: &'a [OpenFileInfo]) -> Vec<&'a OpenFileInfo> {
+        let mut shown: Vec<&OpenFileInfo> = files
+            .iter()
+            .filter(|f| self.type_filter.is_none_or(|t| t.matches(&f.file_type)))
+            .collect();
+        if let Some(sort) = self.file_sort {
+            shown.sort_by(|a, b| crate::scan::compare_files(a, b, sort.key, sort.descending));
+        }
+        shown
+    }
+
+    /// Sort the OpenFiles table by `key`; choosing the current key again
+    /// flips the direction. The highlighted file stays highlighted.
+    pub fn sort_files_by(&mut self, key: FileSortKey) {
+        if !matches!(self.detail_tab, DetailTab::OpenFiles) {
+            return;
+        }
+        let Some(proc) = &self.selected_process else {
+            return;
+        };
+        let identity = |f: &OpenFileInfo| (f.fd.to_string(), f.node.clone(), f.name.clone());
+        let highlighted = self.file_table_state.selected().and_then(|i| {
+            self.filtered_files(&proc.open_files)
+                .get(i)
+                .map(|f| identity(f))
+        });
+
+        self.file_sort = Some(FileSort {
+            key,
+            descending: self
+                .file_sort
+                .is_some_and(|s| s.key == key && !s.descending),
+        });
+
+        if let Some(key) = highlighted {
+            let files = self.filtered_files(&proc.open_files);
+            let index = files.iter().position(|f| identity(f) == key);
+            self.file_table_state.select(index.or(Some(0)));
+        }
+    }
+
     /// Switch to the next detail tab.
     pub fn next_tab(&mut self) {
         self.detail_tab = match self.detail_tab {
@@ -151,42 +625,87 @@ impl AppState {
         self.reset_detail_scroll();
     }
 
+    /// Scroll the detail tables back to the top, selecting the first row
+    /// of the current tab, or nothing when it has no rows.
     fn reset_detail_scroll(&mut self) {
+        let first = (self.detail_item_count() > 0).then_some(0);
         self.file_table_state = TableState::default();
-        self.file_table_state.select(Some(0));
+        self.file_table_state.select(first);
         self.tree_list_state = ListState::default();
-        self.tree_list_state.select(Some(0));
+        self.tree_list_state.select(first);
     }
 
     /// Move selection up by one.
     pub fn move_up(&mut self) {
         match self.mode {
-            ViewMode::Search => self.search_move(-1),
+            ViewMode::Search | ViewMode::FilterInput => self.search_move(-1),
             ViewMode::Detail => self.detail_move(-1),
+            ViewMode::ExportMenu => self.export_move(-1),
         }
     }
 
     /// Move selection down by one.
     pub fn move_down(&mut self) {
         match self.mode {
-            ViewMode::Search => self.search_move(1),
+            ViewMode::Search | ViewMode::FilterInput => self.search_move(1),
             ViewMode::Detail => self.detail_move(1),
+            ViewMode::ExportMenu => self.export_move(1),
         }
     }
 
     /// Move selection up by a page (10 items).
     pub fn page_up(&mut self) {
         match self.mode {
-            ViewMode::Search => self.search_move(-10),
+            ViewMode::Search | ViewMode::FilterInput => self.search_move(-10),
             ViewMode::Detail => self.detail_move(-10),
+            ViewMode::ExportMenu => self.export_move(-10),
         }
     }
 
     /// Move selection down by a page (10 items).
     pub fn page_down(&mut self) {
         match self.mode {
-            ViewMode::Search => self.search_move(10),
+            ViewMode::Search | ViewMode::FilterInput => self.search_move(10),
             ViewMode::Detail => self.detail_move(10),
+            ViewMode::ExportMenu => self.export_move(10),
+        }
+    }
+
+    /// Jump to the first item of the list or table in view.
+    pub fn move_top(&mut self) {
+        self.move_to_end(false);
+    }
+
+    /// Jump to the last item of the list or table in view.
+    pub fn move_bottom(&mut self) {
+        self.move_to_end(true);
+    }
+
+    fn move_to_end(&mut self, last: bool) {
+        let pick = |len: usize| if last { len - 1 } else { 0 };
+        match self.mode {
+            ViewMode::Search | ViewMode::FilterInput => {
+                if self.filtered_indices.is_empty() {
+                    return;
+                }
+                self.list_state
+                    .select(Some(pick(self.filtered_indices.len())));
+                self.schedule_preview();
+            }
+            ViewMode::Detail => {
+                let count = self.detail_item_count();
+                if count == 0 {
+                    return;
+                }
+                match self.detail_tab {
+                    DetailTab::FileTree => self.tree_list_state.select(Some(pick(count))),
+                    DetailTab::Summary => {}
+                    _ => self.file_table_state.select(Some(pick(count))),
+                }
+            }
+            ViewMode::ExportMenu => {
+                self.export_format = ExportFormat::ALL[pick(ExportFormat::ALL.len())];
+            }
         }
     }
 
@@ -198,6 +717,7 @@ impl AppState {
         let current = self.list_state.selected().unwrap_or(0) as i32;
         let next = ((current + delta) % len + len) % len;
         self.list_state.select(Some(next as usize));
+        self.schedule_preview();
     }
 
     fn detail_move(&mut self, delta: i32) {
@@ -209,8 +729,10 @@ impl AppState {
 
         match self.detail_tab {
             DetailTab::FileTree => {
-                let current = self.tree_list_state.selected().unwrap_or(0) as i32;
-                let next = (current + delta).clamp(0, len - 1);
+                let next = match self.tree_list_state.selected() {
+                    Some(current) => (current as i32 + delta).clamp(0, len - 1),
+                    None => 0,
+                };
                 self.tree_list_state.select(Some(next as usize));
             }
             DetailTab::Summary => {
@@ -218,8 +740,10 @@ impl AppState {
             }
             _ => {
                 // OpenFiles and Network use file_table_state
-                let current = self.file_table_state.selected().unwrap_or(0) as i32;
-                let next = (current + delta).clamp(0, len - 1);
+                let next = match self.file_table_state.selected() {
+                    Some(current) => (current as i32 + delta).clamp(0, len - 1),
+                    None => 0,
+                };
                 self.file_table_state.select(Some(next as usize));
             }
         }
@@ -231,7 +755,7 @@ impl AppState {
             None => return 0,
         };
         match self.detail_tab {
-            DetailTab::OpenFiles => proc.open_files.len(),
+            DetailTab::OpenFiles => self.filtered_files(&proc.open_files).len(),
             DetailTab::Network => proc
                 .open_files
                 .iter()
@@ -246,8 +770,13 @@ impl AppState {
                 })
                 .count(),
             DetailTab::FileTree => {
-                // Approximate: directories + files
-                proc.open_files.len() + 10
+                // One row per directory plus one per file.
+                let dirs: HashSet<String> = proc
+                    .open_files
+                    .iter()
+                    .map(|f| tree_dir_key(&f.name))
+                    .collect();
+                dirs.len() + proc.open_files.len()
             }
             DetailTab::Summary => 0,
         }
@@ -258,7 +787,7 @@ impl AppState {
         match self.detail_tab {
             DetailTab::OpenFiles => {
                 let idx = self.file_table_state.selected()?;
-                let file = open_files.get(idx)?;
+                let file = *self.filtered_files(open_files).get(idx)?;
                 Some(format!(
                     "{}\t{}\t{}\t{}\t{}\t{}",
                     file.fd,
@@ -273,12 +802,78 @@ impl AppState {
         }
     }
 
-    /// Export full process data as formatted text.
+    /// Name (path) of the highlighted file in the OpenFiles tab.
+    pub fn selected_path<'a>(&self, open_files: &'a [OpenFileInfo]) -> Option<&'a str> {
+        if !matches!(self.detail_tab, DetailTab::OpenFiles) {
+            return None;
+        }
+        let idx = self.file_table_state.selected()?;
+        let file = *self.filtered_files(open_files).get(idx)?;
+        Some(&file.name)
+    }
+
+    /// Path of the highlighted file in the OpenFiles tab, if it can be
+    /// opened in a pager (see [`openable_path`]).
+    pub fn selected_openable_path(&self, open_files: &[OpenFileInfo]) -> Option<PathBuf> {
+        if !matches!(self.detail_tab, DetailTab::OpenFiles) {
+            return None;
+        }
+        let idx = self.file_table_state.selected()?;
+        let file = *self.filtered_files(open_files).get(idx)?;
+        openable_path(file).map(Path::to_path_buf)
+    }
+
+    /// Open the export format menu over the detail view.
+    pub fn open_export_menu(&mut self) {
+        if self.selected_process.is_some() {
+            self.mode = ViewMode::ExportMenu;
+        }
+    }
+
+    /// Close the export menu without exporting.
+    pub fn cancel_export_menu(&mut self) {
+        self.mode = ViewMode::Detail;
+    }
+
+    /// Move the export menu highlight, clamped to the first and last entry.
+    fn export_move(&mut self, delta: i32) {
+        let all = ExportFormat::ALL;
+        let current = all
+            .iter()
+            .position(|f| *f == self.export_format)
+            .unwrap_or(0);
+        let next = (current as i32 + delta).clamp(0, all.len() as i32 - 1);
+        self.export_format = all[next as usize];
+    }
+
+    /// Export the selected process in the highlighted format and quit, so
+    /// the data is printed once the terminal is restored.
+    pub fn confirm_export(&mut self) {
+        if let Some(ref proc) = self.selected_process {
+            self.export_data = Some(self.export_process_data(proc, &proc.open_files));
+            self.should_quit = true;
+        }
+        self.mode = ViewMode::Detail;
+    }
+
+    /// Export full process data in `export_format`: plain text, or the
+    /// `--json` object / CSV rows that `OutputFormatter` produces.
     pub fn export_process_data(
         &self,
         process: &ProcessInfo,
         open_files: &[OpenFileInfo],
     ) -> String {
+        if self.export_format != ExportFormat::Plain {
+            let process = ProcessInfo {
+                open_files: open_files.to_vec(),
+                ..process.clone()
+            };
+            let formatter = OutputFormatter::default();
+            return match self.export_format {
+                ExportFormat::Json => formatter.process_json(&process),
+                _ => formatter.process_csv(&process),
+            };
+        }
         let mut out = String::new();
         out.push_str(&format!("PID: {}\n", process.pid));
         out.push_str(&format!("COMMAND: {}\n", process.comm));
@@ -303,10 +898,33 @@ impl AppState {
     }
 }
 
+/// The path of `file` if it is a regular file we can hand to `$PAGER`: it
+/// must be `REG`, have an absolute name, and still be a regular file on disk
+/// (not deleted, and not a directory, socket or FIFO now living at that path).
+pub fn openable_path(file: &OpenFileInfo) -> Option<&Path> {
+    if file.file_type != FileType::Reg {
+        return None;
+    }
+    let path = Path::new(&file.name);
+    (path.is_absolute() && path.is_file()).then_some(path)
+}
+
+/// The directory row a file is grouped under in the file tree: its parent
+/// directory, `/` for top-level files, or `(other)` for names that aren't
+/// paths (sockets, pipes).
+pub fn tree_dir_key(name: &str) -> String {
+    match name.rfind('/') {
+        Some(0) => "/".to_string(),
+        Some(pos) => name[..pos].to_string(),
+        None => "(other)".to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::open_file::{FdMode, FdType, FileType, OpenFileInfo};
+    use crate::model::fixtures;
+    use crate::model::open_file::{FdMode, FdType, OpenFileInfo};
     use crate::model::process::ProcessInfo;
 
     fn make_test_file(name: &str) -> OpenFileInfo {
@@ -318,25 +936,263 @@ mod tests {
             node: "12345".into(),
             name: name.into(),
             mode: Some(FdMode::Read),
-            link_target: None,
-            send_queue: None,
-            recv_queue: None,
+            ..Default::default()
         }
     }
 
     fn make_test_process() -> ProcessInfo {
         ProcessInfo {
-            pid: 1234,
             command: "test-cmd".into(),
-            comm: "test".into(),
-            user: "root".into(),
-            uid: 0,
             ppid: Some(1),
-            pgid: None,
-            open_files: vec![],
+            ..fixtures::process(1234, "test", Vec::new())
         }
     }
 
+    #[test]
+    fn test_selected_path_is_the_file_name() {
+        let mut app = AppState::new(vec![make_test_process()]);
+        app.select_current();
+        let files = vec![
+            make_test_file("/etc/hosts"),
+            make_test_file("/var/log/app log.txt"),
+        ];
+        app.file_table_state.select(Some(1));
+        assert_eq!(app.selected_path(&files), Some("/var/log/app log.txt"));
+        app.detail_tab = DetailTab::Summary;
+        assert_eq!(app.selected_path(&files), None);
+    }
+
+    #[test]
+    fn test_move_bottom_selects_last_row_in_each_view() {
+        use crate::model::FileType;
+
+        let procs: Vec<ProcessInfo> = (1..=5)
+            .map(|pid| ProcessInfo {
+                pid,
+                ..make_test_process()
+            })
+            .collect();
+        let mut app = AppState::new(procs);
+        app.move_bottom();
+        assert_eq!(app.list_state.selected(), Some(4));
+        app.move_top();
+        assert_eq!(app.list_state.selected(), Some(0));
+
+        app.select_current();
+        let mut socket = make_test_file("10.0.0.1:80");
+        socket.file_type = FileType::IPv4;
+        app.selected_process.as_mut().unwrap().open_files = vec![
+            make_test_file("/etc/hosts"),
+            make_test_file("/etc/passwd"),
+            make_test_file("/tmp/a"),
+            socket,
+        ];
+
+        app.detail_tab = DetailTab::OpenFiles;
+        app.move_bottom();
+        assert_eq!(app.file_table_state.selected(), Some(3));
+
+        app.detail_tab = DetailTab::Network;
+        app.move_bottom();
+        assert_eq!(app.file_table_state.selected(), Some(0));
+
+        // Rows: /etc/, hosts, passwd, /tmp/, a, (other)/, the socket.
+        app.detail_tab = DetailTab::FileTree;
+        app.move_bottom();
+        assert_eq!(app.tree_list_state.selected(), Some(6));
+        app.move_top();
+        assert_eq!(app.tree_list_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_compare_files_by_size_and_name() {
+        use crate::scan::compare_files;
+        use std::cmp::Ordering;
+
+        let file = |name: &str, size| OpenFileInfo {
+            size_off: size,
+            ..make_test_file(name)
+        };
+        let (small, unknown, big) = (file("/b", Some(10)), file("/c", None), file("/a", Some(99)));
+        assert_eq!(
+            compare_files(&unknown, &small, FileSortKey::Size, false),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_files(&big, &small, FileSortKey::Size, false),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_files(&unknown, &file("/d", Some(0)), FileSortKey::Size, false),
+            Ordering::Equal
+        );
+        assert_eq!(
+            compare_files(&big, &small, FileSortKey::Name, false),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_files(&small, &unknown, FileSortKey::Name, false),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_sort_keeps_highlighted_file() {
+        let mut app = AppState::new(vec![make_test_process()]);
+        app.select_current();
+        app.selected_process.as_mut().unwrap().open_files = vec![
+            make_test_file("/b"),
+            make_test_file("/c"),
+            make_test_file("/a"),
+        ];
+        app.file_table_state.select(Some(1));
+
+        app.sort_files_by(FileSortKey::Name);
+        let names = |app: &AppState| -> Vec<String> {
+            let proc = app.selected_process.as_ref().unwrap();
+            app.filtered_files(&proc.open_files)
+                .iter()
+                .map(|f| f.name.clone())
+                .collect()
+        };
+        assert_eq!(names(&app), vec!["/a", "/b", "/c"]);
+        assert_eq!(app.file_table_state.selected(), Some(2));
+
+        app.sort_files_by(FileSortKey::Name);
+        assert_eq!(names(&app), vec!["/c", "/b", "/a"]);
+        assert_eq!(app.file_table_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_empty_network_tab_has_no_selection() {
+        let mut app = AppState::new(vec![make_test_process()]);
+        app.select_current();
+        app.selected_process.as_mut().unwrap().open_files = vec![make_test_file("/etc/hosts")];
+
+        app.next_tab();
+        assert!(matches!(app.detail_tab, DetailTab::Network));
+        assert_eq!(app.file_table_state.selected(), None);
+        app.move_down();
+        assert_eq!(app.file_table_state.selected(), None);
+
+        app.prev_tab();
+        assert_eq!(app.file_table_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn test_refresh_resyncs_selected_process_by_pid() {
+        use crate::platform::mock::MockProvider;
+
+        let mut app = AppState::new(vec![make_test_process()]);
+        app.select_current();
+        app.selected_process.as_mut().unwrap().open_files = vec![make_test_file("/tmp/a")];
+
+        let mut changed = make_test_process();
+        changed.user = "nobody".into();
+        changed.ppid = Some(42);
+        let other = ProcessInfo {
+            pid: 7,
+            ..make_test_process()
+        };
+        app.refresh(&MockProvider::new(vec![other, changed]));
+
+        let proc = app.selected_process.as_ref().unwrap();
+        assert_eq!(proc.pid, 1234);
+        assert_eq!(proc.user, "nobody");
+        assert_eq!(proc.ppid, Some(42));
+        assert_eq!(proc.open_files.len(), 1);
+        assert!(app.detail_banner.is_none());
+
+        app.refresh(&MockProvider::new(vec![]));
+        assert_eq!(app.selected_process.as_ref().unwrap().user, "nobody");
+        assert_eq!(
+            app.detail_banner.as_deref(),
+            Some("Process 1234 has exited")
+        );
+    }
+
+    #[test]
+    fn test_empty_process_list_is_navigable() {
+        let mut app = AppState::new(vec![]);
+        assert!(app.empty_message().is_some());
+        app.move_down();
+        app.move_up();
+        app.page_down();
+        app.select_current();
+        assert!(app.selected_process.is_none());
+        assert!(matches!(app.mode, ViewMode::Search));
+        app.search_input = "x".into();
+        app.update_filter();
+        assert_eq!(app.list_state.selected(), None);
+
+        assert!(AppState::new(vec![make_test_process()])
+            .empty_message()
+            .is_none());
+    }
+
+    #[test]
+    fn test_filter_expr_narrows_list() {
+        use crate::platform::mock::MockProvider;
+
+        let mut web = make_test_process();
+        web.pid = 10;
+        web.user = "www".into();
+        let mut tls = make_test_file("10.0.0.1:443 -> 10.0.0.2:5000 (ESTABLISHED)");
+        tls.file_type = FileType::IPv4;
+        let root = make_test_process();
+        let mut root_with_tls = root.clone();
+        root_with_tls.open_files = vec![tls];
+        let provider = MockProvider::new(vec![web.clone(), root_with_tls]);
+
+        let mut app = AppState::new(vec![web, root]);
+        app.start_filter_input();
+        app.filter_expr = "user:root".into();
+        app.apply_filter_expr(&provider);
+        assert_eq!(app.match_count, 1);
+        assert!(!app.files_loaded);
+
+        app.filter_expr = "user:root port:443".into();
+        app.apply_filter_expr(&provider);
+        assert!(app.files_loaded);
+        assert_eq!(app.highlighted_process().unwrap().pid, 1234);
+
+        // A half-typed token keeps the previous filter.
+        app.filter_expr.push_str(" typ");
+        app.apply_filter_expr(&provider);
+        assert!(app.filter_error.is_some());
+        assert_eq!(app.match_count, 1);
+
+        app.cancel_filter_input();
+        assert_eq!(app.match_count, 2);
+        assert!(matches!(app.mode, ViewMode::Search));
+    }
+
+    #[test]
+    fn test_fuzzy_ties_ordered_by_pid() {
+        let mut high = make_test_process();
+        high.pid = 2001;
+        high.comm = "nginx".into();
+        let mut low = make_test_process();
+        low.pid = 1001;
+        low.comm = "nginx".into();
+
+        let mut app = AppState::new(vec![high.clone(), low.clone()]);
+        app.search_input = "nginx".into();
+        app.update_filter();
+        let pids: Vec<u32> = app
+            .filtered_indices
+            .iter()
+            .map(|&i| app.all_processes[i].pid)
+            .collect();
+        assert_eq!(pids, vec![1001, 2001]);
+
+        // Same result regardless of discovery order.
+        let mut app = AppState::new(vec![low, high]);
+        app.search_input = "nginx".into();
+        app.update_filter();
+        assert_eq!(app.all_processes[app.filtered_indices[0]].pid, 1001);
+    }
+
     #[test]
     fn test_export_process_data() {
         let app = AppState::new(vec![]);
@@ -351,6 +1207,57 @@ mod tests {
         assert!(data.contains("Open Files (1):"));
     }
 
+    #[test]
+    fn test_export_formats() {
+        let mut app = AppState::new(vec![]);
+        let process = make_test_process();
+        let files = vec![make_test_file("/tmp/a,b.txt")];
+
+        app.export_format = ExportFormat::Plain;
+        let plain = app.export_process_data(&process, &files);
+        assert!(plain.starts_with("PID: 1234\n"));
+        assert!(plain.contains("FD\tTYPE\tDEVICE\tSIZE/OFF\tNODE\tNAME\n"));
+
+        app.export_format = ExportFormat::Json;
+        let json = app.export_process_data(&process, &files);
+        assert!(json.starts_with("{\"pid\":1234,\"ppid\":1,\"command\":\"test\""));
+        assert!(json.contains("\"files\":[{\"fd\":\"1r\""));
+        assert!(json.contains("\"name\":\"/tmp/a,b.txt\""));
+
+        app.export_format = ExportFormat::Csv;
+        let csv = app.export_process_data(&process, &files);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "PID,COMMAND,USER,FD,TYPE,DEVICE,SIZE/OFF,NODE,NAME"
+        );
+        assert_eq!(
+            lines[1],
+            "1234,test,root,1r,REG,\"1,5\",1024,12345,\"/tmp/a,b.txt\""
+        );
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn test_export_menu_picks_format() {
+        let mut app = AppState::new(vec![make_test_process()]);
+        app.open_export_menu();
+        assert!(matches!(app.mode, ViewMode::Search));
+
+        app.select_current();
+        app.open_export_menu();
+        assert!(matches!(app.mode, ViewMode::ExportMenu));
+        app.move_down();
+        app.move_down();
+        app.move_down();
+        assert_eq!(app.export_format, ExportFormat::Csv);
+        app.move_up();
+        app.confirm_export();
+        assert!(app.export_data.unwrap().starts_with("{\"pid\":1234"));
+        assert!(app.should_quit);
+        assert!(matches!(app.mode, ViewMode::Detail));
+    }
+
     #[test]
     fn test_export_process_data_no_ppid() {
         let app = AppState::new(vec![]);
@@ -377,6 +1284,29 @@ mod tests {
         assert!(line.contains("12345"));
     }
 
+    #[test]
+    fn test_openable_path_only_for_existing_regular_files() {
+        let dir = std::env::temp_dir().join(format!("loof-open-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("notes.txt");
+        std::fs::write(&file, "hi").unwrap();
+
+        let reg = make_test_file(file.to_str().unwrap());
+        assert_eq!(openable_path(&reg), Some(file.as_path()));
+
+        // A REG entry whose path is now a directory, or was deleted.
+        assert_eq!(openable_path(&make_test_file(dir.to_str().unwrap())), None);
+        let deleted = make_test_file(&format!("{} (deleted)", file.display()));
+        assert_eq!(openable_path(&deleted), None);
+        assert_eq!(openable_path(&make_test_file("notes.txt")), None);
+
+        let mut sock = make_test_file(file.to_str().unwrap());
+        sock.file_type = FileType::Unix;
+        assert_eq!(openable_path(&sock), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_yank_selected_line_no_selection() {
         let mut app = AppState::new(vec![]);
@@ -397,6 +1327,119 @@ mod tests {
         assert!(line.is_none());
     }
 
+    #[test]
+    fn test_debounce_waits_for_delay() {
+        let start = Instant::now();
+        let mut gate = Debounce::new(Duration::from_millis(100));
+        gate.request(1, start);
+        assert_eq!(gate.poll(start + Duration::from_millis(50)), None);
+        // A new request restarts the delay.
+        gate.request(2, start + Duration::from_millis(60));
+        assert_eq!(gate.poll(start + Duration::from_millis(120)), None);
+        assert_eq!(gate.poll(start + Duration::from_millis(160)), Some(2));
+        // Once fired, the gate stays quiet until the next request.
+        assert_eq!(gate.poll(start + Duration::from_millis(500)), None);
+    }
+
+    #[test]
+    fn test_status_message_expires_on_tick() {
+        let mut app = AppState::new(vec![]);
+        app.set_status("Yanked", StatusKind::Info);
+        let set_at = app.status_message.as_ref().unwrap().1;
+
+        app.expire_status(set_at + STATUS_TTL - Duration::from_millis(1));
+        assert_eq!(
+            app.status_message
+                .as_ref()
+                .map(|(m, _, k)| (m.as_str(), *k)),
+            Some(("Yanked", StatusKind::Info))
+        );
+        app.expire_status(set_at + STATUS_TTL);
+        assert!(app.status_message.is_none());
+    }
+
+    #[test]
+    fn test_type_filter_predicate() {
+        assert!(TypeFilter::Regular.matches(&FileType::Reg));
+        assert!(!TypeFilter::Regular.matches(&FileType::Dir));
+        assert!(TypeFilter::Directory.matches(&FileType::Dir));
+        assert!(TypeFilter::Socket.matches(&FileType::Unix));
+        assert!(TypeFilter::Socket.matches(&FileType::IPv4));
+        assert!(TypeFilter::Network.matches(&FileType::IPv6));
+        assert!(!TypeFilter::Network.matches(&FileType::Unix));
+    }
+
+    #[test]
+    fn test_toggle_type_filter_same_key_clears() {
+        let mut app = AppState::new(vec![]);
+        app.toggle_type_filter(TypeFilter::Network);
+        assert_eq!(app.type_filter, Some(TypeFilter::Network));
+        app.toggle_type_filter(TypeFilter::Regular);
+        assert_eq!(app.type_filter, Some(TypeFilter::Regular));
+        app.toggle_type_filter(TypeFilter::Regular);
+        assert_eq!(app.type_filter, None);
+    }
+
+    #[test]
+    fn test_yank_honors_type_filter() {
+        let mut app = AppState::new(vec![]);
+        app.type_filter = Some(TypeFilter::Directory);
+        app.file_table_state.select(Some(0));
+        let mut dir = make_test_file("/var/lib");
+        dir.file_type = FileType::Dir;
+        let files = vec![make_test_file("/tmp/test.txt"), dir];
+        let line = app.yank_selected_line(&files).unwrap();
+        assert!(line.contains("/var/lib"));
+    }
+
+    #[test]
+    fn test_refresh_selected_updates_files_in_place() {
+        use crate::platform::mock::MockProvider;
+
+        let mut live = make_test_process();
+        live.open_files = vec![
+            make_test_file("/tmp/a"),
+            make_test_file("/tmp/b"),
+            make_test_file("/tmp/c"),
+        ];
+        let provider = MockProvider::new(vec![live]);
+
+        let mut app = AppState::new(vec![make_test_process()]);
+        app.select_current();
+        app.selected_process.as_mut().unwrap().open_files = vec![make_test_file("/tmp/a")];
+        app.detail_tab = DetailTab::OpenFiles;
+        app.file_table_state.select(Some(0));
+
+        app.refresh_selected(&provider);
+        let proc = app.selected_process.as_ref().unwrap();
+        assert_eq!(proc.open_files.len(), 3);
+        assert!(matches!(app.detail_tab, DetailTab::OpenFiles));
+        assert_eq!(app.file_table_state.selected(), Some(0));
+        assert!(app.detail_banner.is_none());
+
+        // The process exits: files are kept, a banner explains why.
+        app.file_table_state.select(Some(2));
+        let gone = MockProvider::new(vec![]);
+        app.refresh_selected(&gone);
+        assert_eq!(app.selected_process.as_ref().unwrap().open_files.len(), 3);
+        assert_eq!(app.file_table_state.selected(), Some(2));
+        assert_eq!(
+            app.detail_banner.as_deref(),
+            Some("Process 1234 has exited")
+        );
+    }
+
+    #[test]
+    fn test_toggle_full_command_switches_display() {
+        let mut app = AppState::new(vec![]);
+        let proc = make_test_process();
+        assert_eq!(app.display_command(&proc), "test");
+        app.toggle_full_command();
+        assert_eq!(app.display_command(&proc), "test-cmd");
+        app.toggle_full_command();
+        assert_eq!(app.display_command(&proc), "test");
+    }
+
     #[test]
     fn test_export_data_field_default_none() {
         let app = AppState::new(vec![]);
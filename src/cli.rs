@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -7,7 +7,7 @@ use clap::Parser;
     about = "A modern lsof replacement with interactive TUI"
 )]
 pub struct CliArgs {
-    /// Select by PID (comma-separated, prefix ^ to exclude)
+    /// Select by PID (comma-separated, A-B ranges, prefix ^ to exclude)
     #[arg(short = 'p', value_name = "PID")]
     pub pid: Option<String>,
 
@@ -15,14 +15,42 @@ pub struct CliArgs {
     #[arg(short = 'i', value_name = "SPEC", num_args = 0..=1, default_missing_value = "")]
     pub inet: Option<String>,
 
+    /// Restrict network output to IPv4 (implies -i)
+    #[arg(short = '4', conflicts_with = "ipv6")]
+    pub ipv4: bool,
+
+    /// Restrict network output to IPv6 (implies -i)
+    #[arg(short = '6')]
+    pub ipv6: bool,
+
     /// Select by user (comma-separated, prefix ^ to exclude)
     #[arg(short = 'u', value_name = "USER")]
     pub user: Option<String>,
 
-    /// Select by command name (prefix match, prefix ^ to exclude)
+    /// Select by command name (prefix match unless --command-exact, prefix ^ to exclude)
     #[arg(short = 'c', value_name = "CMD")]
     pub command: Option<String>,
 
+    /// Make -c match the whole command name (-c ssh then skips sshd)
+    #[arg(long = "command-exact", requires = "command")]
+    pub command_exact: bool,
+
+    /// Select by exact command name (comma-separated, prefix ^ to exclude)
+    #[arg(long = "name", value_name = "NAME", conflicts_with = "command")]
+    pub name: Option<String>,
+
+    /// Keep command lines exactly as argv (NULs shown as spaces) instead of collapsing whitespace
+    #[arg(long = "argv-raw")]
+    pub argv_raw: bool,
+
+    /// Select processes with an ESTABLISHED TCP connection to this remote HOST:PORT
+    #[arg(long = "connected-to", value_name = "HOST:PORT")]
+    pub connected_to: Option<String>,
+
+    /// Select by command-line argument substring (repeatable, prefix ^ to exclude)
+    #[arg(long = "arg", value_name = "SUBSTR", allow_hyphen_values = true)]
+    pub arg: Vec<String>,
+
     /// Terse output: PIDs only
     #[arg(short = 't')]
     pub terse: bool,
@@ -39,6 +67,10 @@ pub struct CliArgs {
     #[arg(short = 'a')]
     pub and_mode: bool,
 
+    /// Color theme for the TUI
+    #[arg(long = "theme", value_enum, default_value_t = ThemeName::Dark)]
+    pub theme: ThemeName,
+
     /// Enter interactive TUI mode
     #[arg(short = 'I', long = "interactive")]
     pub interactive: bool,
@@ -55,10 +87,22 @@ pub struct CliArgs {
     #[arg(short = 'F', value_name = "FIELDS")]
     pub field_output: Option<String>,
 
+    /// Character ending each -F field instead of newline (e.g. '\t')
+    #[arg(long = "field-sep", value_name = "CHAR", value_parser = parse_separator, requires = "field_output")]
+    pub field_sep: Option<char>,
+
+    /// Character written after each process's -F fields (e.g. '\n')
+    #[arg(long = "record-sep", value_name = "CHAR", value_parser = parse_separator, requires = "field_output")]
+    pub record_sep: Option<char>,
+
     /// Repeat mode interval in seconds
     #[arg(short = 'r', value_name = "SECONDS")]
     pub repeat: Option<u64>,
 
+    /// Add a random 0..=MS millisecond offset to each -r sleep
+    #[arg(long = "jitter", value_name = "MS", default_value_t = 0)]
+    pub jitter: u64,
+
     /// FD set filter
     #[arg(short = 'd', value_name = "FD")]
     pub fd_filter: Option<String>,
@@ -67,26 +111,86 @@ pub struct CliArgs {
     #[arg(long = "dir-tree", value_name = "DIR")]
     pub dir_tree: Option<String>,
 
+    /// With +D, walk DIR first and report processes holding any path found in it
+    #[arg(long = "walk", requires = "dir_tree")]
+    pub walk: bool,
+
+    /// Select processes whose working directory is under DIR
+    #[arg(
+        long = "cwd-under",
+        value_name = "DIR",
+        conflicts_with_all = ["top", "count_only", "zero_fds"]
+    )]
+    pub cwd_under: Option<String>,
+
     /// Search directory non-recursively (+d)
     #[arg(long = "dir", value_name = "DIR")]
     pub dir: Option<String>,
 
-    /// Command name width (+c)
+    /// Command name width (+c); 0 means no truncation
     #[arg(long = "cmd-width", value_name = "WIDTH")]
     pub cmd_width: Option<usize>,
 
-    /// Suppress warnings
+    /// Cut the NAME column to WIDTH terminal columns (plain output only)
+    #[arg(long = "max-name-width", value_name = "WIDTH")]
+    pub max_name_width: Option<usize>,
+
+    /// Print DEVICE as hex 0xMAJ,MIN instead of decimal major,minor
+    #[arg(long = "hex-device")]
+    pub hex_device: bool,
+
+    /// Suppress warnings (unreadable processes, empty results)
     #[arg(short = 'w')]
     pub suppress_warnings: bool,
 
-    /// Select by process group ID (comma-separated, prefix ^ to exclude)
+    /// Select by process group ID (comma-separated, A-B ranges, prefix ^ to exclude)
     #[arg(short = 'g', value_name = "PGID")]
     pub pgid: Option<String>,
 
+    /// Select the process whose PID inside its container/namespace is N
+    #[arg(long = "nspid", value_name = "N")]
+    pub nspid: Option<u32>,
+
+    /// Show the namespace-local PID next to the host PID (host/ns)
+    #[arg(long = "show-nspid")]
+    pub show_nspid: bool,
+
+    /// List loof's own process (the default)
+    #[arg(long = "include-self", overrides_with = "exclude_self")]
+    pub include_self: bool,
+
+    /// Leave loof's own process out of the results
+    #[arg(long = "exclude-self", overrides_with = "include_self")]
+    pub exclude_self: bool,
+
+    /// Select processes by scheduler state letters (e.g. Z for zombies, Z,T)
+    #[arg(long = "state-proc", value_name = "STATES")]
+    pub state_proc: Option<String>,
+
+    /// Select processes in the container with this ID (or ID prefix)
+    #[arg(long = "container", value_name = "ID")]
+    pub container: Option<String>,
+
+    /// Show each process's SELinux/SMACK security context in a CONTEXT column
+    #[arg(long = "context")]
+    pub context: bool,
+
+    /// Select processes whose security context contains this text (e.g. httpd_t)
+    #[arg(long = "context-filter", value_name = "TEXT")]
+    pub context_filter: Option<String>,
+
+    /// Show extra columns (STATE, CONTAINER)
+    #[arg(long = "wide")]
+    pub wide: bool,
+
     /// File size filter (prefix: +=greater, -=less, exact match)
     #[arg(short = 's', value_name = "SIZE")]
     pub size_filter: Option<String>,
 
+    /// Select files accessed within a duration (e.g. 30s, 10m, 2h, 1d)
+    #[arg(long = "accessed-within", value_name = "DURATION")]
+    pub accessed_within: Option<String>,
+
     /// Avoid kernel blocks (compatibility, no-op)
     #[arg(short = 'b')]
     pub avoid_blocking: bool,
@@ -107,12 +211,254 @@ pub struct CliArgs {
     #[arg(short = 'T', value_name = "INFO", num_args = 0..=1, default_missing_value = "s")]
     pub tcp_info: Option<String>,
 
+    /// Select files on filesystems of the given type (comma-separated, e.g. nfs,tmpfs)
+    #[arg(long = "fstype", value_name = "TYPE")]
+    pub fstype: Option<String>,
+
+    /// Only list regular files (no sockets, pipes, devices or directories)
+    #[arg(long = "only-files")]
+    pub only_files: bool,
+
+    /// Only list files locked (fcntl/flock) through the FD
+    #[arg(long = "locked")]
+    pub locked: bool,
+
+    /// Only list FDs without close-on-exec (inherited by exec'd children)
+    #[arg(long = "no-cloexec")]
+    pub no_cloexec: bool,
+
+    /// Only list files whose path no longer resolves (stat fails)
+    #[arg(long = "missing")]
+    pub missing: bool,
+
+    /// Only list memory-mapped files mapped shared and writable
+    #[arg(long = "shared-writable", conflicts_with = "no_mmap")]
+    pub shared_writable: bool,
+
+    /// Select files on the filesystem mounted at PATH (matched by device)
+    #[arg(long = "mount", value_name = "PATH")]
+    pub mount: Option<String>,
+
+    /// Sort processes by the given key (default: pid)
+    #[arg(long = "sort", value_name = "KEY", conflicts_with = "no_sort")]
+    pub sort: Option<SortKey>,
+
+    /// Order each process's files by size, fd, name, type or node
+    #[arg(long = "sort-files", value_name = "KEY")]
+    pub sort_files: Option<FileSortKey>,
+
+    /// Keep processes and files in discovery order
+    #[arg(long = "no-sort")]
+    pub no_sort: bool,
+
+    /// Show which processes listen on and which connect to a TCP port
+    #[arg(long = "port", value_name = "PORT")]
+    pub port: Option<u16>,
+
+    /// Custom per-file output template, e.g. '{pid} {comm} {fd} {name}'
+    #[arg(long = "format", value_name = "TEMPLATE")]
+    pub format: Option<String>,
+
+    /// Drop processes whose PID was reused while their files were read
+    #[arg(long = "strict")]
+    pub strict: bool,
+
+    /// Output JSON instead of lsof-style columns
+    #[arg(long = "json")]
+    pub json: bool,
+
+    /// With --json, print the bare process array without the versioned wrapper
+    #[arg(long = "json-flat", requires = "json")]
+    pub json_flat: bool,
+
+    /// Write each process's listing to <DIR>/<pid>.txt (or .json) instead of stdout
+    #[arg(long = "split-output", value_name = "DIR")]
+    pub split_output: Option<String>,
+
+    /// Exit 0 if anything matched, 1 if nothing did, 2 on error (like grep)
+    #[arg(
+        long = "exit-code-match",
+        conflicts_with_all = [
+            "repeat", "port", "net", "dump_sockets", "dry_run", "top", "count_only", "zero_fds",
+            "min_connections", "leak_scan", "watch_file", "serve", "self_check", "interactive",
+        ]
+    )]
+    pub exit_code_match: bool,
+
+    /// Write the report to FILE (created or truncated) instead of stdout
+    #[arg(
+        long = "output",
+        value_name = "FILE",
+        conflicts_with_all = ["split_output", "serve", "interactive"]
+    )]
+    pub output: Option<String>,
+
+    /// List network connections, followed by a summary of listening ports
+    #[arg(short = 'N', long = "net")]
+    pub net: bool,
+
+    /// With --net, flag TIME_WAIT above this percentage of TCP sockets
+    #[arg(long = "warn-time-wait", value_name = "PCT", default_value_t = 50.0)]
+    pub warn_time_wait: f64,
+
+    /// Show the numeric family/protocol of sockets loof can't decode
+    #[arg(long = "raw-proto")]
+    pub raw_proto: bool,
+
+    /// Show addresses exactly as the kernel reports them (keep ::ffff:a.b.c.d)
+    #[arg(long = "raw-addr")]
+    pub raw_addr: bool,
+
+    /// Experimental: answer each connection on a Unix socket with a JSON scan
+    #[arg(long = "serve", value_name = "PATH")]
+    pub serve: Option<String>,
+
+    /// Show the N processes with the most open files
+    #[arg(long = "top", value_name = "N")]
+    pub top: Option<usize>,
+
+    /// Print only per-process FD counts (fast: no open-file details)
+    #[arg(long = "count-only", conflicts_with = "top")]
+    pub count_only: bool,
+
+    /// List processes with no open numbered FDs (often zombies or stuck)
+    #[arg(long = "zero-fds", conflicts_with_all = ["top", "count_only"])]
+    pub zero_fds: bool,
+
+    /// Report processes holding at least N network sockets (connection leaks)
+    #[arg(long = "min-connections", value_name = "N", conflicts_with_all = ["top", "count_only"])]
+    pub min_connections: Option<usize>,
+
+    /// With --min-connections, count only TCP sockets in this state (e.g. CLOSE_WAIT)
+    #[arg(long = "state", value_name = "STATE", requires = "min_connections")]
+    pub tcp_state: Option<String>,
+
+    /// Report how many processes match and roughly how many files a scan would read, without reading them
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+
+    /// Sample open files twice and report FDs that look leaked
+    #[arg(long = "leak-scan")]
+    pub leak_scan: bool,
+
+    /// Seconds between the two --leak-scan samples
+    #[arg(long = "leak-interval", value_name = "SECONDS", default_value_t = 5)]
+    pub leak_interval: u64,
+
+    /// Print the processes holding PATH open; with --follow, alert on changes
+    #[arg(long = "watch-file", value_name = "PATH")]
+    pub watch_file: Option<String>,
+
+    /// With --watch-file, keep scanning (every -r seconds, default 1)
+    #[arg(long = "follow", requires = "watch_file")]
+    pub follow: bool,
+
+    /// Skip memory-mapped files (mem entries) for a faster scan
+    #[arg(long = "no-mmap")]
+    pub no_mmap: bool,
+
+    /// Merge entries for the same file (e.g. a mem mapping also open as an FD)
+    #[arg(long = "dedup")]
+    pub dedup: bool,
+
+    /// Omit the rtd entry of processes whose root directory is /
+    #[arg(long = "hide-root")]
+    pub hide_root: bool,
+
+    /// Prefix paths of chroot'd processes that lie outside their root with it
+    #[arg(long = "prefix-root")]
+    pub prefix_root: bool,
+
+    /// Prefix names of files on NFS mounts with "nfs:"
+    #[arg(long = "mark-nfs")]
+    pub mark_nfs: bool,
+
+    /// Print timing spans for the scan to stderr (also enabled by RUST_LOG)
+    #[cfg(feature = "trace")]
+    #[arg(long = "trace")]
+    pub trace: bool,
+
+    /// List the values of the TYPE column and exit
+    #[arg(long = "list-types")]
+    pub list_types: bool,
+
+    /// List the forms of the FD column and exit
+    #[arg(long = "list-fd-types")]
+    pub list_fd_types: bool,
+
+    /// List the -F field characters and exit
+    #[arg(long = "fields-help")]
+    pub fields_help: bool,
+
+    /// Print a JSON description of the platform, features and output formats
+    #[arg(long = "capabilities")]
+    pub capabilities: bool,
+
+    /// Dump the socket inode table reported by the OS (debugging)
+    #[arg(long = "dump-sockets", hide = true)]
+    pub dump_sockets: bool,
+
+    /// Check that the provider reads this process's own open files correctly
+    #[arg(long = "self-check")]
+    pub self_check: bool,
+
     /// Positional: file names to search for
     pub names: Vec<String>,
 }
 
+/// Process ordering keys accepted by `--sort`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Pid,
+    Command,
+    User,
+    Fds,
+}
+
+/// Per-process file ordering keys accepted by `--sort-files`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum FileSortKey {
+    /// Largest first; files without a size go last.
+    Size,
+    Fd,
+    Name,
+    Type,
+    /// Inode, numerically where it is a number.
+    Node,
+}
+
+/// TUI color presets accepted by `--theme`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum ThemeName {
+    Dark,
+    Light,
+    Mono,
+}
+
+/// Parse a `--field-sep`/`--record-sep` value: one character, or one of the
+/// escapes `\t`, `\n`, `\0`.
+fn parse_separator(s: &str) -> Result<char, String> {
+    match s {
+        "\\t" => Ok('\t'),
+        "\\n" => Ok('\n'),
+        "\\0" => Ok('\0'),
+        _ => {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(format!(
+                    "expected one character or \\t, \\n, \\0, got '{}'",
+                    s
+                )),
+            }
+        }
+    }
+}
+
 /// Preprocess command-line arguments to convert lsof-style `+` prefix flags
-/// into clap-compatible `--long` flags before parsing.
+/// into clap-compatible `--long` flags before parsing. The value may be the
+/// next argument or glued on (`+c15`, `+D/tmp`). argv[0] is kept as-is, so
+/// running loof through an `lsof` symlink or by full path changes nothing.
 ///
 /// Conversions:
 ///   +D DIR   -> --dir-tree DIR
@@ -128,26 +474,26 @@ pub fn preprocess_args(args: Vec<String>) -> Vec<String> {
     }
 
     while let Some(arg) = iter.next() {
-        match arg.as_str() {
-            "+D" => {
-                result.push("--dir-tree".to_string());
-                if let Some(val) = iter.next() {
-                    result.push(val);
-                }
-            }
-            "+d" => {
-                result.push("--dir".to_string());
-                if let Some(val) = iter.next() {
-                    result.push(val);
-                }
-            }
-            "+c" => {
-                result.push("--cmd-width".to_string());
-                if let Some(val) = iter.next() {
+        let plus = arg.strip_prefix('+').and_then(|rest| {
+            let mut chars = rest.chars();
+            let long = match chars.next()? {
+                'D' => "--dir-tree",
+                'd' => "--dir",
+                'c' => "--cmd-width",
+                _ => return None,
+            };
+            Some((long, chars.as_str().to_string()))
+        });
+        match plus {
+            Some((long, glued)) => {
+                result.push(long.to_string());
+                if !glued.is_empty() {
+                    result.push(glued);
+                } else if let Some(val) = iter.next() {
                     result.push(val);
                 }
             }
-            _ => {
+            None => {
                 result.push(arg);
             }
         }
@@ -181,6 +527,39 @@ mod tests {
         assert_eq!(result, vec!["loof", "--cmd-width", "15"]);
     }
 
+    #[test]
+    fn test_preprocess_glued_plus_flags() {
+        let args = ["loof", "+c15", "+D/tmp", "+d/var/log", "+x"]
+            .map(String::from)
+            .to_vec();
+        let result = preprocess_args(args);
+        assert_eq!(
+            result,
+            vec![
+                "loof",
+                "--cmd-width",
+                "15",
+                "--dir-tree",
+                "/tmp",
+                "--dir",
+                "/var/log",
+                "+x"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preprocess_via_symlink_name() {
+        for prog in ["lsof", "/usr/local/bin/lsof", "./target/debug/loof"] {
+            let args = [prog, "+c", "15", "-p", "1"].map(String::from).to_vec();
+            let result = preprocess_args(args);
+            assert_eq!(result, vec![prog, "--cmd-width", "15", "-p", "1"]);
+            let parsed = CliArgs::try_parse_from(result).unwrap();
+            assert_eq!(parsed.cmd_width, Some(15));
+            assert_eq!(parsed.pid.as_deref(), Some("1"));
+        }
+    }
+
     #[test]
     fn test_preprocess_mixed_args() {
         let args = vec![
@@ -209,4 +588,14 @@ mod tests {
         let result = preprocess_args(args.clone());
         assert_eq!(result, args);
     }
+
+    #[test]
+    fn test_parse_separator_escapes() {
+        assert_eq!(parse_separator("\\t"), Ok('\t'));
+        assert_eq!(parse_separator("\\0"), Ok('\0'));
+        assert_eq!(parse_separator(","), Ok(','));
+        assert_eq!(parse_separator("\t"), Ok('\t'));
+        assert!(parse_separator("ab").is_err());
+        assert!(parse_separator("").is_err());
+    }
 }
@@ -9,7 +9,7 @@ pub enum LoofError {
     #[allow(dead_code)]
     ProcessNotFound(u32),
     #[error("Permission denied: {0}")]
-    #[allow(dead_code)]
+    #[cfg_attr(not(test), allow(dead_code))]
     PermissionDenied(String),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
@@ -1,22 +1,69 @@
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, ToSocketAddrs};
 use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 
 use crate::cli::CliArgs;
 use crate::error::{LoofError, Result};
-use crate::model::{FileType, OpenFileInfo, ProcessInfo};
+use crate::model::{
+    Connection, FdType, FileType, MountInfo, OpenFileInfo, ProcState, ProcessInfo, TcpState,
+};
 
 /// Top-level filter configuration built from CLI arguments.
 #[derive(Debug, Default)]
 pub struct FilterConfig {
     pub pids: Option<PidFilter>,
     pub pgids: Option<PgidFilter>,
+    /// `--nspid`: the namespace-local PID. Applied on top of the other
+    /// process filters regardless of `-a`.
+    pub nspid: Option<u32>,
+    /// `--container`: container ID or ID prefix. Applied like `nspid`.
+    pub container: Option<String>,
+    /// `--context-filter`: text the security context must contain. Applied
+    /// like `nspid`.
+    pub context: Option<String>,
+    /// `--state-proc`: scheduler states to select (e.g. zombies).
+    pub states: Option<Vec<ProcState>>,
+    /// `--cwd-under`: processes whose working directory is in this tree.
+    /// Needs the open files, so it is checked by [`matches_cwd`](Self::matches_cwd)
+    /// after they are read; otherwise applied like `nspid`.
+    pub cwd_under: Option<PathBuf>,
     pub users: Option<UserFilter>,
     pub commands: Option<CommandFilter>,
     pub inet: Option<InetFilter>,
+    /// `--connected-to`: ESTABLISHED TCP sockets whose remote end is this
+    /// endpoint.
+    pub connected_to: Option<RemoteEndpoint>,
     pub dir_tree: Option<PathBuf>,
+    /// `--walk`: every path under `dir_tree`, filled in by
+    /// [`walk_dir_tree`](Self::walk_dir_tree). `dir_tree` then matches
+    /// these paths instead of the name prefix.
+    pub walked: Option<HashSet<String>>,
     pub dir: Option<PathBuf>,
     pub names: Vec<PathBuf>,
     pub and_mode: bool,
     pub size_filter: Option<SizeFilter>,
+    pub fstype: Option<FstypeFilter>,
+    pub mount: Option<MountFilter>,
+    /// `--accessed-within`: files whose atime is at most this long ago.
+    pub accessed_within: Option<Duration>,
+    /// `--only-files`: keep regular files only. Applied on top of the other
+    /// file filters regardless of `-a`.
+    pub only_files: bool,
+    /// `--locked`: keep files with a lock held through the FD. Applied like
+    /// `only_files`.
+    pub locked: bool,
+    /// `--no-cloexec`: keep FDs without close-on-exec, i.e. those a child
+    /// would inherit across exec. Applied like `only_files`.
+    pub no_cloexec: bool,
+    /// `--shared-writable`: keep memory-mapped files mapped shared and
+    /// writable, whose changes go straight to the file. Applied like
+    /// `only_files`.
+    pub shared_writable: bool,
+    /// `--missing`: keep files whose path no longer resolves (deleted,
+    /// dangling links, vanished mounts). Applied like `only_files`.
+    pub missing: bool,
+    pub args: Option<ArgFilter>,
 }
 
 /// PID-based filter with include/exclude lists.
@@ -55,11 +102,40 @@ pub struct UserFilter {
     pub exclude: Vec<String>,
 }
 
-/// Command-name filter with include/exclude lists (prefix match).
+/// Command-name filter with include/exclude lists (prefix match, or exact
+/// match for `--name`).
 #[derive(Debug, Default)]
 pub struct CommandFilter {
     pub include: Vec<String>,
     pub exclude: Vec<String>,
+    pub exact: bool,
+}
+
+/// Command-line argument filter (`--arg`, substring match on the full argv).
+#[derive(Debug, Default)]
+pub struct ArgFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+/// Filesystem-type filter (`--fstype`).
+///
+/// The device -> fstype map is filled in from the provider's mount table
+/// with [`FilterConfig::set_mounts`] before matching.
+#[derive(Debug, Default)]
+pub struct FstypeFilter {
+    pub types: Vec<String>,
+    pub devices: HashMap<String, String>,
+}
+
+/// Mount filter (`--mount`): files on the filesystem mounted at `path`,
+/// matched by device so bind-mounted and relative paths are still caught.
+///
+/// `device` is resolved from the mount table by [`FilterConfig::set_mounts`].
+#[derive(Debug, Default)]
+pub struct MountFilter {
+    pub path: String,
+    pub device: Option<String>,
 }
 
 /// Network/inet filter parsed from `-i` spec.
@@ -67,7 +143,7 @@ pub struct CommandFilter {
 pub struct InetFilter {
     /// TCP, UDP, or None for all protocols
     pub protocol: Option<String>,
-    /// Host to match
+    /// Host to match (socket path for `unix`)
     pub host: Option<String>,
     /// Port to match
     pub port: Option<u16>,
@@ -75,60 +151,115 @@ pub struct InetFilter {
     pub ip_version: Option<u8>,
 }
 
+/// Remote endpoint for `--connected-to`: every address the host resolved
+/// to, and the port.
+#[derive(Debug)]
+pub struct RemoteEndpoint {
+    pub addrs: Vec<IpAddr>,
+    pub port: u16,
+}
+
 // ---------------------------------------------------------------------------
 // Parsing helpers
 // ---------------------------------------------------------------------------
 
-/// Parse a PID filter string.
-///
-/// Format: comma-separated PIDs, prefix `^` to exclude.
-/// Examples: "1234,5678", "^1234", "1234,^5678"
-fn parse_pid_filter(s: &str) -> Result<PidFilter> {
-    let mut filter = PidFilter::default();
+/// Largest `A-B` range accepted by `-p`/`-g`; ranges are expanded into
+/// the include/exclude lists, so this bounds memory and match time.
+const MAX_ID_RANGE: u32 = 65_536;
+
+/// Parse a comma-separated list of IDs and inclusive `A-B` ranges, each
+/// optionally prefixed with `^` to exclude, into (include, exclude).
+/// `what` names the ID kind in error messages.
+fn parse_id_list(s: &str, what: &str) -> Result<(Vec<u32>, Vec<u32>)> {
+    let mut include = Vec::new();
+    let mut exclude = Vec::new();
     for token in s.split(',') {
         let token = token.trim();
         if token.is_empty() {
             continue;
         }
-        if let Some(rest) = token.strip_prefix('^') {
-            let pid: u32 = rest
-                .parse()
-                .map_err(|_| LoofError::Parse(format!("invalid PID: {}", rest)))?;
-            filter.exclude.push(pid);
-        } else {
-            let pid: u32 = token
-                .parse()
-                .map_err(|_| LoofError::Parse(format!("invalid PID: {}", token)))?;
-            filter.include.push(pid);
+        let (list, spec) = match token.strip_prefix('^') {
+            Some(rest) => (&mut exclude, rest),
+            None => (&mut include, token),
+        };
+        let parse = |v: &str| {
+            v.trim()
+                .parse::<u32>()
+                .map_err(|_| LoofError::Parse(format!("invalid {}: {}", what, spec)))
+        };
+        match spec.split_once('-') {
+            Some((lo, hi)) => {
+                let (lo, hi) = (parse(lo)?, parse(hi)?);
+                if lo > hi {
+                    return Err(LoofError::Parse(format!(
+                        "invalid {} range {}: start is after end",
+                        what, spec
+                    )));
+                }
+                if hi - lo >= MAX_ID_RANGE {
+                    return Err(LoofError::Parse(format!(
+                        "{} range {} is too large (at most {} IDs)",
+                        what, spec, MAX_ID_RANGE
+                    )));
+                }
+                list.extend(lo..=hi);
+            }
+            None => list.push(parse(spec)?),
         }
     }
-    Ok(filter)
+    Ok((include, exclude))
+}
+
+/// Parse a PID filter string.
+///
+/// Format: comma-separated PIDs or `A-B` ranges, prefix `^` to exclude.
+/// Examples: "1234,5678", "^1234", "1234,^5678", "1000-1010"
+fn parse_pid_filter(s: &str) -> Result<PidFilter> {
+    let (include, exclude) = parse_id_list(s, "PID")?;
+    Ok(PidFilter { include, exclude })
+}
+
+/// Add `pid` to the exclude list of `pids`, creating an exclude-only
+/// filter if there was none (`--exclude-self`).
+fn exclude_pid(pids: &mut Option<PidFilter>, pid: u32) {
+    let filter = pids.get_or_insert_with(PidFilter::default);
+    if !filter.exclude.contains(&pid) {
+        filter.exclude.push(pid);
+    }
 }
 
 /// Parse a PGID filter string.
 ///
-/// Format: comma-separated PGIDs, prefix `^` to exclude.
-/// Examples: "1234,5678", "^1234", "1234,^5678"
+/// Format: comma-separated PGIDs or `A-B` ranges, prefix `^` to exclude.
+/// Examples: "1234,5678", "^1234", "1234,^5678", "^2000-2002"
 fn parse_pgid_filter(s: &str) -> Result<PgidFilter> {
-    let mut filter = PgidFilter::default();
-    for token in s.split(',') {
-        let token = token.trim();
-        if token.is_empty() {
-            continue;
-        }
-        if let Some(rest) = token.strip_prefix('^') {
-            let pgid: u32 = rest
-                .parse()
-                .map_err(|_| LoofError::Parse(format!("invalid PGID: {}", rest)))?;
-            filter.exclude.push(pgid);
-        } else {
-            let pgid: u32 = token
-                .parse()
-                .map_err(|_| LoofError::Parse(format!("invalid PGID: {}", token)))?;
-            filter.include.push(pgid);
-        }
-    }
-    Ok(filter)
+    let (include, exclude) = parse_id_list(s, "PGID")?;
+    Ok(PgidFilter { include, exclude })
+}
+
+/// Parse a `--state-proc` list of `ps`-style state letters.
+///
+/// Format: comma-separated letters from `R S D Z T I` (case-insensitive).
+/// Examples: "Z", "Z,T"
+fn parse_proc_states(s: &str) -> Result<Vec<ProcState>> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|token| {
+            let mut chars = token.chars();
+            let state = match (chars.next(), chars.next()) {
+                (Some(c), None) => ProcState::from_code(c.to_ascii_uppercase()),
+                _ => ProcState::Other('?'),
+            };
+            match state {
+                ProcState::Other(_) => Err(LoofError::Parse(format!(
+                    "invalid process state '{}' (expected one of R, S, D, Z, T, I)",
+                    token
+                ))),
+                state => Ok(state),
+            }
+        })
+        .collect()
 }
 
 /// Parse a size filter string.
@@ -137,10 +268,11 @@ fn parse_pgid_filter(s: &str) -> Result<PgidFilter> {
 ///
 /// Prefix `+` means greater-than, `-` means less-than, no prefix means exact.
 /// Suffixes: K/KB = 1024, M/MB = 1048576, G/GB = 1073741824.
-fn parse_size_filter(s: &str) -> Option<SizeFilter> {
+/// Missing or oversized numbers and unknown suffixes are errors.
+fn parse_size_filter(s: &str) -> Result<SizeFilter> {
     let s = s.trim();
     if s.is_empty() {
-        return None;
+        return Err(LoofError::Parse("empty size filter".to_string()));
     }
 
     let (op, rest) = if let Some(r) = s.strip_prefix('+') {
@@ -157,20 +289,88 @@ fn parse_size_filter(s: &str) -> Option<SizeFilter> {
         .find(|c: char| !c.is_ascii_digit())
         .unwrap_or(rest.len());
     let (num_str, suffix) = rest.split_at(num_end);
-    let base: u64 = num_str.parse().ok()?;
+    let base: u64 = num_str
+        .parse()
+        .map_err(|_| LoofError::Parse(format!("invalid size: {}", s)))?;
 
     let multiplier: u64 = match suffix.to_uppercase().as_str() {
         "" => 1,
         "K" | "KB" => 1024,
         "M" | "MB" => 1_048_576,
         "G" | "GB" => 1_073_741_824,
-        _ => return None,
+        _ => return Err(LoofError::Parse(format!("invalid size suffix: {}", suffix))),
     };
 
-    Some(SizeFilter {
-        op,
-        bytes: base * multiplier,
-    })
+    let bytes = base
+        .checked_mul(multiplier)
+        .ok_or_else(|| LoofError::Parse(format!("size too large: {}", s)))?;
+
+    Ok(SizeFilter { op, bytes })
+}
+
+/// Parse a duration such as `30s`, `10m`, `2h` or `1d`. A bare number
+/// is seconds.
+fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let num_end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num_str, suffix) = s.split_at(num_end);
+    let base: u64 = num_str
+        .parse()
+        .map_err(|_| LoofError::Parse(format!("invalid duration: {}", s)))?;
+    let multiplier: u64 = match suffix {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        _ => {
+            return Err(LoofError::Parse(format!(
+                "invalid duration suffix: {} (expected s, m, h or d)",
+                suffix
+            )))
+        }
+    };
+    base.checked_mul(multiplier)
+        .map(Duration::from_secs)
+        .ok_or_else(|| LoofError::Parse(format!("duration too large: {}", s)))
+}
+
+/// Whether a file accessed at `atime` falls within `window` of `now`.
+/// Unknown access times never match; times in the future (clock skew)
+/// do.
+fn accessed_within(atime: Option<SystemTime>, now: SystemTime, window: Duration) -> bool {
+    match atime {
+        Some(t) => now.duration_since(t).map_or(true, |age| age <= window),
+        None => false,
+    }
+}
+
+/// Warnings for `--accessed-within` on filesystems whose mount options
+/// stop atime from tracking every read: `noatime` anywhere, and
+/// `relatime` on `/` when the window is shorter than its one-day update
+/// interval.
+pub fn atime_warnings(mounts: &[MountInfo], window: Duration) -> Vec<String> {
+    let has = |m: &MountInfo, opt: &str| m.options.iter().any(|o| o == opt);
+    let mut warnings = Vec::new();
+    let noatime: Vec<&str> = mounts
+        .iter()
+        .filter(|m| has(m, "noatime"))
+        .map(|m| m.mount_point.as_str())
+        .collect();
+    if !noatime.is_empty() {
+        warnings.push(format!(
+            "atime is not updated on {} (noatime); --accessed-within can't select files there",
+            noatime.join(", ")
+        ));
+    }
+    let root = mounts.iter().rev().find(|m| m.mount_point == "/");
+    if window < Duration::from_secs(86_400) && root.is_some_and(|m| has(m, "relatime")) {
+        warnings.push(
+            "/ is mounted relatime, so atime changes at most once a day unless the file \
+             is modified; --accessed-within may miss recent reads"
+                .to_string(),
+        );
+    }
+    warnings
 }
 
 /// Parse a user filter string.
@@ -211,105 +411,423 @@ fn parse_command_filter(s: &str) -> CommandFilter {
     filter
 }
 
+/// Parse a `--name` filter: exact command names, comma-separated, prefix
+/// `^` to exclude.
+/// Examples: "nginx", "nginx,php-fpm", "^sshd"
+fn parse_name_filter(s: &str) -> CommandFilter {
+    let mut filter = CommandFilter {
+        exact: true,
+        ..Default::default()
+    };
+    for token in s.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        match token.strip_prefix('^') {
+            Some(rest) => filter.exclude.push(rest.to_string()),
+            None => filter.include.push(token.to_string()),
+        }
+    }
+    filter
+}
+
+/// Parse the repeated `--arg` values.
+///
+/// Each value is an argv substring, prefix `^` to exclude.
+/// Examples: "-Dapp.name=foo", "^--debug"
+fn parse_arg_filter(values: &[String]) -> ArgFilter {
+    let mut filter = ArgFilter::default();
+    for value in values {
+        if value.is_empty() {
+            continue;
+        }
+        if let Some(rest) = value.strip_prefix('^') {
+            filter.exclude.push(rest.to_string());
+        } else {
+            filter.include.push(value.clone());
+        }
+    }
+    filter
+}
+
+/// Parse a filesystem-type filter string.
+///
+/// Format: comma-separated filesystem type names.
+/// Examples: "nfs", "nfs,nfs4,cifs"
+fn parse_fstype_filter(s: &str) -> FstypeFilter {
+    FstypeFilter {
+        types: s
+            .split(',')
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect(),
+        devices: HashMap::new(),
+    }
+}
+
 impl FilterConfig {
     /// Build a `FilterConfig` from parsed CLI arguments.
     pub fn from_cli(args: &CliArgs) -> Result<Self> {
-        let pids = match &args.pid {
+        let mut pids = match &args.pid {
             Some(s) => Some(parse_pid_filter(s)?),
             None => None,
         };
+        if args.exclude_self {
+            exclude_pid(&mut pids, std::process::id());
+        }
 
         let pgids = match &args.pgid {
             Some(s) => Some(parse_pgid_filter(s)?),
             None => None,
         };
 
+        let states = match &args.state_proc {
+            Some(s) => Some(parse_proc_states(s)?),
+            None => None,
+        };
+
         let users = args.user.as_ref().map(|s| parse_user_filter(s));
-        let commands = args.command.as_ref().map(|s| parse_command_filter(s));
-        let inet = args.inet.as_ref().map(|s| parse_inet_filter(s));
-        let size_filter = args.size_filter.as_ref().and_then(|s| parse_size_filter(s));
+        let commands = match (&args.command, &args.name) {
+            (_, Some(s)) => Some(parse_name_filter(s)),
+            (Some(s), None) => Some(CommandFilter {
+                exact: args.command_exact,
+                ..parse_command_filter(s)
+            }),
+            (None, None) => None,
+        };
+        let mut inet = args.inet.as_ref().map(|s| parse_inet_filter(s));
+        // -4/-6 restrict the family on top of any -i spec, or select all
+        // network files of that family on their own.
+        let family = match (args.ipv4, args.ipv6) {
+            (true, _) => Some(4),
+            (_, true) => Some(6),
+            _ => None,
+        };
+        if let Some(ver) = family {
+            inet.get_or_insert_with(InetFilter::default).ip_version = Some(ver);
+        }
+        let size_filter = match &args.size_filter {
+            Some(s) => Some(parse_size_filter(s)?),
+            None => None,
+        };
+        let accessed_within = match &args.accessed_within {
+            Some(s) => Some(parse_duration(s)?),
+            None => None,
+        };
+        let fstype = args.fstype.as_ref().map(|s| parse_fstype_filter(s));
+        let mount = args.mount.as_ref().map(|p| MountFilter {
+            path: normalize_mount_path(p),
+            device: None,
+        });
+        let arg_filter = (!args.arg.is_empty()).then(|| parse_arg_filter(&args.arg));
 
         let dir_tree = args.dir_tree.as_ref().map(PathBuf::from);
         let dir = args.dir.as_ref().map(PathBuf::from);
         let names = args.names.iter().map(PathBuf::from).collect();
 
+        let connected_to = match &args.connected_to {
+            Some(s) => Some(parse_remote_endpoint(s)?),
+            None => None,
+        };
+
         Ok(FilterConfig {
             pids,
             pgids,
+            nspid: args.nspid,
+            container: args.container.clone(),
+            context: args.context_filter.clone(),
+            states,
+            cwd_under: args.cwd_under.as_ref().map(PathBuf::from),
             users,
             commands,
             inet,
+            connected_to,
             dir_tree,
+            walked: None,
             dir,
             names,
             and_mode: args.and_mode,
             size_filter,
+            fstype,
+            mount,
+            accessed_within,
+            only_files: args.only_files,
+            locked: args.locked,
+            no_cloexec: args.no_cloexec,
+            shared_writable: args.shared_writable,
+            missing: args.missing,
+            args: arg_filter,
         })
     }
 
+    /// Parse a TUI filter expression of space-separated `key:value` tokens,
+    /// e.g. `user:root type:IPv4 port:443`. Values use the same syntax as
+    /// the matching CLI flag (commas, `^` to exclude). All tokens must match
+    /// (as with `-a`).
+    pub fn parse_expr(expr: &str) -> Result<Self> {
+        let mut config = FilterConfig {
+            and_mode: true,
+            ..Default::default()
+        };
+        let mut arg_values = Vec::new();
+
+        for token in expr.split_whitespace() {
+            let (key, value) = token
+                .split_once(':')
+                .filter(|(_, v)| !v.is_empty())
+                .ok_or_else(|| LoofError::Parse(format!("expected key:value, got '{}'", token)))?;
+            match key.to_ascii_lowercase().as_str() {
+                "pid" => config.pids = Some(parse_pid_filter(value)?),
+                "pgid" => config.pgids = Some(parse_pgid_filter(value)?),
+                "state" => config.states = Some(parse_proc_states(value)?),
+                "user" => config.users = Some(parse_user_filter(value)),
+                "cmd" | "command" => config.commands = Some(parse_command_filter(value)),
+                "arg" => arg_values.push(value.to_string()),
+                "size" => config.size_filter = Some(parse_size_filter(value)?),
+                "dir" => config.dir_tree = Some(PathBuf::from(value)),
+                "type" => match value.to_ascii_lowercase().as_str() {
+                    "reg" | "file" => config.only_files = true,
+                    "ipv4" | "4" => inet(&mut config).ip_version = Some(4),
+                    "ipv6" | "6" => inet(&mut config).ip_version = Some(6),
+                    "unix" => inet(&mut config).protocol = Some("UNIX".to_string()),
+                    _ => {
+                        return Err(LoofError::Parse(format!(
+                            "unsupported type '{}' (expected REG, IPv4, IPv6 or unix)",
+                            value
+                        )))
+                    }
+                },
+                "proto" => inet(&mut config).protocol = Some(value.to_uppercase()),
+                "port" => {
+                    let port = value
+                        .parse()
+                        .map_err(|_| LoofError::Parse(format!("invalid port '{}'", value)))?;
+                    inet(&mut config).port = Some(port);
+                }
+                "host" => inet(&mut config).host = Some(value.to_string()),
+                _ => {
+                    return Err(LoofError::Parse(format!(
+                        "unknown filter key '{}' (expected pid, pgid, state, user, cmd, \
+                         arg, size, dir, type, proto, port or host)",
+                        key
+                    )))
+                }
+            }
+        }
+
+        if !arg_values.is_empty() {
+            config.args = Some(parse_arg_filter(&arg_values));
+        }
+        Ok(config)
+    }
+
+    /// Whether `--fstype` or `--mount` need the mount table.
+    pub fn needs_mounts(&self) -> bool {
+        self.fstype.is_some() || self.mount.is_some()
+    }
+
+    /// Provide the mount table used to resolve `--fstype` and `--mount`.
+    /// Fails if the `--mount` path is not a mount point.
+    pub fn set_mounts(&mut self, mounts: &[MountInfo]) -> Result<()> {
+        if let Some(ref mut f) = self.fstype {
+            f.devices = mounts
+                .iter()
+                .map(|m| (m.device.clone(), m.fstype.clone()))
+                .collect();
+        }
+        if let Some(ref mut f) = self.mount {
+            // The last entry for a path is what's visible when something is
+            // mounted over an earlier mount.
+            f.device = mounts
+                .iter()
+                .rev()
+                .find(|m| m.mount_point == f.path)
+                .map(|m| m.device.clone());
+            if f.device.is_none() {
+                return Err(LoofError::Parse(format!("{} is not a mount point", f.path)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Walk `dir_tree` (`--walk`) and record every path in it, so open files
+    /// are matched against what is on disk. The root is canonicalized, as
+    /// the kernel reports resolved paths; symlinks inside are not followed
+    /// and unreadable subdirectories are skipped.
+    pub fn walk_dir_tree(&mut self) -> Result<()> {
+        let Some(ref root) = self.dir_tree else {
+            return Ok(());
+        };
+        let root = std::fs::canonicalize(root)?;
+        let mut paths = HashSet::new();
+        paths.insert(root.to_string_lossy().into_owned());
+        let mut pending = vec![std::fs::read_dir(&root)?];
+        while let Some(entries) = pending.pop() {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                    if let Ok(sub) = std::fs::read_dir(&path) {
+                        pending.push(sub);
+                    }
+                }
+                paths.insert(path.to_string_lossy().into_owned());
+            }
+        }
+        self.walked = Some(paths);
+        Ok(())
+    }
+
+    /// Returns `true` if any file-level filter is configured.
+    pub fn has_file_filters(&self) -> bool {
+        self.inet.is_some()
+            || self.connected_to.is_some()
+            || self.dir_tree.is_some()
+            || self.dir.is_some()
+            || !self.names.is_empty()
+            || self.size_filter.is_some()
+            || self.fstype.is_some()
+            || self.mount.is_some()
+            || self.accessed_within.is_some()
+            || self.only_files
+            || self.locked
+            || self.no_cloexec
+            || self.shared_writable
+            || self.missing
+    }
+
     /// Returns `true` if no filters are configured at all.
     #[allow(dead_code)]
     pub fn is_empty(&self) -> bool {
         self.pids.is_none()
             && self.pgids.is_none()
+            && self.nspid.is_none()
+            && self.container.is_none()
+            && self.context.is_none()
+            && self.states.is_none()
+            && self.cwd_under.is_none()
             && self.users.is_none()
             && self.commands.is_none()
             && self.inet.is_none()
+            && self.connected_to.is_none()
             && self.dir_tree.is_none()
             && self.dir.is_none()
             && self.names.is_empty()
             && self.size_filter.is_none()
+            && self.fstype.is_none()
+            && self.mount.is_none()
+            && self.accessed_within.is_none()
+            && !self.only_files
+            && !self.locked
+            && !self.no_cloexec
+            && !self.shared_writable
+            && !self.missing
+            && self.args.is_none()
+    }
+
+    /// Whether a process with these open files passes `--cwd-under`: its
+    /// `cwd` entry is inside the directory tree. Always true without it.
+    pub fn matches_cwd(&self, files: &[OpenFileInfo]) -> bool {
+        let Some(ref dir) = self.cwd_under else {
+            return true;
+        };
+        files
+            .iter()
+            .any(|f| f.fd == FdType::Cwd && file_in_dir_tree(&f.name, dir))
     }
 
     /// Check whether a process matches the configured process-level filters
-    /// (PID, user, command). In OR mode (default) any matching filter is
+    /// (PID, user, command, argv). In OR mode (default) any matching filter is
     /// sufficient; in AND mode all active filters must match.
     pub fn matches_process(&self, proc: &ProcessInfo) -> bool {
+        if self.nspid.is_some() && proc.nspid != self.nspid {
+            return false;
+        }
+        if let Some(ref id) = self.container {
+            if !proc
+                .container
+                .as_ref()
+                .is_some_and(|c| c.starts_with(id.as_str()))
+            {
+                return false;
+            }
+        }
+        if let Some(ref text) = self.context {
+            if !proc
+                .security_context
+                .as_ref()
+                .is_some_and(|c| c.contains(text.as_str()))
+            {
+                return false;
+            }
+        }
+        // PID exclusions (`-p ^N`, `--exclude-self`) always apply; only an
+        // include list makes `-p` a selector combined with the others.
+        if self
+            .pids
+            .as_ref()
+            .is_some_and(|f| f.exclude.contains(&proc.pid))
+        {
+            return false;
+        }
+        let pid_selects = self.pids.as_ref().is_some_and(|f| !f.include.is_empty());
         // If no process-level filters are set, everything matches.
-        if self.pids.is_none()
+        if !pid_selects
             && self.pgids.is_none()
+            && self.states.is_none()
             && self.users.is_none()
             && self.commands.is_none()
+            && self.args.is_none()
         {
             return true;
         }
 
         let pid_match = self.check_pid(proc);
         let pgid_match = self.check_pgid(proc);
+        let state_match = self.check_state(proc);
         let user_match = self.check_user(proc);
         let cmd_match = self.check_command(proc);
+        let arg_match = self.check_args(proc);
 
         if self.and_mode {
             // AND: every *active* filter must match
             let mut pass = true;
-            if self.pids.is_some() {
+            if pid_selects {
                 pass = pass && pid_match;
             }
             if self.pgids.is_some() {
                 pass = pass && pgid_match;
             }
+            if self.states.is_some() {
+                pass = pass && state_match;
+            }
             if self.users.is_some() {
                 pass = pass && user_match;
             }
             if self.commands.is_some() {
                 pass = pass && cmd_match;
             }
+            if self.args.is_some() {
+                pass = pass && arg_match;
+            }
             pass
         } else {
             // OR: at least one active filter must match
             let mut any = false;
-            if self.pids.is_some() {
+            if pid_selects {
                 any = any || pid_match;
             }
             if self.pgids.is_some() {
                 any = any || pgid_match;
             }
+            if self.states.is_some() {
+                any = any || state_match;
+            }
             if self.users.is_some() {
                 any = any || user_match;
             }
             if self.commands.is_some() {
                 any = any || cmd_match;
             }
+            if self.args.is_some() {
+                any = any || arg_match;
+            }
             any
         }
     }
@@ -318,22 +836,38 @@ impl FilterConfig {
     /// (inet, directory, names).
     pub fn matches_file(&self, file: &OpenFileInfo) -> bool {
         // If no file-level filters are set, everything matches.
-        if self.inet.is_none()
-            && self.dir_tree.is_none()
-            && self.dir.is_none()
-            && self.names.is_empty()
-            && self.size_filter.is_none()
-        {
+        if !self.has_file_filters() {
             return true;
         }
+        if self.only_files && file.file_type != FileType::Reg {
+            return false;
+        }
+        if self.locked && file.lock.is_none() {
+            return false;
+        }
+        if self.no_cloexec && file.cloexec != Some(false) {
+            return false;
+        }
+        if self.shared_writable && !file.mapping.is_some_and(|m| m.shared && m.writable) {
+            return false;
+        }
+        if self.missing && file.resolved != Some(false) {
+            return false;
+        }
 
         let mut results: Vec<bool> = Vec::new();
 
         if let Some(ref inet) = self.inet {
             results.push(inet.matches_file(file));
         }
+        if let Some(ref remote) = self.connected_to {
+            results.push(remote.matches_file(file));
+        }
         if let Some(ref dir_tree) = self.dir_tree {
-            results.push(file_in_dir_tree(&file.name, dir_tree));
+            results.push(match self.walked {
+                Some(ref paths) => paths.contains(&file.name),
+                None => file_in_dir_tree(&file.name, dir_tree),
+            });
         }
         if let Some(ref dir) = self.dir {
             results.push(file_in_dir(&file.name, dir));
@@ -356,6 +890,19 @@ impl FilterConfig {
                 results.push(false);
             }
         }
+        if let Some(ref fs) = self.fstype {
+            results.push(
+                fs.devices
+                    .get(&file.device)
+                    .is_some_and(|t| fs.types.iter().any(|w| w == t)),
+            );
+        }
+        if let Some(ref m) = self.mount {
+            results.push(m.device.as_ref() == Some(&file.device));
+        }
+        if let Some(window) = self.accessed_within {
+            results.push(accessed_within(file.atime, SystemTime::now(), window));
+        }
 
         if results.is_empty() {
             return true;
@@ -426,13 +973,44 @@ impl FilterConfig {
         match &self.commands {
             None => true,
             Some(f) => {
-                if f.exclude.iter().any(|c| proc.comm.starts_with(c.as_str())) {
+                let matches = |c: &String| {
+                    if f.exact {
+                        proc.comm == *c
+                    } else {
+                        proc.comm.starts_with(c.as_str())
+                    }
+                };
+                if f.exclude.iter().any(matches) {
                     return false;
                 }
                 if f.include.is_empty() {
                     true
                 } else {
-                    f.include.iter().any(|c| proc.comm.starts_with(c.as_str()))
+                    f.include.iter().any(matches)
+                }
+            }
+        }
+    }
+
+    fn check_state(&self, proc: &ProcessInfo) -> bool {
+        match (&self.states, proc.state) {
+            (None, _) => true,
+            (Some(states), Some(state)) => states.contains(&state),
+            (Some(_), None) => false,
+        }
+    }
+
+    fn check_args(&self, proc: &ProcessInfo) -> bool {
+        match &self.args {
+            None => true,
+            Some(f) => {
+                if f.exclude.iter().any(|a| proc.command.contains(a.as_str())) {
+                    return false;
+                }
+                if f.include.is_empty() {
+                    true
+                } else {
+                    f.include.iter().any(|a| proc.command.contains(a.as_str()))
                 }
             }
         }
@@ -443,9 +1021,58 @@ impl FilterConfig {
 // Inet filter matching
 // ---------------------------------------------------------------------------
 
+impl RemoteEndpoint {
+    /// Whether `file` is an ESTABLISHED TCP socket to this endpoint. A
+    /// listener on the same local port doesn't count.
+    pub fn matches_file(&self, file: &OpenFileInfo) -> bool {
+        match &file.connection {
+            Some(Connection {
+                remote,
+                tcp_state: Some(TcpState::Established),
+            }) => remote.port() == self.port && self.addrs.contains(&canonical_ip(remote.ip())),
+            _ => false,
+        }
+    }
+}
+
+/// IPv4-mapped IPv6 addresses as plain IPv4, so both spellings compare equal.
+fn canonical_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        v4 => v4,
+    }
+}
+
+/// Parse a `--connected-to` endpoint: `host:port`, with `[addr]:port` for
+/// IPv6. The host may be a name, which is resolved now.
+fn parse_remote_endpoint(s: &str) -> Result<RemoteEndpoint> {
+    let bad = || LoofError::Parse(format!("--connected-to expects HOST:PORT, got '{}'", s));
+    let (host, port) = s.rsplit_once(':').ok_or_else(bad)?;
+    let port: u16 = port.parse().map_err(|_| bad())?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return Err(bad());
+    }
+    let addrs: Vec<IpAddr> = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![canonical_ip(ip)],
+        Err(_) => (host, port)
+            .to_socket_addrs()
+            .map_err(|e| LoofError::Parse(format!("cannot resolve '{}': {}", host, e)))?
+            .map(|a| canonical_ip(a.ip()))
+            .collect(),
+    };
+    Ok(RemoteEndpoint { addrs, port })
+}
+
 impl InetFilter {
     /// Check whether an open file matches this inet filter.
     pub fn matches_file(&self, file: &OpenFileInfo) -> bool {
+        // `unix[@path]`: unix sockets only, optionally bound to an exact path.
+        if self.is_unix() {
+            return file.file_type == FileType::Unix
+                && self.host.as_ref().is_none_or(|path| file.name == *path);
+        }
+
         // Must be a network file type
         match file.file_type {
             FileType::IPv4 | FileType::IPv6 | FileType::Sock | FileType::Unix => {}
@@ -455,16 +1082,8 @@ impl InetFilter {
         // Check IP version
         if let Some(ver) = self.ip_version {
             match ver {
-                4 => {
-                    if file.file_type != FileType::IPv4 {
-                        return false;
-                    }
-                }
-                6 => {
-                    if file.file_type != FileType::IPv6 {
-                        return false;
-                    }
-                }
+                4 if file.file_type != FileType::IPv4 => return false,
+                6 if file.file_type != FileType::IPv6 => return false,
                 _ => {}
             }
         }
@@ -495,6 +1114,11 @@ impl InetFilter {
 
         true
     }
+
+    /// Whether this spec selects unix-domain sockets (`-i unix`).
+    fn is_unix(&self) -> bool {
+        self.protocol.as_deref() == Some("UNIX")
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -503,7 +1127,8 @@ impl InetFilter {
 
 /// Parse an inet filter spec string.
 ///
-/// Format: `[46][protocol][@host][:port]`
+/// Format: `[46][protocol][@host][:port]`, or `unix[@path]` for unix
+/// sockets (the path is taken verbatim, so it may contain `:`).
 ///
 /// Examples:
 ///   ""                -> match all network files
@@ -513,6 +1138,7 @@ impl InetFilter {
 ///   "UDP"             -> match UDP
 ///   ":8080"           -> match any protocol on port 8080
 ///   "@192.168.1.1"    -> match any protocol to host
+///   "unix@/run/docker.sock" -> match the unix socket at that path
 fn parse_inet_filter(s: &str) -> InetFilter {
     let mut filter = InetFilter::default();
     if s.is_empty() {
@@ -538,14 +1164,32 @@ fn parse_inet_filter(s: &str) -> InetFilter {
         remaining = &remaining[proto_end..];
     }
 
-    // Extract host (@host before :)
-    if remaining.starts_with('@') {
-        remaining = &remaining[1..]; // skip '@'
-        let host_end = remaining.find(':').unwrap_or(remaining.len());
-        if host_end > 0 {
-            filter.host = Some(remaining[..host_end].to_string());
+    if filter.is_unix() {
+        if let Some(path) = remaining.strip_prefix('@') {
+            if !path.is_empty() {
+                filter.host = Some(path.to_string());
+            }
+        }
+        return filter;
+    }
+
+    // Extract host (@host before :). An IPv6 literal is either bracketed
+    // (`@[::1]:443`) or bare with no port (`@2001:db8::1`).
+    if let Some(spec) = remaining.strip_prefix('@') {
+        let (host, rest) = if let Some(bracketed) = spec.strip_prefix('[') {
+            match bracketed.split_once(']') {
+                Some((host, rest)) => (host, rest),
+                None => (bracketed, ""),
+            }
+        } else if spec.matches(':').count() > 1 {
+            (spec, "")
+        } else {
+            spec.split_at(spec.find(':').unwrap_or(spec.len()))
+        };
+        if !host.is_empty() {
+            filter.host = Some(host.to_string());
         }
-        remaining = &remaining[host_end..];
+        remaining = rest;
     }
 
     // Extract port (:port)
@@ -574,6 +1218,21 @@ fn file_in_dir_tree(file_name: &str, dir: &std::path::Path) -> bool {
     file_name.starts_with(&dir_prefix) || file_name == dir_str.as_ref()
 }
 
+/// The inet filter of `config`, created on first use.
+fn inet(config: &mut FilterConfig) -> &mut InetFilter {
+    config.inet.get_or_insert_with(InetFilter::default)
+}
+
+/// Strip trailing slashes so `/mnt/` matches the `/mnt` mount point.
+fn normalize_mount_path(path: &str) -> String {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() {
+        "/".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
 /// Check if a file path is directly inside a directory (non-recursive).
 fn file_in_dir(file_name: &str, dir: &std::path::Path) -> bool {
     let path = std::path::Path::new(file_name);
@@ -593,6 +1252,7 @@ fn file_in_dir(file_name: &str, dir: &std::path::Path) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::fixtures;
     use crate::model::{FdMode, FdType};
 
     // -- PID filter parsing --
@@ -716,6 +1376,25 @@ mod tests {
         assert!(f.port.is_none());
     }
 
+    #[test]
+    fn test_parse_inet_ipv6_literal_hosts() {
+        let f = parse_inet_filter("@[::1]:443");
+        assert_eq!(f.host.as_deref(), Some("::1"));
+        assert_eq!(f.port, Some(443));
+
+        let f = parse_inet_filter("@[2001:db8::1]");
+        assert_eq!(f.host.as_deref(), Some("2001:db8::1"));
+        assert!(f.port.is_none());
+
+        let f = parse_inet_filter("@2001:db8::1");
+        assert_eq!(f.host.as_deref(), Some("2001:db8::1"));
+        assert!(f.port.is_none());
+
+        let f = parse_inet_filter("TCP@192.168.1.1:80");
+        assert_eq!(f.host.as_deref(), Some("192.168.1.1"));
+        assert_eq!(f.port, Some(80));
+    }
+
     #[test]
     fn test_parse_inet_ipv4_only() {
         let f = parse_inet_filter("4");
@@ -727,30 +1406,13 @@ mod tests {
 
     fn make_proc(pid: u32, user: &str, comm: &str) -> ProcessInfo {
         ProcessInfo {
-            pid,
-            ppid: None,
-            pgid: None,
-            command: comm.to_string(),
-            comm: comm.to_string(),
             user: user.to_string(),
-            uid: 0,
-            open_files: Vec::new(),
+            ..fixtures::process(pid, comm, Vec::new())
         }
     }
 
     fn make_file(name: &str, file_type: FileType) -> OpenFileInfo {
-        OpenFileInfo {
-            fd: FdType::Numbered(0, FdMode::Read),
-            file_type,
-            device: String::new(),
-            size_off: None,
-            node: String::new(),
-            name: name.to_string(),
-            mode: Some(FdMode::Read),
-            link_target: None,
-            send_queue: None,
-            recv_queue: None,
-        }
+        fixtures::file(FdType::Numbered(0, FdMode::Read), file_type, name)
     }
 
     #[test]
@@ -779,6 +1441,31 @@ mod tests {
         assert!(config.matches_process(&make_proc(200, "root", "bash")));
     }
 
+    #[test]
+    fn test_exclude_self_drops_only_our_pid() {
+        let self_pid = 4242;
+        let mut pids = None;
+        exclude_pid(&mut pids, self_pid);
+        let config = FilterConfig {
+            pids,
+            ..Default::default()
+        };
+        assert!(!config.matches_process(&make_proc(self_pid, "root", "loof")));
+        assert!(config.matches_process(&make_proc(1, "root", "init")));
+
+        // Injected into an existing -p list, and still excluded when another
+        // selector (here -u) would match on its own.
+        let mut pids = Some(parse_pid_filter("1,4242").unwrap());
+        exclude_pid(&mut pids, self_pid);
+        let config = FilterConfig {
+            pids,
+            users: Some(parse_user_filter("root")),
+            ..Default::default()
+        };
+        assert!(!config.matches_process(&make_proc(self_pid, "root", "loof")));
+        assert!(config.matches_process(&make_proc(1, "root", "init")));
+    }
+
     #[test]
     fn test_matches_process_user() {
         let config = FilterConfig {
@@ -798,6 +1485,7 @@ mod tests {
             commands: Some(CommandFilter {
                 include: vec!["ngin".to_string()],
                 exclude: vec![],
+                exact: false,
             }),
             ..Default::default()
         };
@@ -805,6 +1493,73 @@ mod tests {
         assert!(!config.matches_process(&make_proc(2, "root", "bash")));
     }
 
+    #[test]
+    fn test_command_exact_skips_longer_names() {
+        use clap::Parser;
+        let ssh = make_proc(1, "alice", "ssh");
+        let sshd = make_proc(2, "root", "sshd");
+
+        let prefix = FilterConfig::from_cli(&CliArgs::parse_from(["loof", "-c", "ssh"])).unwrap();
+        assert!(prefix.matches_process(&ssh));
+        assert!(prefix.matches_process(&sshd));
+
+        let exact = FilterConfig::from_cli(&CliArgs::parse_from([
+            "loof",
+            "-c",
+            "ssh",
+            "--command-exact",
+        ]))
+        .unwrap();
+        assert!(exact.matches_process(&ssh));
+        assert!(!exact.matches_process(&sshd));
+    }
+
+    #[test]
+    fn test_matches_process_exact_name() {
+        let config = FilterConfig {
+            commands: Some(parse_name_filter("nginx")),
+            ..Default::default()
+        };
+        assert!(config.matches_process(&make_proc(1, "root", "nginx")));
+        assert!(!config.matches_process(&make_proc(2, "root", "nginx-debug")));
+        assert!(!config.matches_process(&make_proc(3, "root", "ngin")));
+
+        let config = FilterConfig {
+            commands: Some(parse_name_filter("^nginx")),
+            ..Default::default()
+        };
+        assert!(!config.matches_process(&make_proc(1, "root", "nginx")));
+        assert!(config.matches_process(&make_proc(2, "root", "nginx-debug")));
+    }
+
+    #[test]
+    fn test_matches_process_arg_substring() {
+        let config = FilterConfig {
+            args: Some(parse_arg_filter(&["-Dapp.name=foo".to_string()])),
+            ..Default::default()
+        };
+        let mut foo = make_proc(1, "root", "java");
+        foo.command = "java -Xmx1g -Dapp.name=foo -jar app.jar".to_string();
+        let mut bar = make_proc(2, "root", "java");
+        bar.command = "java -Dapp.name=bar -jar app.jar".to_string();
+        assert!(config.matches_process(&foo));
+        assert!(!config.matches_process(&bar));
+    }
+
+    #[test]
+    fn test_matches_process_arg_exclude() {
+        let config = FilterConfig {
+            args: Some(parse_arg_filter(&["^--debug".to_string()])),
+            ..Default::default()
+        };
+        let mut plain = make_proc(1, "root", "app");
+        plain.command = "app --port 80".to_string();
+        let mut debug = make_proc(2, "root", "app");
+        debug.command = "app --debug --port 80".to_string();
+        assert!(config.matches_process(&plain));
+        assert!(!config.matches_process(&debug));
+    }
+
     #[test]
     fn test_matches_process_and_mode() {
         let config = FilterConfig {
@@ -865,6 +1620,28 @@ mod tests {
         assert!(!config.matches_file(&make_file("/var/log/syslog", FileType::Reg)));
     }
 
+    #[test]
+    fn test_matches_cwd_under() {
+        let config = FilterConfig {
+            cwd_under: Some(PathBuf::from("/srv/app")),
+            ..Default::default()
+        };
+        assert!(!config.is_empty());
+        let cwd = |name: &str| OpenFileInfo {
+            fd: FdType::Cwd,
+            ..make_file(name, FileType::Dir)
+        };
+        assert!(config.matches_cwd(&[cwd("/srv/app/worker")]));
+        assert!(config.matches_cwd(&[cwd("/srv/app")]));
+        assert!(!config.matches_cwd(&[cwd("/srv/application")]));
+        // Only the cwd entry counts, not files opened under the tree.
+        assert!(!config.matches_cwd(&[
+            cwd("/home/user"),
+            make_file("/srv/app/log.txt", FileType::Reg),
+        ]));
+        assert!(FilterConfig::default().matches_cwd(&[]));
+    }
+
     #[test]
     fn test_matches_file_dir_non_recursive() {
         let config = FilterConfig {
@@ -890,6 +1667,81 @@ mod tests {
         assert!(inet.matches_file(&file));
     }
 
+    #[test]
+    fn test_connected_to_matches_remote_end_only() {
+        let remote = parse_remote_endpoint("10.0.0.5:5432").unwrap();
+        let client =
+            fixtures::tcp_socket(3, "10.0.0.9:41822", "10.0.0.5:5432", TcpState::Established);
+        let mapped = fixtures::tcp_socket(
+            3,
+            "[::ffff:10.0.0.9]:41822",
+            "[::ffff:10.0.0.5]:5432",
+            TcpState::Established,
+        );
+        let listener = fixtures::tcp_socket(3, "10.0.0.5:5432", "0.0.0.0:0", TcpState::Listen);
+        let server_side =
+            fixtures::tcp_socket(3, "10.0.0.5:5432", "10.0.0.9:41822", TcpState::Established);
+        let closing =
+            fixtures::tcp_socket(3, "10.0.0.9:41823", "10.0.0.5:5432", TcpState::CloseWait);
+        // Only the structured endpoint counts, not a look-alike name.
+        let udp = OpenFileInfo {
+            connection: Some(Connection {
+                tcp_state: None,
+                ..client.connection.clone().unwrap()
+            }),
+            ..client.clone()
+        };
+        assert!(remote.matches_file(&client));
+        assert!(remote.matches_file(&mapped));
+        assert!(!remote.matches_file(&listener));
+        assert!(!remote.matches_file(&server_side));
+        assert!(!remote.matches_file(&closing));
+        assert!(!remote.matches_file(&udp));
+        assert!(!remote.matches_file(&OpenFileInfo {
+            connection: None,
+            ..client.clone()
+        }));
+
+        let config = FilterConfig {
+            connected_to: Some(remote),
+            ..Default::default()
+        };
+        let app = ProcessInfo {
+            open_files: vec![client],
+            ..make_proc(10, "app", "api")
+        };
+        let db = ProcessInfo {
+            open_files: vec![listener],
+            ..make_proc(20, "postgres", "postgres")
+        };
+        let has_match = |p: &ProcessInfo| p.open_files.iter().any(|f| config.matches_file(f));
+        assert!(has_match(&app));
+        assert!(!has_match(&db));
+
+        let localhost = parse_remote_endpoint("localhost:80").unwrap();
+        assert!(!localhost.addrs.is_empty());
+        assert!(parse_remote_endpoint("10.0.0.5").is_err());
+        let v6 = parse_remote_endpoint("[::1]:443").unwrap();
+        assert_eq!(v6.addrs, vec!["::1".parse::<IpAddr>().unwrap()]);
+    }
+
+    #[test]
+    fn test_ipv4_flag_restricts_family() {
+        use clap::Parser;
+        let args = CliArgs::parse_from(["loof", "-4"]);
+        let config = FilterConfig::from_cli(&args).unwrap();
+        assert!(config.has_file_filters());
+        assert!(config.matches_file(&make_file("127.0.0.1:80", FileType::IPv4)));
+        assert!(!config.matches_file(&make_file("[::1]:80", FileType::IPv6)));
+        assert!(!config.matches_file(&make_file("/etc/hosts", FileType::Reg)));
+
+        // Combined with a protocol spec, the family still applies.
+        let args = CliArgs::parse_from(["loof", "-6", "-i", "TCP"]);
+        let inet = FilterConfig::from_cli(&args).unwrap().inet.unwrap();
+        assert_eq!(inet.ip_version, Some(6));
+        assert_eq!(inet.protocol.as_deref(), Some("TCP"));
+    }
+
     #[test]
     fn test_inet_filter_rejects_wrong_protocol() {
         let inet = InetFilter {
@@ -901,6 +1753,30 @@ mod tests {
         assert!(!inet.matches_file(&file));
     }
 
+    #[test]
+    fn test_inet_filter_unix_by_path() {
+        let inet = parse_inet_filter("unix@/run/docker.sock");
+        assert_eq!(inet.protocol.as_deref(), Some("UNIX"));
+        assert_eq!(inet.host.as_deref(), Some("/run/docker.sock"));
+        assert!(inet.matches_file(&make_file("/run/docker.sock", FileType::Unix)));
+        assert!(!inet.matches_file(&make_file("/run/docker.sock.bak", FileType::Unix)));
+        assert!(!inet.matches_file(&make_file("127.0.0.1:80", FileType::IPv4)));
+
+        // Paths are taken verbatim, including ':'.
+        let inet = parse_inet_filter("unix@/tmp/a:b.sock");
+        assert_eq!(inet.host.as_deref(), Some("/tmp/a:b.sock"));
+        assert_eq!(inet.port, None);
+    }
+
+    #[test]
+    fn test_inet_filter_unix_all() {
+        let inet = parse_inet_filter("unix");
+        assert!(inet.matches_file(&make_file("/run/dbus/system_bus_socket", FileType::Unix)));
+        assert!(inet.matches_file(&make_file("unix socket inode=42", FileType::Unix)));
+        assert!(!inet.matches_file(&make_file("127.0.0.1:80", FileType::IPv4)));
+        assert!(!inet.matches_file(&make_file("net:[1234]", FileType::Sock)));
+    }
+
     #[test]
     fn test_inet_filter_rejects_non_network() {
         let inet = InetFilter::default();
@@ -969,6 +1845,243 @@ mod tests {
         assert_eq!(f3.bytes, 2 * 1_073_741_824);
     }
 
+    #[test]
+    fn test_parse_size_filter_overflow() {
+        // Fits in u64 on its own, overflows once multiplied.
+        let err = parse_size_filter("+99999999999G").unwrap_err();
+        assert!(err.to_string().contains("too large"));
+        // Does not fit in u64 at all.
+        assert!(parse_size_filter("99999999999999999999G").is_err());
+    }
+
+    #[test]
+    fn test_parse_size_filter_rejects_missing_number() {
+        assert!(parse_size_filter("M").is_err());
+        assert!(parse_size_filter("+K").is_err());
+        assert!(parse_size_filter("10X").is_err());
+    }
+
+    // -- Filesystem type filter --
+
+    #[test]
+    fn test_parse_fstype_filter() {
+        let f = parse_fstype_filter("nfs, tmpfs,");
+        assert_eq!(f.types, vec!["nfs", "tmpfs"]);
+    }
+
+    #[test]
+    fn test_matches_file_fstype() {
+        let mut config = FilterConfig {
+            fstype: Some(parse_fstype_filter("nfs")),
+            ..Default::default()
+        };
+        config
+            .set_mounts(&[
+                MountInfo {
+                    device: "0,35".into(),
+                    mount_point: "/mnt/share".into(),
+                    fstype: "nfs".into(),
+                    options: vec![],
+                },
+                MountInfo {
+                    device: "8,1".into(),
+                    mount_point: "/".into(),
+                    fstype: "ext4".into(),
+                    options: vec![],
+                },
+            ])
+            .unwrap();
+
+        let mut on_nfs = make_file("/mnt/share/data.db", FileType::Reg);
+        on_nfs.device = "0,35".into();
+        let mut on_root = make_file("/etc/hosts", FileType::Reg);
+        on_root.device = "8,1".into();
+        let socket = make_file("127.0.0.1:80", FileType::IPv4);
+
+        assert!(config.matches_file(&on_nfs));
+        assert!(!config.matches_file(&on_root));
+        assert!(!config.matches_file(&socket));
+    }
+
+    #[test]
+    fn test_parse_expr_maps_tokens_to_filters() {
+        let config = FilterConfig::parse_expr("user:root type:IPv4 port:443").unwrap();
+        assert!(config.and_mode);
+        assert_eq!(config.users.unwrap().include, vec!["root"]);
+        let inet = config.inet.unwrap();
+        assert_eq!(inet.ip_version, Some(4));
+        assert_eq!(inet.port, Some(443));
+        assert!(inet.protocol.is_none());
+
+        let config =
+            FilterConfig::parse_expr("pid:1,^2 cmd:ngin proto:tcp arg:--debug type:REG size:+1k")
+                .unwrap();
+        let pids = config.pids.unwrap();
+        assert_eq!((pids.include, pids.exclude), (vec![1], vec![2]));
+        assert_eq!(config.commands.unwrap().include, vec!["ngin"]);
+        assert_eq!(config.inet.unwrap().protocol.as_deref(), Some("TCP"));
+        assert_eq!(config.args.unwrap().include, vec!["--debug"]);
+        assert!(config.only_files);
+        assert_eq!(config.size_filter.unwrap().bytes, 1024);
+
+        assert!(FilterConfig::parse_expr("").unwrap().is_empty());
+        let err = FilterConfig::parse_expr("bogus:1").unwrap_err().to_string();
+        assert!(err.contains("state"), "{}", err);
+        assert!(FilterConfig::parse_expr("user").is_err());
+        assert!(FilterConfig::parse_expr("user:").is_err());
+        assert!(FilterConfig::parse_expr("port:http").is_err());
+        assert!(FilterConfig::parse_expr("type:CHR").is_err());
+    }
+
+    #[test]
+    fn test_only_files_keeps_regular_files() {
+        let config = FilterConfig {
+            only_files: true,
+            ..Default::default()
+        };
+        assert!(config.matches_file(&make_file("/etc/hosts", FileType::Reg)));
+        assert!(!config.matches_file(&make_file("/tmp", FileType::Dir)));
+        assert!(!config.matches_file(&make_file("127.0.0.1:80", FileType::IPv4)));
+        assert!(!config.matches_file(&make_file("pipe:[9]", FileType::Pipe)));
+
+        // Other file filters still apply, even in the default OR mode.
+        let config = FilterConfig {
+            only_files: true,
+            dir_tree: Some(PathBuf::from("/tmp")),
+            ..Default::default()
+        };
+        assert!(config.matches_file(&make_file("/tmp/a.log", FileType::Reg)));
+        assert!(!config.matches_file(&make_file("/tmp/sub", FileType::Dir)));
+        assert!(!config.matches_file(&make_file("/etc/hosts", FileType::Reg)));
+    }
+
+    #[test]
+    fn test_no_cloexec_keeps_inherited_fds() {
+        let config = FilterConfig {
+            no_cloexec: true,
+            ..Default::default()
+        };
+        let mut inherited = make_file("/var/log/app.log", FileType::Reg);
+        inherited.cloexec = Some(false);
+        let mut private = make_file("/etc/hosts", FileType::Reg);
+        private.cloexec = Some(true);
+        assert!(config.matches_file(&inherited));
+        assert!(!config.matches_file(&private));
+        // cwd/txt/mem entries carry no flags.
+        assert!(!config.matches_file(&make_file("/", FileType::Dir)));
+    }
+
+    #[test]
+    fn test_shared_writable_keeps_writable_shared_maps() {
+        use crate::model::MapInfo;
+
+        let config = FilterConfig {
+            shared_writable: true,
+            ..Default::default()
+        };
+        let mapped = |shared, writable| OpenFileInfo {
+            fd: FdType::Mem,
+            mapping: Some(MapInfo { shared, writable }),
+            ..make_file("/dev/shm/ring", FileType::Reg)
+        };
+        assert!(config.matches_file(&mapped(true, true)));
+        assert!(!config.matches_file(&mapped(true, false)));
+        assert!(!config.matches_file(&mapped(false, true)));
+        assert!(!config.matches_file(&make_file("/dev/shm/ring", FileType::Reg)));
+    }
+
+    #[test]
+    fn test_missing_keeps_unresolved_paths() {
+        let config = FilterConfig {
+            missing: true,
+            ..Default::default()
+        };
+        let with = |resolved| OpenFileInfo {
+            resolved,
+            ..make_file("/var/log/app.log", FileType::Reg)
+        };
+        assert!(config.matches_file(&with(Some(false))));
+        assert!(!config.matches_file(&with(Some(true))));
+        assert!(!config.matches_file(&with(None)));
+    }
+
+    #[test]
+    fn test_locked_keeps_locked_files() {
+        let config = FilterConfig {
+            locked: true,
+            ..Default::default()
+        };
+        let mut locked = make_file("/run/app.pid", FileType::Reg);
+        locked.lock = Some(crate::model::LockInfo {
+            write: true,
+            whole_file: true,
+        });
+        assert!(config.matches_file(&locked));
+        assert!(!config.matches_file(&make_file("/etc/hosts", FileType::Reg)));
+    }
+
+    #[test]
+    fn test_dir_tree_respects_path_boundary() {
+        let dir = PathBuf::from("/mnt");
+        assert!(file_in_dir_tree("/mnt/foo", &dir));
+        assert!(file_in_dir_tree("/mnt", &dir));
+        assert!(!file_in_dir_tree("/mnt2/foo", &dir));
+        assert!(file_in_dir_tree("/mnt/foo", &PathBuf::from("/mnt/")));
+    }
+
+    #[test]
+    fn test_matches_file_mount() {
+        let mounts = [
+            MountInfo {
+                device: "8,1".into(),
+                mount_point: "/".into(),
+                fstype: "ext4".into(),
+                options: vec![],
+            },
+            MountInfo {
+                device: "8,17".into(),
+                mount_point: "/mnt".into(),
+                fstype: "ext4".into(),
+                options: vec![],
+            },
+            MountInfo {
+                device: "8,33".into(),
+                mount_point: "/mnt2".into(),
+                fstype: "xfs".into(),
+                options: vec![],
+            },
+        ];
+        let mut config = FilterConfig {
+            mount: Some(MountFilter {
+                path: normalize_mount_path("/mnt/"),
+                device: None,
+            }),
+            ..Default::default()
+        };
+        config.set_mounts(&mounts).unwrap();
+
+        let mut on_mnt = make_file("/mnt/foo", FileType::Reg);
+        on_mnt.device = "8,17".into();
+        let mut on_mnt2 = make_file("/mnt2/foo", FileType::Reg);
+        on_mnt2.device = "8,33".into();
+        // Same filesystem seen through a bind mount elsewhere.
+        let mut bound = make_file("/srv/data/foo", FileType::Reg);
+        bound.device = "8,17".into();
+
+        assert!(config.matches_file(&on_mnt));
+        assert!(!config.matches_file(&on_mnt2));
+        assert!(config.matches_file(&bound));
+
+        let mut config = FilterConfig {
+            mount: Some(MountFilter {
+                path: "/mnt/foo".into(),
+                device: None,
+            }),
+            ..Default::default()
+        };
+        assert!(config.set_mounts(&mounts).is_err());
+    }
+
     // -- PGID filter matching --
 
     #[test]
@@ -992,4 +2105,157 @@ mod tests {
         let p3 = make_proc(3, "root", "bash");
         assert!(!config.matches_process(&p3));
     }
+
+    #[test]
+    fn test_accessed_within_comparator() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(10_000);
+        let window = parse_duration("10m").unwrap();
+        assert_eq!(window, Duration::from_secs(600));
+        let ago = |secs| Some(now - Duration::from_secs(secs));
+        assert!(accessed_within(ago(0), now, window));
+        assert!(accessed_within(ago(600), now, window));
+        assert!(!accessed_within(ago(601), now, window));
+        assert!(accessed_within(
+            Some(now + Duration::from_secs(5)),
+            now,
+            window
+        ));
+        assert!(!accessed_within(None, now, window));
+
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("1d").unwrap(), Duration::from_secs(86_400));
+        assert!(parse_duration("5w").is_err());
+        assert!(parse_duration("h").is_err());
+
+        let config = FilterConfig {
+            accessed_within: Some(window),
+            ..Default::default()
+        };
+        let mut file = make_file("/tmp/a", FileType::Reg);
+        assert!(!config.matches_file(&file));
+        file.atime = Some(SystemTime::now());
+        assert!(config.matches_file(&file));
+    }
+
+    #[test]
+    fn test_atime_warnings_gate_on_mount_options() {
+        let mount = |point: &str, opts: &[&str]| MountInfo {
+            device: "8,1".into(),
+            mount_point: point.into(),
+            fstype: "ext4".into(),
+            options: opts.iter().map(|o| o.to_string()).collect(),
+        };
+        let hour = Duration::from_secs(3600);
+        let week = Duration::from_secs(7 * 86_400);
+
+        assert!(atime_warnings(&[mount("/", &["rw", "strictatime"])], hour).is_empty());
+        assert!(atime_warnings(&[], hour).is_empty());
+
+        let relatime = [mount("/", &["rw", "relatime"])];
+        assert_eq!(atime_warnings(&relatime, hour).len(), 1);
+        assert!(atime_warnings(&relatime, week).is_empty());
+
+        let noatime = [mount("/", &["rw"]), mount("/data", &["rw", "noatime"])];
+        let warnings = atime_warnings(&noatime, week);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("/data (noatime)"));
+    }
+
+    #[test]
+    fn test_container_filter_matches_id_prefix() {
+        let id = "3f4e9b1c2d7a8e6f5b4c3d2e1f0a9b8c7d6e5f4a3b2c1d0e9f8a7b6c5d4e3f2a";
+        let in_container = ProcessInfo {
+            container: Some(id.to_string()),
+            ..make_proc(10, "root", "nginx")
+        };
+        let config = FilterConfig {
+            container: Some("3f4e9b1c2d7a".to_string()),
+            users: Some(parse_user_filter("root")),
+            ..Default::default()
+        };
+        assert!(config.matches_process(&in_container));
+        // Other selectors don't widen it: a host process is left out.
+        assert!(!config.matches_process(&make_proc(11, "root", "sshd")));
+    }
+
+    #[test]
+    fn test_context_filter_matches_substring() {
+        let httpd = ProcessInfo {
+            security_context: Some("system_u:system_r:httpd_t:s0".to_string()),
+            ..make_proc(10, "apache", "httpd")
+        };
+        let config = FilterConfig {
+            context: Some("httpd_t".to_string()),
+            ..Default::default()
+        };
+        assert!(config.matches_process(&httpd));
+        let unconfined = ProcessInfo {
+            security_context: Some("unconfined_u:unconfined_r:unconfined_t:s0".to_string()),
+            ..make_proc(11, "alice", "bash")
+        };
+        assert!(!config.matches_process(&unconfined));
+        // No label (SELinux off): never matches.
+        assert!(!config.matches_process(&make_proc(12, "root", "sshd")));
+    }
+
+    #[test]
+    fn test_proc_state_mapping() {
+        assert_eq!(ProcState::from_code('Z'), ProcState::Zombie);
+        assert_eq!(ProcState::from_code('t'), ProcState::Stopped);
+        assert_eq!(ProcState::from_code('D'), ProcState::DiskSleep);
+        assert_eq!(ProcState::from_code('X'), ProcState::Other('X'));
+        assert_eq!(ProcState::from_bsd_status(5), Some(ProcState::Zombie));
+        assert_eq!(ProcState::from_bsd_status(4), Some(ProcState::Stopped));
+        assert_eq!(ProcState::from_bsd_status(0), None);
+        assert_eq!(ProcState::Stopped.to_string(), "T");
+    }
+
+    #[test]
+    fn test_state_proc_selects_zombies() {
+        let with_state = |pid, state| ProcessInfo {
+            state,
+            ..make_proc(pid, "root", "worker")
+        };
+        let config = FilterConfig {
+            states: Some(parse_proc_states("z").unwrap()),
+            ..Default::default()
+        };
+        assert!(config.matches_process(&with_state(1, Some(ProcState::Zombie))));
+        assert!(!config.matches_process(&with_state(2, Some(ProcState::Sleeping))));
+        assert!(!config.matches_process(&with_state(3, None)));
+
+        assert_eq!(
+            parse_proc_states("Z,T").unwrap(),
+            vec![ProcState::Zombie, ProcState::Stopped]
+        );
+        assert!(parse_proc_states("Q").is_err());
+        assert!(parse_proc_states("ZT").is_err());
+    }
+
+    #[test]
+    fn test_pid_and_pgid_ranges() {
+        let pids = parse_pid_filter("1000-1005").unwrap();
+        assert_eq!(pids.include, (1000..=1005).collect::<Vec<_>>());
+        let config = FilterConfig {
+            pids: Some(pids),
+            ..Default::default()
+        };
+        assert!(config.matches_process(&make_proc(1003, "root", "a")));
+        assert!(!config.matches_process(&make_proc(1006, "root", "a")));
+
+        let pgids = parse_pgid_filter("^2000-2002,7").unwrap();
+        assert_eq!(pgids.exclude, vec![2000, 2001, 2002]);
+        assert_eq!(pgids.include, vec![7]);
+
+        assert!(parse_pid_filter("10-5")
+            .unwrap_err()
+            .to_string()
+            .contains("start is after end"));
+        assert!(parse_pid_filter("1-4000000000")
+            .unwrap_err()
+            .to_string()
+            .contains("too large"));
+        assert!(parse_pid_filter("1-x").is_err());
+        assert!(parse_pid_filter("-5").is_err());
+    }
 }
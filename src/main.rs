@@ -6,17 +6,28 @@ mod filter;
 mod model;
 mod output;
 mod platform;
+mod report;
+mod scan;
+#[cfg(unix)]
+mod serve;
+mod trace;
 mod ui;
 
-use std::time::Duration;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 use clap::Parser;
 use cli::{preprocess_args, CliArgs};
 use filter::FilterConfig;
+use model::TcpState;
 use output::OutputFormatter;
 use platform::{create_provider, ProviderConfig};
+use scan::ScanOptions;
 
 use app::action::map_key_to_action;
+use app::state::StatusKind;
 use app::{Action, AppState};
 use event::{AppEvent, EventHandler};
 
@@ -25,14 +36,59 @@ fn main() {
     let raw_args: Vec<String> = std::env::args().collect();
     let processed = preprocess_args(raw_args);
     let args = CliArgs::parse_from(processed);
+    #[cfg(feature = "trace")]
+    trace::init(args.trace);
 
     let config = ProviderConfig {
         avoid_stat: args.avoid_stat,
         follow_symlinks: args.follow_symlinks,
+        raw_proto: args.raw_proto,
+        raw_addr: args.raw_addr,
+        no_mmap: args.no_mmap,
+        ns_pids: args.show_nspid || args.nspid.is_some(),
+        containers: args.container.is_some() || args.wide || args.interactive,
+        security_contexts: args.context || args.context_filter.is_some(),
+        fd_limits: args.interactive,
+        argv_raw: args.argv_raw,
     };
     let provider = create_provider(config);
 
+    // `--exit-code-match` reserves 1 for "nothing matched".
+    let error_exit = if args.exit_code_match { 2 } else { 1 };
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(e) => {
+                eprintln!("Error: cannot create {}: {}", path, e);
+                std::process::exit(error_exit);
+            }
+        },
+        None => Box::new(io::stdout().lock()),
+    };
+
+    if args.capabilities {
+        let written = writeln!(out, "{}", report::capabilities_json());
+        finish_report(&mut out, written);
+        return;
+    }
+
+    if args.list_types || args.list_fd_types || args.fields_help {
+        let lines = if args.list_types {
+            report::type_list_lines()
+        } else if args.list_fd_types {
+            report::fd_type_list_lines()
+        } else {
+            report::field_list_lines()
+        };
+        let written = report::print_list(&mut out, &lines);
+        finish_report(&mut out, written);
+        return;
+    }
+
     if args.interactive {
+        // The TUI draws to stdout itself.
+        drop(out);
+        ui::theme::set_theme(ui::theme::Theme::preset(args.theme));
         if let Err(e) = run_tui(&*provider) {
             ratatui::restore();
             eprintln!("Error: {}", e);
@@ -41,118 +97,394 @@ fn main() {
         return;
     }
 
+    if let Some(port) = args.port {
+        match provider.list_network_connections(None) {
+            Ok(conns) => {
+                let written = report::print_port_report(&mut out, &conns, port);
+                finish_report(&mut out, written);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if args.self_check {
+        match report::self_check(&*provider) {
+            Ok(results) => {
+                let passed = report::print_self_check(&mut out, &results);
+                let all_ok = matches!(passed, Ok(true));
+                finish_report(&mut out, passed.map(|_| ()));
+                if !all_ok {
+                    std::process::exit(1);
+                }
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if args.dump_sockets {
+        match provider.socket_inode_map() {
+            Ok(entries) => {
+                let written = if args.json {
+                    writeln!(out, "{}", report::socket_dump_json(&entries))
+                } else {
+                    report::print_socket_dump(&mut out, &entries)
+                };
+                finish_report(&mut out, written);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if args.net {
+        match provider.list_network_connections(None) {
+            Ok(conns) => {
+                let written = if args.json {
+                    writeln!(out, "{}", report::net_report_json(&conns))
+                } else {
+                    report::print_net_report(&mut out, &conns, args.warn_time_wait)
+                };
+                finish_report(&mut out, written);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     // Build filter config and output formatter from CLI args.
-    let filter_config = match FilterConfig::from_cli(&args) {
+    let mut filter_config = match FilterConfig::from_cli(&args) {
         Ok(fc) => fc,
         Err(e) => {
             eprintln!("Error parsing filters: {}", e);
+            std::process::exit(error_exit);
+        }
+    };
+    if filter_config.needs_mounts() {
+        let mounts = match provider.list_mounts() {
+            Ok(mounts) => mounts,
+            Err(e) => {
+                eprintln!("Error reading mount table: {}", e);
+                std::process::exit(error_exit);
+            }
+        };
+        if let Err(e) = filter_config.set_mounts(&mounts) {
+            eprintln!("Error parsing filters: {}", e);
+            std::process::exit(error_exit);
+        }
+    }
+    if args.walk {
+        if let Err(e) = filter_config.walk_dir_tree() {
+            eprintln!("Error walking directory: {}", e);
+            std::process::exit(error_exit);
+        }
+    }
+    if args.dry_run {
+        match report::dry_run_estimate(&*provider, &filter_config) {
+            Ok(estimate) => {
+                let written = if args.json {
+                    writeln!(out, "{}", report::dry_run_json(&estimate))
+                } else {
+                    report::print_dry_run(&mut out, &estimate)
+                };
+                finish_report(&mut out, written);
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if args.top.is_some() || args.count_only || args.zero_fds {
+        // These count FDs without reading the files, so file filters can't apply.
+        if filter_config.has_file_filters() {
+            let mode = if args.zero_fds {
+                "--zero-fds"
+            } else if args.count_only {
+                "--count-only"
+            } else {
+                "--top"
+            };
+            eprintln!(
+                "Error: {} takes only process filters (-p, -u, -c, ...), not file filters",
+                mode
+            );
             std::process::exit(1);
         }
+        let processes = match provider.list_processes() {
+            Ok(procs) => procs
+                .into_iter()
+                .filter(|p| filter_config.matches_process(p))
+                .collect(),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        };
+        if args.zero_fds {
+            let empty = report::zero_fd_processes(&*provider, processes);
+            let written = if args.json {
+                let counts: Vec<_> = empty.into_iter().map(|p| (0, p)).collect();
+                writeln!(out, "{}", report::top_report_json(&counts))
+            } else {
+                report::print_zero_fd_report(&mut out, &empty)
+            };
+            finish_report(&mut out, written);
+            return;
+        }
+        let counts = match args.top {
+            Some(n) => report::top_fd_consumers(&*provider, processes, n),
+            None => report::fd_counts(&*provider, processes),
+        };
+        let written = if args.json {
+            writeln!(out, "{}", report::top_report_json(&counts))
+        } else if args.count_only {
+            report::print_count_report(&mut out, &counts)
+        } else {
+            report::print_top_report(&mut out, &counts)
+        };
+        finish_report(&mut out, written);
+        return;
+    }
+
+    let scan_opts = ScanOptions::from_cli(&args);
+
+    if let Some(min) = args.min_connections {
+        let state = match &args.tcp_state {
+            Some(name) => match TcpState::from_name(name) {
+                Some(state) => Some(state),
+                None => {
+                    eprintln!("Error: unknown TCP state '{}'", name);
+                    std::process::exit(1);
+                }
+            },
+            None => None,
+        };
+        let processes = match scan::scan(&*provider, &filter_config, &scan_opts) {
+            Ok(result) => result.processes,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        };
+        let counts = report::connection_counts(processes, min, state.as_ref());
+        let written = if args.json {
+            writeln!(out, "{}", report::connection_report_json(&counts))
+        } else {
+            report::print_connection_report(&mut out, &counts)
+        };
+        finish_report(&mut out, written);
+        return;
+    }
+
+    if args.leak_scan {
+        let sample = |filter: &FilterConfig| match scan::scan(&*provider, filter, &scan_opts) {
+            Ok(result) => result.processes,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        };
+        let before = sample(&filter_config);
+        std::thread::sleep(Duration::from_secs(args.leak_interval));
+        let after = sample(&filter_config);
+        // The other end of a pipe may be in a process the filters hide.
+        let everyone = sample(&FilterConfig::default());
+        let suspects = report::classify_leaks(&before, &after, &everyone);
+        let written = report::print_leak_report(&mut out, &suspects);
+        finish_report(&mut out, written);
+        return;
+    }
+    if let Some(path) = &args.watch_file {
+        // Open file names are absolute and resolved, so match that form.
+        let path = std::fs::canonicalize(path)
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| path.clone());
+        filter_config.names.push(path.clone().into());
+        filter_config.and_mode = true;
+        let sample = || match scan::scan(&*provider, &filter_config, &scan_opts) {
+            Ok(result) => result.processes,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        };
+        let mut holders = Vec::new();
+        loop {
+            let now = sample();
+            let written = report::watch_events(&holders, &now)
+                .iter()
+                .try_for_each(|event| writeln!(out, "{}", report::watch_event_line(&path, event)));
+            finish_report(&mut out, written);
+            if !args.follow {
+                break;
+            }
+            holders = now;
+            std::thread::sleep(Duration::from_secs(args.repeat.unwrap_or(1)));
+        }
+        return;
+    }
+    let formatter = match OutputFormatter::from_cli(&args) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("Error parsing --format: {}", e);
+            std::process::exit(error_exit);
+        }
     };
-    let formatter = OutputFormatter::from_cli(&args);
+
+    formatter.print_warnings(&formatter.separator_warnings());
+    if let Some(window) = filter_config.accessed_within {
+        if let Ok(mounts) = provider.list_mounts() {
+            formatter.print_warnings(&filter::atime_warnings(&mounts, window));
+        }
+    }
+
+    #[cfg(unix)]
+    if let Some(path) = &args.serve {
+        if let Err(e) = serve::serve(
+            Path::new(path),
+            &*provider,
+            &filter_config,
+            &scan_opts,
+            &formatter,
+        ) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
 
     // Handle repeat mode (-r)
     let repeat_interval = args.repeat;
 
     loop {
-        if let Err(e) = run_once(&*provider, &filter_config, &formatter) {
+        let result = run_once(
+            &*provider,
+            &filter_config,
+            &scan_opts,
+            &formatter,
+            &mut out,
+            args.split_output.as_deref().map(Path::new),
+        );
+        if let Err(e) = &result {
             eprintln!("Error: {}", e);
+        }
+        if args.exit_code_match {
+            drop(out);
+            std::process::exit(match_exit_code(&result));
+        }
+        if result.is_err() {
             std::process::exit(1);
         }
 
         match repeat_interval {
             Some(secs) => {
-                std::thread::sleep(std::time::Duration::from_secs(secs));
+                std::thread::sleep(repeat_delay(secs, args.jitter, random_u64()));
             }
             None => break,
         }
     }
 }
 
+/// Flush a report written to `out`, exiting if writing it failed.
+fn finish_report(out: &mut dyn Write, written: io::Result<()>) {
+    if let Err(e) = written.and_then(|_| out.flush()) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// The `--exit-code-match` status for a scan returning the number of
+/// matched processes: 0 if any matched, 1 if none, 2 on error.
+fn match_exit_code(result: &error::Result<usize>) -> i32 {
+    match result {
+        Ok(0) => 1,
+        Ok(_) => 0,
+        Err(_) => 2,
+    }
+}
+
+/// Sleep between `-r` iterations: the interval plus a random offset of up
+/// to `jitter_ms`, so many instances on a fleet drift apart instead of
+/// reading /proc in lockstep. `seed` supplies the randomness.
+fn repeat_delay(interval_secs: u64, jitter_ms: u64, seed: u64) -> Duration {
+    let offset = match jitter_ms {
+        0 => 0,
+        j => seed % (j + 1),
+    };
+    Duration::from_secs(interval_secs) + Duration::from_millis(offset)
+}
+
+/// A random value from the std hasher's per-process random keys, which is
+/// plenty for spreading out sleeps.
+fn random_u64() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default(),
+    );
+    hasher.finish()
+}
+
 fn run_once(
     provider: &dyn platform::PlatformProvider,
     filter_config: &FilterConfig,
+    scan_opts: &ScanOptions,
     formatter: &OutputFormatter,
-) -> error::Result<()> {
-    let mut processes = provider.list_processes()?;
-
-    // Step 1: Filter processes by process-level criteria.
-    processes.retain(|p| filter_config.matches_process(p));
-
-    // Step 2: For each matching process, get open files and apply file-level filters.
-    let has_file_filters = filter_config.inet.is_some()
-        || filter_config.dir_tree.is_some()
-        || filter_config.dir.is_some()
-        || !filter_config.names.is_empty();
-
-    for proc in &mut processes {
-        // Populate open files from the platform provider.
-        match provider.list_open_files(proc.pid) {
-            Ok(files) => proc.open_files = files,
-            Err(_) => {
-                // Permission denied or process gone -- skip silently.
-                continue;
-            }
+    out: &mut dyn Write,
+    split_output: Option<&Path>,
+) -> error::Result<usize> {
+    let print_warnings = |result: &scan::ScanResult| {
+        formatter.print_warnings(&result.warnings());
+        if let Some(hint) = result.privilege_hint(users::get_effective_uid()) {
+            formatter.print_warnings(&[hint.to_string()]);
         }
+    };
 
-        // Apply file-level filters if any are active.
-        if has_file_filters {
-            proc.open_files.retain(|f| filter_config.matches_file(f));
-        }
+    // Streamed `-t`: PIDs go out as they match, warnings once at the end.
+    if scan_opts.stream && split_output.is_none() {
+        let mut matched = 0;
+        let result = scan::scan_each(provider, filter_config, scan_opts, &mut |proc| {
+            matched += 1;
+            Ok(formatter.write_terse_pid(out, &proc)?)
+        })?;
+        print_warnings(&result);
+        return Ok(matched);
     }
 
-    // If file-level filters are active, remove processes with no matching files.
-    if has_file_filters {
-        processes.retain(|p| !p.open_files.is_empty());
-    }
+    let result = scan::scan(provider, filter_config, scan_opts)?;
+    print_warnings(&result);
+    let processes = result.processes;
+    // `+c 0`: size the COMMAND column to this result set.
+    let sized = formatter.sized_for(&processes);
+    let formatter = sized.as_ref().unwrap_or(formatter);
 
-    // Step 3: Output.
-    if formatter.terse {
-        formatter.print_terse(&processes);
-    } else if formatter.field_output.is_some() {
-        for proc in &processes {
-            formatter.print_field_output(proc);
-        }
+    // Output.
+    if let Some(dir) = split_output {
+        formatter.write_split_output(dir, &processes)?;
     } else {
-        formatter.print_header();
-        for proc in &processes {
-            if proc.open_files.is_empty() {
-                // Print at least one line for the process even without open files.
-                let user_display = if formatter.list_uid {
-                    proc.uid.to_string()
-                } else {
-                    proc.user.clone()
-                };
-                let cmd = if proc.comm.len() > formatter.cmd_width {
-                    &proc.comm[..formatter.cmd_width]
-                } else {
-                    &proc.comm
-                };
-                if formatter.show_ppid {
-                    println!(
-                        "{:<width$} {:>5} {:>5} {:<8}",
-                        cmd,
-                        proc.pid,
-                        proc.ppid.map(|p| p.to_string()).unwrap_or_default(),
-                        user_display,
-                        width = formatter.cmd_width,
-                    );
-                } else {
-                    println!(
-                        "{:<width$} {:>5} {:<8}",
-                        cmd,
-                        proc.pid,
-                        user_display,
-                        width = formatter.cmd_width,
-                    );
-                }
-            } else {
-                formatter.print_process_files(proc);
-            }
-        }
+        formatter.write_report(out, &processes)?;
     }
+    out.flush()?;
 
-    Ok(())
+    Ok(processes.len())
 }
 
 fn run_tui(provider: &dyn platform::PlatformProvider) -> std::io::Result<()> {
@@ -177,15 +509,22 @@ fn run_tui(provider: &dyn platform::PlatformProvider) -> std::io::Result<()> {
         match event_handler.next()? {
             AppEvent::Key(key) => {
                 let search_empty = state.search_input.is_empty();
-                if let Some(action) = map_key_to_action(key, &state.mode, search_empty) {
-                    dispatch_action(&mut state, action, provider);
+                match map_key_to_action(key, &state.mode, search_empty) {
+                    // Needs the terminal, so it can't go through dispatch_action.
+                    Some(Action::OpenInPager) => open_in_pager(&mut terminal, &mut state),
+                    Some(action) => dispatch_action(&mut state, action, provider),
+                    None => {}
                 }
             }
             AppEvent::Resize(_, _) => {
                 // Terminal will redraw on next loop iteration
             }
             AppEvent::Tick => {
-                // Nothing to do on tick for now
+                state.expire_status(Instant::now());
+                if let app::state::ViewMode::Search | app::state::ViewMode::FilterInput = state.mode
+                {
+                    state.poll_preview(provider);
+                }
             }
         }
 
@@ -205,6 +544,73 @@ fn run_tui(provider: &dyn platform::PlatformProvider) -> std::io::Result<()> {
     Ok(())
 }
 
+/// Suspend the TUI, run `$PAGER` (else `$EDITOR`, else `less`) on the
+/// highlighted file, and take the screen back once it exits.
+fn open_in_pager(terminal: &mut ratatui::DefaultTerminal, state: &mut AppState) {
+    let path = state
+        .selected_process
+        .as_ref()
+        .and_then(|proc| state.selected_openable_path(&proc.open_files));
+    let Some(path) = path else {
+        state.set_status("Only regular files can be opened", StatusKind::Warn);
+        return;
+    };
+    let viewer = ["PAGER", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| "less".to_string());
+    // Allow `PAGER="less -R"` style values.
+    let mut words = viewer.split_whitespace();
+    let program = words.next().unwrap_or("less");
+
+    ratatui::restore();
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(&path)
+        .status();
+    *terminal = ratatui::init();
+    let _ = terminal.clear();
+
+    match status {
+        Ok(s) if s.success() => {}
+        Ok(s) => state.set_status(format!("{} exited with {}", program, s), StatusKind::Warn),
+        Err(e) => state.set_status(format!("Cannot run {}: {}", program, e), StatusKind::Error),
+    }
+}
+
+/// Put `text` on the terminal's clipboard with an OSC 52 sequence, which
+/// works over SSH and needs no clipboard tool. It goes to stderr, which is
+/// still the terminal, so it doesn't interleave with the TUI's frames;
+/// terminals without OSC 52 ignore it and the toast still shows the text.
+fn copy_to_clipboard(text: &str) {
+    let mut stderr = io::stderr().lock();
+    let _ = write!(stderr, "\x1b]52;c;{}\x07", base64(text.as_bytes()));
+    let _ = stderr.flush();
+}
+
+/// Standard base64 with padding, for OSC 52.
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
 fn dispatch_action(
     state: &mut AppState,
     action: Action,
@@ -238,6 +644,12 @@ fn dispatch_action(
         Action::PageDown => {
             state.page_down();
         }
+        Action::MoveTop => {
+            state.move_top();
+        }
+        Action::MoveBottom => {
+            state.move_bottom();
+        }
         Action::Select => {
             state.select_current();
             // Populate open files for the selected process
@@ -259,20 +671,132 @@ fn dispatch_action(
         Action::Refresh => {
             state.refresh(provider);
         }
+        Action::RefreshDetail => {
+            state.refresh_selected(provider);
+        }
         Action::YankSelected => {
             if let Some(ref proc) = state.selected_process {
-                if let Some(line) = state.yank_selected_line(&proc.open_files) {
-                    // Print to stderr so it doesn't interfere with TUI
-                    eprintln!("\x1b[33m[Yanked]\x1b[0m {}", line);
+                let yanked = state.yank_selected_line(&proc.open_files);
+                match yanked {
+                    Some(line) => {
+                        copy_to_clipboard(&line);
+                        state.set_status(format!("Yanked: {}", line), StatusKind::Info)
+                    }
+                    None => state.set_status("Nothing to yank", StatusKind::Warn),
                 }
             }
         }
-        Action::ExportProcess => {
+        Action::CopyPath => {
             if let Some(ref proc) = state.selected_process {
-                let data = state.export_process_data(proc, &proc.open_files);
-                state.export_data = Some(data);
-                state.should_quit = true;
+                match state.selected_path(&proc.open_files).map(str::to_string) {
+                    Some(path) => {
+                        copy_to_clipboard(&path);
+                        state.set_status(format!("Copied: {}", path), StatusKind::Info)
+                    }
+                    None => state.set_status("No file selected", StatusKind::Warn),
+                }
             }
         }
+        Action::ToggleTypeFilter(filter) => {
+            state.toggle_type_filter(filter);
+        }
+        Action::ToggleFullCommand => {
+            state.toggle_full_command();
+        }
+        Action::CycleColumns => {
+            state.cycle_columns();
+        }
+        Action::SortFiles(key) => {
+            state.sort_files_by(key);
+        }
+        Action::StartFilter => {
+            state.start_filter_input();
+        }
+        Action::FilterInput(c) => {
+            state.filter_expr.push(c);
+            state.apply_filter_expr(provider);
+        }
+        Action::FilterBackspace => {
+            state.filter_expr.pop();
+            state.apply_filter_expr(provider);
+        }
+        Action::FilterClear => {
+            state.filter_expr.clear();
+            state.apply_filter_expr(provider);
+        }
+        Action::FilterDone => {
+            state.finish_filter_input();
+        }
+        Action::FilterCancel => {
+            state.cancel_filter_input();
+        }
+        // Handled in run_tui, which owns the terminal.
+        Action::OpenInPager => {}
+        Action::ExportProcess => {
+            state.open_export_menu();
+        }
+        Action::ExportConfirm => {
+            state.confirm_export();
+        }
+        Action::ExportCancel => {
+            state.cancel_export_menu();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures;
+
+    #[test]
+    fn test_base64_for_osc52() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"/etc/hosts"), "L2V0Yy9ob3N0cw==");
+    }
+
+    #[test]
+    fn test_exit_code_reflects_matches() {
+        use platform::mock::MockProvider;
+
+        let proc = model::ProcessInfo {
+            user: "www".to_string(),
+            uid: 33,
+            ..fixtures::process(42, "nginx", Vec::new())
+        };
+        let provider = MockProvider::new(vec![proc]);
+        let run = |expr: &str| {
+            let filter = FilterConfig::parse_expr(expr).unwrap();
+            let mut out = Vec::new();
+            let result = run_once(
+                &provider,
+                &filter,
+                &ScanOptions::default(),
+                &OutputFormatter::default(),
+                &mut out,
+                None,
+            );
+            match_exit_code(&result)
+        };
+        assert_eq!(run("cmd:nginx"), 0);
+        assert_eq!(run("cmd:postgres"), 1);
+        assert_eq!(
+            match_exit_code(&Err(error::LoofError::ProcessNotFound(1))),
+            2
+        );
+    }
+
+    #[test]
+    fn test_repeat_delay_stays_within_jitter() {
+        let interval = Duration::from_secs(2);
+        for seed in [0, 1, 250, 251, 999, u64::MAX, random_u64(), random_u64()] {
+            let delay = repeat_delay(2, 250, seed);
+            assert!(delay >= interval && delay <= interval + Duration::from_millis(250));
+        }
+        assert_eq!(repeat_delay(2, 250, 250), Duration::from_millis(2250));
+        assert_eq!(repeat_delay(2, 0, 12345), interval);
     }
 }
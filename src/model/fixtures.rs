@@ -0,0 +1,54 @@
+//! Builders for the processes and open files that unit tests need. Fields
+//! that aren't arguments keep their defaults; set them with struct update
+//! syntax (`OpenFileInfo { size_off: Some(4), ..file(...) }`).
+
+use super::{Connection, FdMode, FdType, FileType, OpenFileInfo, ProcessInfo, TcpState};
+use std::net::SocketAddr;
+
+/// A root process running `comm` (also its command line).
+pub fn process(pid: u32, comm: &str, open_files: Vec<OpenFileInfo>) -> ProcessInfo {
+    ProcessInfo {
+        pid,
+        command: comm.to_string(),
+        comm: comm.to_string(),
+        user: "root".to_string(),
+        open_files,
+        ..Default::default()
+    }
+}
+
+/// An open file `name` of `file_type` on `fd`, with the mode of a numbered
+/// FD filled in.
+pub fn file(fd: FdType, file_type: FileType, name: &str) -> OpenFileInfo {
+    let mode = match fd {
+        FdType::Numbered(_, mode) => Some(mode),
+        _ => None,
+    };
+    OpenFileInfo {
+        fd,
+        file_type,
+        name: name.to_string(),
+        mode,
+        ..Default::default()
+    }
+}
+
+/// A TCP socket on `fd` from `local` to `remote` (`addr:port`, `[v6]:port`)
+/// in `state`, named the way the providers name it.
+pub fn tcp_socket(fd: u32, local: &str, remote: &str, state: TcpState) -> OpenFileInfo {
+    let remote: SocketAddr = remote.parse().unwrap();
+    let file_type = if remote.is_ipv6() {
+        FileType::IPv6
+    } else {
+        FileType::IPv4
+    };
+    let name = format!("{} -> {} ({})", local, remote, state);
+    OpenFileInfo {
+        node: "TCP".to_string(),
+        connection: Some(Connection {
+            remote,
+            tcp_state: Some(state),
+        }),
+        ..file(FdType::Numbered(fd, FdMode::ReadWrite), file_type, &name)
+    }
+}
@@ -1,7 +1,11 @@
+#[cfg(test)]
+pub mod fixtures;
+pub mod mount;
 pub mod network;
 pub mod open_file;
 pub mod process;
 
+pub use mount::MountInfo;
 pub use network::{NetworkInfo, Protocol, TcpState};
-pub use open_file::{FdMode, FdType, FileType, OpenFileInfo};
-pub use process::ProcessInfo;
+pub use open_file::{Connection, FdMode, FdType, FileType, LockInfo, MapInfo, OpenFileInfo};
+pub use process::{ProcState, ProcessInfo};
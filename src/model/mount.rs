@@ -0,0 +1,11 @@
+/// A mounted filesystem, keyed by the device number its files report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MountInfo {
+    /// Device in the same "major,minor" form used by `OpenFileInfo::device`.
+    pub device: String,
+    pub mount_point: String,
+    pub fstype: String,
+    /// Mount options such as `rw`, `relatime` or `noatime`; empty when the
+    /// platform doesn't report them.
+    pub options: Vec<String>,
+}
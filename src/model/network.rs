@@ -48,9 +48,33 @@ pub enum TcpState {
     Closing,
     LastAck,
     Closed,
+    /// No connection state: unconnected UDP and Unix sockets.
+    None,
     Unknown(String),
 }
 
+impl TcpState {
+    /// The state named `name` as printed (`CLOSE_WAIT`), ignoring case.
+    /// `NONE` is not a TCP state and is rejected.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let state = match name.to_ascii_uppercase().as_str() {
+            "LISTEN" => TcpState::Listen,
+            "ESTABLISHED" => TcpState::Established,
+            "CLOSE_WAIT" => TcpState::CloseWait,
+            "TIME_WAIT" => TcpState::TimeWait,
+            "SYN_SENT" => TcpState::SynSent,
+            "SYN_RECV" => TcpState::SynRecv,
+            "FIN_WAIT1" => TcpState::FinWait1,
+            "FIN_WAIT2" => TcpState::FinWait2,
+            "CLOSING" => TcpState::Closing,
+            "LAST_ACK" => TcpState::LastAck,
+            "CLOSED" => TcpState::Closed,
+            _ => return None,
+        };
+        Some(state)
+    }
+}
+
 impl fmt::Display for TcpState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -65,6 +89,7 @@ impl fmt::Display for TcpState {
             TcpState::Closing => write!(f, "CLOSING"),
             TcpState::LastAck => write!(f, "LAST_ACK"),
             TcpState::Closed => write!(f, "CLOSED"),
+            TcpState::None => write!(f, "NONE"),
             TcpState::Unknown(s) => write!(f, "{}", s),
         }
     }
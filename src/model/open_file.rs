@@ -1,4 +1,7 @@
+use super::TcpState;
 use std::fmt;
+use std::net::SocketAddr;
+use std::time::SystemTime;
 
 #[derive(Debug, Clone)]
 pub struct OpenFileInfo {
@@ -13,6 +16,107 @@ pub struct OpenFileInfo {
     pub link_target: Option<String>,
     pub send_queue: Option<u64>,
     pub recv_queue: Option<u64>,
+    /// Lock held on the file through this FD, if any.
+    pub lock: Option<LockInfo>,
+    /// Last access time from the stat data, if it was collected.
+    pub atime: Option<SystemTime>,
+    /// Whether the FD is close-on-exec (`O_CLOEXEC`); `None` for entries
+    /// that aren't FDs or when the flags can't be read.
+    pub cloexec: Option<bool>,
+    /// How a `mem` entry is mapped; `None` for everything else.
+    pub mapping: Option<MapInfo>,
+    /// Whether the path still resolves (stat succeeds, following links);
+    /// `None` when the entry has no path or wasn't stat'ed.
+    pub resolved: Option<bool>,
+    /// Far end and state of an IP socket; `None` for everything else.
+    pub connection: Option<Connection>,
+}
+
+impl OpenFileInfo {
+    /// The state of a TCP socket; `None` for every other entry.
+    pub fn tcp_state(&self) -> Option<&TcpState> {
+        self.connection.as_ref()?.tcp_state.as_ref()
+    }
+}
+
+/// An entry with nothing collected (FD 0 of unknown type, no name), so
+/// providers and tests only spell out what they know.
+impl Default for OpenFileInfo {
+    fn default() -> Self {
+        OpenFileInfo {
+            fd: FdType::Numbered(0, FdMode::Unknown),
+            file_type: FileType::Unknown(String::new()),
+            device: String::new(),
+            size_off: None,
+            node: String::new(),
+            name: String::new(),
+            mode: None,
+            link_target: None,
+            send_queue: None,
+            recv_queue: None,
+            lock: None,
+            atime: None,
+            cloexec: None,
+            mapping: None,
+            resolved: None,
+            connection: None,
+        }
+    }
+}
+
+/// The far end of an IP socket and, for TCP, its state: the parts of the
+/// `LOCAL -> REMOTE (STATE)` name that filters and reports match on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Connection {
+    pub remote: SocketAddr,
+    /// `None` for UDP and raw IP sockets.
+    pub tcp_state: Option<TcpState>,
+}
+
+/// The sharing and write permission of a memory mapping. With several
+/// mappings of one file, the riskiest one is kept (see [`MapInfo::rank`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapInfo {
+    /// `MAP_SHARED`: writes reach the file and other mappers.
+    pub shared: bool,
+    pub writable: bool,
+}
+
+impl MapInfo {
+    /// `ws`, `wp`, `rs` or `rp`: write or read-only, then shared or private,
+    /// like the permission column of `/proc/[pid]/maps`.
+    pub fn code(&self) -> String {
+        let access = if self.writable { 'w' } else { 'r' };
+        let sharing = if self.shared { 's' } else { 'p' };
+        format!("{}{}", access, sharing)
+    }
+
+    /// Ordering used to pick one mapping per file: shared and writable
+    /// first, then writable, then shared.
+    pub fn rank(&self) -> (bool, bool, bool) {
+        (self.shared && self.writable, self.writable, self.shared)
+    }
+}
+
+/// A file lock (POSIX `fcntl`, OFD or `flock`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LockInfo {
+    pub write: bool,
+    /// Whether the lock covers the whole file rather than a byte range.
+    pub whole_file: bool,
+}
+
+impl LockInfo {
+    /// lsof's lock character, shown after the FD mode: `W`/`R` for a
+    /// whole-file write/read lock, `w`/`r` for a partial one.
+    pub fn code(&self) -> char {
+        match (self.write, self.whole_file) {
+            (true, true) => 'W',
+            (true, false) => 'w',
+            (false, true) => 'R',
+            (false, false) => 'r',
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -56,6 +160,48 @@ impl fmt::Display for FileType {
     }
 }
 
+impl FileType {
+    /// Every fixed `TYPE` value, in `--list-types` order. `Unknown` carries
+    /// a kernel-provided label and is described separately.
+    pub const VARIANTS: [FileType; 13] = [
+        FileType::Reg,
+        FileType::Dir,
+        FileType::Chr,
+        FileType::Blk,
+        FileType::Fifo,
+        FileType::Pipe,
+        FileType::Link,
+        FileType::Sock,
+        FileType::IPv4,
+        FileType::IPv6,
+        FileType::Unix,
+        FileType::Kqueue,
+        FileType::Systm,
+    ];
+
+    /// One-line description for `--list-types`.
+    pub fn description(&self) -> &'static str {
+        match self {
+            FileType::Reg => "regular file",
+            FileType::Dir => "directory",
+            FileType::Chr => "character device",
+            FileType::Blk => "block device",
+            FileType::Fifo => "named pipe (FIFO)",
+            FileType::Pipe => "anonymous pipe",
+            FileType::Link => "symbolic link",
+            FileType::Sock => "socket of another family (netlink, packet, ...)",
+            FileType::IPv4 => "IPv4 socket",
+            FileType::IPv6 => "IPv6 socket",
+            FileType::Unix => "unix domain socket",
+            FileType::Kqueue => "kqueue (macOS)",
+            FileType::Systm => "kernel event socket (macOS)",
+            FileType::Unknown(_) => {
+                "other kernel-reported kinds, e.g. a_inode, PIDFD, BPFMAP, IOURING"
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum FdType {
     #[allow(dead_code)]
@@ -83,6 +229,31 @@ impl fmt::Display for FdType {
     }
 }
 
+impl FdType {
+    /// The named (non-numeric) FD labels, in `--list-fd-types` order.
+    pub const NAMED: [FdType; 5] = [
+        FdType::Cwd,
+        FdType::Rtd,
+        FdType::Txt,
+        FdType::Mem,
+        FdType::Mmap,
+    ];
+
+    /// One-line description for `--list-fd-types`.
+    pub fn description(&self) -> &'static str {
+        match self {
+            FdType::Cwd => "current working directory",
+            FdType::Rtd => "root directory",
+            FdType::Txt => "program text (executable)",
+            FdType::Mem => "memory-mapped file",
+            FdType::Mmap => "memory-mapped device",
+            FdType::Numbered(..) => {
+                "file descriptor number followed by its mode: r read, w write, u read/write"
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FdMode {
     Read,
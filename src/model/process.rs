@@ -1,10 +1,87 @@
-use super::open_file::OpenFileInfo;
+use std::fmt;
 
-#[derive(Debug, Clone)]
+use super::open_file::{FdType, OpenFileInfo};
+
+/// A process's scheduler state, using the `ps`/`/proc/[pid]/stat` letters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcState {
+    Running,
+    Sleeping,
+    /// Uninterruptible (usually disk) sleep.
+    DiskSleep,
+    Zombie,
+    /// Stopped by a signal or a tracer.
+    Stopped,
+    Idle,
+    Other(char),
+}
+
+impl ProcState {
+    /// Map a `/proc/[pid]/stat` state letter. `t` (tracing stop) counts as
+    /// stopped.
+    pub fn from_code(c: char) -> Self {
+        match c {
+            'R' => ProcState::Running,
+            'S' => ProcState::Sleeping,
+            'D' => ProcState::DiskSleep,
+            'Z' => ProcState::Zombie,
+            'T' | 't' => ProcState::Stopped,
+            'I' => ProcState::Idle,
+            other => ProcState::Other(other),
+        }
+    }
+
+    /// Map macOS `pbi_status` (`SIDL`=1, `SRUN`, `SSLEEP`, `SSTOP`, `SZOMB`=5).
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub fn from_bsd_status(status: u32) -> Option<Self> {
+        match status {
+            1 => Some(ProcState::Idle),
+            2 => Some(ProcState::Running),
+            3 => Some(ProcState::Sleeping),
+            4 => Some(ProcState::Stopped),
+            5 => Some(ProcState::Zombie),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> char {
+        match self {
+            ProcState::Running => 'R',
+            ProcState::Sleeping => 'S',
+            ProcState::DiskSleep => 'D',
+            ProcState::Zombie => 'Z',
+            ProcState::Stopped => 'T',
+            ProcState::Idle => 'I',
+            ProcState::Other(c) => c,
+        }
+    }
+}
+
+impl fmt::Display for ProcState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct ProcessInfo {
     pub pid: u32,
     pub ppid: Option<u32>,
     pub pgid: Option<u32>,
+    /// PID inside the process's innermost PID namespace (e.g. a container),
+    /// when it differs from `pid`. Only read when asked for.
+    pub nspid: Option<u32>,
+    /// Scheduler state (running, sleeping, zombie, ...), when known.
+    pub state: Option<ProcState>,
+    /// Full ID of the container the process runs in, from its cgroup path
+    /// (Linux; docker, containerd, CRI-O and podman). Only read when asked for.
+    pub container: Option<String>,
+    /// SELinux or SMACK label from `/proc/[pid]/attr/current` (Linux).
+    /// `None` when no security module provides one or it wasn't asked for.
+    pub security_context: Option<String>,
+    /// Soft and hard `RLIMIT_NOFILE` ("Max open files" in
+    /// `/proc/[pid]/limits`, Linux); `u64::MAX` means unlimited.
+    pub fd_limit: Option<(u64, u64)>,
     #[allow(dead_code)]
     pub command: String,
     pub comm: String,
@@ -13,9 +90,36 @@ pub struct ProcessInfo {
     pub open_files: Vec<OpenFileInfo>,
 }
 
+/// Format a numbered FD count for display: `open/soft-limit` (e.g.
+/// `820/1024`) when the limit is known, otherwise just the count.
+pub fn fd_usage_label(numbered: usize, limit: Option<(u64, u64)>) -> String {
+    match limit {
+        Some((u64::MAX, _)) => format!("{}/∞", numbered),
+        Some((soft, _)) => format!("{}/{}", numbered, soft),
+        None => numbered.to_string(),
+    }
+}
+
 impl ProcessInfo {
-    pub fn fd_count(&self) -> usize {
-        self.open_files.len()
+    /// Open numbered FDs, the ones `RLIMIT_NOFILE` counts.
+    pub fn numbered_fd_count(&self) -> usize {
+        self.open_files
+            .iter()
+            .filter(|f| matches!(f.fd, FdType::Numbered(..)))
+            .count()
+    }
+
+    /// The numbered FD count for display (see [`fd_usage_label`]).
+    pub fn fd_usage(&self) -> String {
+        fd_usage_label(self.numbered_fd_count(), self.fd_limit)
+    }
+
+    /// Whether the process uses at least 80% of its soft FD limit.
+    pub fn near_fd_limit(&self) -> bool {
+        match self.fd_limit {
+            Some((soft, _)) if soft != u64::MAX => self.numbered_fd_count() as u64 * 5 >= soft * 4,
+            _ => false,
+        }
     }
 
     #[allow(dead_code)]
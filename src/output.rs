@@ -1,7 +1,19 @@
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
 use crate::cli::CliArgs;
-use crate::model::{FileType, ProcessInfo};
+use crate::error::{LoofError, Result};
+use crate::model::{FileType, OpenFileInfo, ProcessInfo};
+use crate::ui::widgets::truncate_to_width;
+
+/// Version of the `--json` document layout. Bump it when keys are renamed,
+/// removed or change meaning; adding keys doesn't need a bump.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
 
 /// Formats process and open-file data in lsof-compatible output.
+#[derive(Clone)]
 pub struct OutputFormatter {
     /// Maximum width for the COMMAND column (default 9, configurable via +c).
     pub cmd_width: usize,
@@ -15,79 +27,259 @@ pub struct OutputFormatter {
     pub list_uid: bool,
     /// `-R` flag: show PPID column.
     pub show_ppid: bool,
+    /// Width of the PID column: 5, or the longest `host/ns` label once
+    /// sized for a result set (see [`sized_for`](Self::sized_for)).
+    pub pid_width: usize,
+    /// `--show-nspid` flag: print the PID as `host/ns` for processes in
+    /// another PID namespace.
+    pub show_nspid: bool,
+    /// `--wide` flag: add the STATE and CONTAINER columns.
+    pub wide: bool,
+    /// `--context` flag: add the CONTEXT column.
+    pub show_context: bool,
     /// `-t` flag: terse output (PIDs only).
     pub terse: bool,
     /// `-F` flag: field-delimited output with the given field characters.
     pub field_output: Option<String>,
     /// `-T` flag: TCP/TPI info (s=state, q=queue sizes).
     pub tcp_info: Option<String>,
+    /// `-w` flag: suppress warnings on stderr.
+    pub suppress_warnings: bool,
+    /// `--format` flag: custom per-file output template.
+    pub template: Option<OutputTemplate>,
+    /// `--json` flag: emit JSON instead of columns.
+    pub json: bool,
+    /// `--json-flat` flag: the bare process array, without the
+    /// `schema_version` wrapper.
+    pub json_flat: bool,
+    /// `--hex-device` flag: print DEVICE as `0xMAJ,MIN` in hex (the
+    /// historical macOS style) instead of decimal `major,minor`.
+    pub hex_device: bool,
+    /// `--missing` flag: tag names that no longer resolve with ` (missing)`.
+    pub mark_missing: bool,
+    /// `--max-name-width` flag: cut the NAME column to this many terminal
+    /// columns, ending in `…`. Not applied to `-F` or JSON output.
+    pub max_name_width: Option<usize>,
+    /// `--field-sep` flag: what ends each `-F` field (default newline).
+    pub field_sep: char,
+    /// `--record-sep` flag: written after each process's `-F` fields.
+    pub record_sep: Option<char>,
+}
+
+impl Default for OutputFormatter {
+    fn default() -> Self {
+        OutputFormatter {
+            cmd_width: 9,
+            no_hostname: false,
+            no_portname: false,
+            list_uid: false,
+            show_ppid: false,
+            pid_width: 5,
+            show_nspid: false,
+            wide: false,
+            show_context: false,
+            terse: false,
+            field_output: None,
+            tcp_info: None,
+            suppress_warnings: false,
+            template: None,
+            json: false,
+            json_flat: false,
+            hex_device: false,
+            mark_missing: false,
+            max_name_width: None,
+            field_sep: '\n',
+            record_sep: None,
+        }
+    }
 }
 
 impl OutputFormatter {
     /// Build an `OutputFormatter` from parsed CLI arguments.
-    pub fn from_cli(args: &CliArgs) -> Self {
-        OutputFormatter {
+    ///
+    /// Fails if the `--format` template references an unknown field.
+    pub fn from_cli(args: &CliArgs) -> Result<Self> {
+        let template = match &args.format {
+            Some(t) => Some(OutputTemplate::parse(t)?),
+            None => None,
+        };
+        Ok(OutputFormatter {
             cmd_width: args.cmd_width.unwrap_or(9),
             no_hostname: args.no_hostname,
             no_portname: args.no_portname,
             list_uid: args.list_uid,
             show_ppid: args.show_ppid,
+            pid_width: 5,
+            show_nspid: args.show_nspid,
+            wide: args.wide,
+            show_context: args.context,
             terse: args.terse,
             field_output: args.field_output.clone(),
             tcp_info: args.tcp_info.clone(),
+            suppress_warnings: args.suppress_warnings,
+            template,
+            json: args.json,
+            json_flat: args.json_flat,
+            hex_device: args.hex_device,
+            mark_missing: args.missing,
+            max_name_width: args.max_name_width,
+            field_sep: args.field_sep.unwrap_or('\n'),
+            record_sep: args.record_sep,
+        })
+    }
+
+    /// A copy whose columns are sized to `processes` so the ones after them
+    /// still line up: with `+c 0` (no truncation) the COMMAND column is as
+    /// wide as the longest command, and with `--show-nspid` the PID column
+    /// as wide as the longest `host/ns` label. `None` when nothing changes.
+    pub fn sized_for(&self, processes: &[ProcessInfo]) -> Option<Self> {
+        let cmd_width = match self.cmd_width {
+            0 => processes
+                .iter()
+                .map(|p| p.comm.chars().count())
+                .chain(["COMMAND".len()])
+                .max()
+                .unwrap_or_default(),
+            fixed => fixed,
+        };
+        let pid_width = if self.show_nspid {
+            processes
+                .iter()
+                .map(|p| self.pid_label(p).len())
+                .chain([self.pid_width])
+                .max()
+                .unwrap_or_default()
+        } else {
+            self.pid_width
+        };
+        if cmd_width == self.cmd_width && pid_width == self.pid_width {
+            return None;
         }
+        Some(OutputFormatter {
+            cmd_width,
+            pid_width,
+            ..self.clone()
+        })
     }
 
-    /// Print the standard lsof-style column header line.
-    pub fn print_header(&self) {
-        if self.show_ppid {
-            println!(
-                "{:<width$} {:>5} {:>5} {:<8} {:>4}  {:>6} {:>8}  {:>8}  {:>4} NAME",
-                "COMMAND",
-                "PID",
-                "PPID",
-                "USER",
-                "FD",
-                "TYPE",
-                "DEVICE",
-                "SIZE/OFF",
-                "NODE",
-                width = self.cmd_width,
-            );
+    /// Write warnings in lsof's `WARNING:` style, unless `-w` was given.
+    pub fn write_warnings(&self, w: &mut dyn Write, warnings: &[String]) -> io::Result<()> {
+        if self.suppress_warnings {
+            return Ok(());
+        }
+        for msg in warnings {
+            writeln!(w, "loof: WARNING: {}", msg)?;
+        }
+        Ok(())
+    }
+
+    /// Print warnings to stderr (see [`write_warnings`](Self::write_warnings)).
+    pub fn print_warnings(&self, warnings: &[String]) {
+        let _ = self.write_warnings(&mut io::stderr().lock(), warnings);
+    }
+
+    /// The DEVICE value as it should be printed.
+    fn device<'a>(&self, device: &'a str) -> Cow<'a, str> {
+        if self.hex_device {
+            Cow::Owned(hex_device(device))
         } else {
-            println!(
-                "{:<width$} {:>5} {:<8} {:>4}  {:>6} {:>8}  {:>8}  {:>4} NAME",
-                "COMMAND",
+            Cow::Borrowed(device)
+        }
+    }
+
+    /// The standard lsof-style column header line.
+    pub fn header_line(&self) -> String {
+        let command = format!("{:<width$}", "COMMAND", width = self.cmd_width);
+        format!(
+            "{} {:>4}  {:>6} {:>8}  {:>8}  {:>4} NAME",
+            self.process_columns(
+                &command,
                 "PID",
+                "PPID",
                 "USER",
-                "FD",
-                "TYPE",
-                "DEVICE",
-                "SIZE/OFF",
-                "NODE",
-                width = self.cmd_width,
-            );
+                ["STATE", "CONTAINER", "CONTEXT"]
+            ),
+            "FD",
+            "TYPE",
+            "DEVICE",
+            "SIZE/OFF",
+            "NODE",
+        )
+    }
+
+    /// The per-process leading columns: COMMAND, PID, PPID (with `-R`),
+    /// USER, then STATE and CONTAINER (with `--wide`) and CONTEXT (with
+    /// `--context`), given in that order in `extra`.
+    fn process_columns(
+        &self,
+        cmd: &str,
+        pid: &str,
+        ppid: &str,
+        user: &str,
+        extra: [&str; 3],
+    ) -> String {
+        let [state, container, context] = extra;
+        let mut out = format!("{} {:>width$}", cmd, pid, width = self.pid_width);
+        if self.show_ppid {
+            out.push_str(&format!(" {:>5}", ppid));
         }
+        out.push_str(&format!(" {:<8}", user));
+        if self.wide {
+            out.push_str(&format!(" {:<5} {:<12}", state, container));
+        }
+        if self.show_context {
+            out.push_str(&format!(" {:<28}", context));
+        }
+        out
+    }
+
+    /// Write the standard lsof-style column header line.
+    pub fn write_header(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "{}", self.header_line())
     }
 
-    /// Print one line per open file for a process, in standard lsof format.
-    pub fn print_process_files(&self, proc: &ProcessInfo) {
+    /// Lines for a process in standard lsof format: one per open file, or a
+    /// single process-only line when it has none.
+    pub fn process_lines(&self, proc: &ProcessInfo) -> Vec<String> {
         let cmd = fit_str(&proc.comm, self.cmd_width);
         let user_display = if self.list_uid {
             proc.uid.to_string()
         } else {
             proc.user.clone()
         };
+        let prefix = self.process_columns(
+            &cmd,
+            &self.pid_label(proc),
+            &proc.ppid.map(|p| p.to_string()).unwrap_or_default(),
+            &user_display,
+            [
+                &proc.state.map(|s| s.to_string()).unwrap_or_default(),
+                proc.container
+                    .as_deref()
+                    .map(short_container_id)
+                    .unwrap_or("-"),
+                proc.security_context.as_deref().unwrap_or("-"),
+            ],
+        );
 
+        if proc.open_files.is_empty() {
+            return vec![prefix];
+        }
+
+        let mut lines = Vec::with_capacity(proc.open_files.len());
         for file in &proc.open_files {
             let size_off = format_size_off(file.size_off);
             let mut display_name = file.name.clone();
 
-            // When -T flag includes "q", append queue sizes for network files.
+            // When -T flag includes "q", append queue sizes for sockets (TCP,
+            // UDP and unix) that report them.
             if let Some(ref tcp_flags) = self.tcp_info {
                 if tcp_flags.contains('q') {
-                    let is_network = matches!(file.file_type, FileType::IPv4 | FileType::IPv6);
-                    if is_network {
+                    let is_socket = matches!(
+                        file.file_type,
+                        FileType::IPv4 | FileType::IPv6 | FileType::Unix | FileType::Sock
+                    );
+                    if is_socket {
                         if let (Some(rq), Some(sq)) = (file.recv_queue, file.send_queue) {
                             display_name.push_str(&format!(" QR={} QS={}", rq, sq));
                         }
@@ -95,94 +287,527 @@ impl OutputFormatter {
                 }
             }
 
-            if self.show_ppid {
-                println!(
-                    "{} {:>5} {:>5} {:<8} {:>4}  {:>6} {:>8}  {:>8}  {:>4} {}",
-                    cmd,
-                    proc.pid,
-                    proc.ppid.map(|p| p.to_string()).unwrap_or_default(),
-                    user_display,
-                    file.fd,
-                    file.file_type,
-                    file.device,
-                    size_off,
-                    file.node,
-                    display_name,
-                );
+            // Deleted files already carry the kernel's "(deleted)" suffix.
+            if self.mark_missing
+                && file.resolved == Some(false)
+                && !display_name.ends_with("(deleted)")
+            {
+                display_name.push_str(" (missing)");
+            }
+
+            if let Some(width) = self.max_name_width {
+                display_name = truncate_to_width(&display_name, width);
+            }
+
+            lines.push(format!(
+                "{} {:>4}  {:>6} {:>8}  {:>8}  {:>4} {}",
+                prefix,
+                fd_label(file),
+                file.file_type,
+                self.device(&file.device),
+                size_off,
+                file.node,
+                display_name,
+            ));
+        }
+        lines
+    }
+
+    /// The PID column value: `host/ns` when `--show-nspid` is on and the
+    /// process has a namespace-local PID, otherwise just the PID.
+    pub fn pid_label(&self, proc: &ProcessInfo) -> String {
+        match proc.nspid {
+            Some(ns) if self.show_nspid => format!("{}/{}", proc.pid, ns),
+            _ => proc.pid.to_string(),
+        }
+    }
+
+    /// Write a process in standard lsof format (see [`process_lines`](Self::process_lines)).
+    pub fn write_process_files(&self, w: &mut dyn Write, proc: &ProcessInfo) -> io::Result<()> {
+        for line in self.process_lines(proc) {
+            writeln!(w, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    /// Write all processes as a JSON document (`--json`).
+    pub fn write_json(&self, w: &mut dyn Write, processes: &[ProcessInfo]) -> io::Result<()> {
+        writeln!(w, "{}", self.json_document(processes))
+    }
+
+    /// All processes as a JSON document: an object with `schema_version`
+    /// and `tool_version` ahead of the `processes` array, or just the array
+    /// with `--json-flat`.
+    pub fn json_document(&self, processes: &[ProcessInfo]) -> String {
+        let items: Vec<String> = processes.iter().map(|p| self.process_json(p)).collect();
+        let array = format!("[{}]", items.join(","));
+        if self.json_flat {
+            return array;
+        }
+        format!(
+            "{{\"schema_version\":{},\"tool_version\":{},\"processes\":{}}}",
+            JSON_SCHEMA_VERSION,
+            json_str(env!("CARGO_PKG_VERSION")),
+            array
+        )
+    }
+
+    /// A process and its open files as a single JSON object.
+    pub fn process_json(&self, proc: &ProcessInfo) -> String {
+        let files: Vec<String> = proc
+            .open_files
+            .iter()
+            .map(|f| {
+                format!(
+                    "{{\"fd\":{},\"type\":{},\"device\":{},\"size_off\":{},\"node\":{},\"name\":{},\"lock\":{}}}",
+                    json_str(&f.fd.to_string()),
+                    json_str(&f.file_type.to_string()),
+                    json_str(&self.device(&f.device)),
+                    json_opt(f.size_off),
+                    json_str(&f.node),
+                    json_str(&f.name),
+                    f.lock
+                        .map(|l| json_str(&l.code().to_string()))
+                        .unwrap_or_else(|| "null".to_string()),
+                )
+            })
+            .collect();
+        format!(
+            "{{\"pid\":{},\"ppid\":{},\"command\":{},\"user\":{},\"uid\":{},\"files\":[{}]}}",
+            proc.pid,
+            json_opt(proc.ppid),
+            json_str(&proc.comm),
+            json_str(&proc.user),
+            proc.uid,
+            files.join(","),
+        )
+    }
+
+    /// A process's open files as CSV: a header row, then one row per file
+    /// carrying the process's PID, command and user.
+    pub fn process_csv(&self, proc: &ProcessInfo) -> String {
+        let mut out = String::from("PID,COMMAND,USER,FD,TYPE,DEVICE,SIZE/OFF,NODE,NAME\n");
+        for f in &proc.open_files {
+            let row = [
+                proc.pid.to_string(),
+                proc.comm.clone(),
+                proc.user.clone(),
+                f.fd.to_string(),
+                f.file_type.to_string(),
+                self.device(&f.device).into_owned(),
+                f.size_off.map(|s| s.to_string()).unwrap_or_default(),
+                f.node.clone(),
+                f.name.clone(),
+            ];
+            let fields: Vec<String> = row.iter().map(|v| csv_field(v)).collect();
+            out.push_str(&fields.join(","));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Write each process to `<dir>/<pid>.txt` (or `.json` with `--json`)
+    /// instead of stdout, creating `dir` if needed (`--split-output`).
+    pub fn write_split_output(&self, dir: &Path, processes: &[ProcessInfo]) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        for proc in processes {
+            let (ext, contents) = if self.json {
+                ("json", format!("{}\n", self.process_json(proc)))
             } else {
-                println!(
-                    "{} {:>5} {:<8} {:>4}  {:>6} {:>8}  {:>8}  {:>4} {}",
-                    cmd,
-                    proc.pid,
-                    user_display,
-                    file.fd,
-                    file.file_type,
-                    file.device,
-                    size_off,
-                    file.node,
-                    display_name,
-                );
+                let mut text = self.header_line();
+                text.push('\n');
+                for line in self.process_lines(proc) {
+                    text.push_str(&line);
+                    text.push('\n');
+                }
+                ("txt", text)
+            };
+            fs::write(dir.join(format!("{}.{}", proc.pid, ext)), contents)?;
+        }
+        Ok(())
+    }
+
+    /// Write one line per open file using the `--format` template.
+    pub fn write_template(
+        &self,
+        w: &mut dyn Write,
+        template: &OutputTemplate,
+        proc: &ProcessInfo,
+    ) -> io::Result<()> {
+        for file in &proc.open_files {
+            if self.hex_device {
+                let file = OpenFileInfo {
+                    device: hex_device(&file.device),
+                    ..file.clone()
+                };
+                writeln!(w, "{}", template.render(proc, &file))?;
+            } else {
+                writeln!(w, "{}", template.render(proc, file))?;
             }
         }
+        Ok(())
     }
 
-    /// Print PIDs only (terse mode, `-t`).
-    pub fn print_terse(&self, processes: &[ProcessInfo]) {
+    /// Write PIDs only (terse mode, `-t`).
+    pub fn write_terse(&self, w: &mut dyn Write, processes: &[ProcessInfo]) -> io::Result<()> {
         for proc in processes {
-            println!("{}", proc.pid);
+            writeln!(w, "{}", proc.pid)?;
         }
+        Ok(())
+    }
+
+    /// Write one terse PID line and flush it, so a pipeline reading a
+    /// streamed `-t` scan sees each PID as soon as it matches.
+    pub fn write_terse_pid(&self, w: &mut dyn Write, proc: &ProcessInfo) -> io::Result<()> {
+        writeln!(w, "{}", proc.pid)?;
+        w.flush()
     }
 
-    /// Print field-delimited output (`-F` mode).
+    /// Write the full non-interactive report for `processes` in the
+    /// selected mode: terse, JSON, `--format` template, `-F` fields or the
+    /// standard columns with a header.
+    pub fn write_report(&self, w: &mut dyn Write, processes: &[ProcessInfo]) -> io::Result<()> {
+        if self.terse {
+            return self.write_terse(w, processes);
+        }
+        if self.json {
+            return self.write_json(w, processes);
+        }
+        if let Some(ref template) = self.template {
+            for proc in processes {
+                self.write_template(w, template, proc)?;
+            }
+        } else if self.field_output.is_some() {
+            for proc in processes {
+                self.write_field_output(w, proc)?;
+            }
+        } else {
+            self.write_header(w)?;
+            for proc in processes {
+                self.write_process_files(w, proc)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write field-delimited output (`-F` mode).
     ///
     /// Each field is printed on its own line as a single-character tag
-    /// followed by the value. A NUL character terminates each record set.
+    /// followed by the value. Process-level fields come first, then the
+    /// file-level fields for every open file. See [`FIELD_TABLE`] for the
+    /// supported characters; unknown ones are ignored.
     ///
-    /// Common field characters:
-    ///   p = PID, c = command, u = user, n = name, f = FD, t = type
-    pub fn print_field_output(&self, proc: &ProcessInfo) {
+    /// Values are never truncated: unlike classic lsof, `c` ignores `+c`
+    /// (as `n` always has), since `-F` output is read by scripts that want
+    /// the full data. `+c` only narrows the COMMAND column.
+    pub fn write_field_output(&self, w: &mut dyn Write, proc: &ProcessInfo) -> io::Result<()> {
         let fields = self.field_output.as_deref().unwrap_or("pcuftn");
+        let specs: Vec<&FieldSpec> = fields.chars().filter_map(field_spec).collect();
 
-        // Process-level fields
-        for ch in fields.chars() {
-            match ch {
-                'p' => println!("p{}", proc.pid),
-                'c' => println!("c{}", proc.comm),
-                'u' => {
-                    if self.list_uid {
-                        println!("u{}", proc.uid);
-                    } else {
-                        println!("u{}", proc.user);
-                    }
+        for spec in &specs {
+            if let FieldValue::Process(value) = spec.value {
+                if let Some(v) = value(self, proc) {
+                    write!(w, "{}{}{}", spec.ch, v, self.field_sep)?;
                 }
-                'R' => {
-                    if let Some(ppid) = proc.ppid {
-                        println!("R{}", ppid);
+            }
+        }
+
+        for file in &proc.open_files {
+            for spec in &specs {
+                if let FieldValue::File(value) = spec.value {
+                    if let Some(v) = value(self, file) {
+                        write!(w, "{}{}{}", spec.ch, v, self.field_sep)?;
                     }
                 }
-                'g' => println!("g{}", proc.pid), // PGID placeholder
-                _ => {}                           // file-level fields handled below
             }
         }
+        if let Some(sep) = self.record_sep {
+            write!(w, "{}", sep)?;
+        }
+        Ok(())
+    }
 
-        // File-level fields
-        for file in &proc.open_files {
-            for ch in fields.chars() {
-                match ch {
-                    'f' => println!("f{}", file.fd),
-                    't' => println!("t{}", file.file_type),
-                    'D' => println!("D{}", file.device),
-                    's' => {
-                        if let Some(sz) = file.size_off {
-                            println!("s{}", sz);
+    /// Warnings for `-F` separators a reader can't split on reliably: an
+    /// alphanumeric one can be mistaken for a field identifier, and equal
+    /// field and record separators hide process boundaries.
+    pub fn separator_warnings(&self) -> Vec<String> {
+        if self.field_output.is_none() {
+            return Vec::new();
+        }
+        let mut warnings = Vec::new();
+        for (flag, sep) in [
+            ("--field-sep", Some(self.field_sep)),
+            ("--record-sep", self.record_sep),
+        ] {
+            if sep.is_some_and(|c| c.is_alphanumeric()) {
+                warnings.push(format!(
+                    "{} {:?} can also start a field; output may not split cleanly",
+                    flag,
+                    sep.unwrap()
+                ));
+            }
+        }
+        if self.record_sep == Some(self.field_sep) {
+            warnings.push(
+                "--record-sep equals --field-sep; process boundaries are ambiguous".to_string(),
+            );
+        }
+        warnings
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Field output (`-F`)
+// ---------------------------------------------------------------------------
+
+/// How a `-F` field obtains its value. `None` omits the line.
+#[derive(Clone, Copy)]
+pub enum FieldValue {
+    Process(fn(&OutputFormatter, &ProcessInfo) -> Option<String>),
+    File(fn(&OutputFormatter, &OpenFileInfo) -> Option<String>),
+}
+
+/// One `-F` field character, what it emits, and how to compute it.
+pub struct FieldSpec {
+    pub ch: char,
+    pub description: &'static str,
+    pub value: FieldValue,
+}
+
+/// Every supported `-F` field, in `--fields-help` order. Both
+/// `write_field_output` and the help text are driven from this table.
+pub static FIELD_TABLE: &[FieldSpec] = &[
+    FieldSpec {
+        ch: 'p',
+        description: "process ID",
+        value: FieldValue::Process(|_, p| Some(p.pid.to_string())),
+    },
+    FieldSpec {
+        ch: 'c',
+        description: "command name (full, +c does not apply)",
+        value: FieldValue::Process(|_, p| Some(p.comm.clone())),
+    },
+    FieldSpec {
+        ch: 'u',
+        description: "user name (UID with -l)",
+        value: FieldValue::Process(|fmt, p| {
+            Some(if fmt.list_uid {
+                p.uid.to_string()
+            } else {
+                p.user.clone()
+            })
+        }),
+    },
+    FieldSpec {
+        ch: 'R',
+        description: "parent process ID",
+        value: FieldValue::Process(|_, p| p.ppid.map(|v| v.to_string())),
+    },
+    FieldSpec {
+        ch: 'g',
+        description: "process group ID",
+        value: FieldValue::Process(|_, p| p.pgid.map(|v| v.to_string())),
+    },
+    FieldSpec {
+        ch: 'f',
+        description: "file descriptor",
+        value: FieldValue::File(|_, f| Some(f.fd.to_string())),
+    },
+    FieldSpec {
+        ch: 't',
+        description: "file type",
+        value: FieldValue::File(|_, f| Some(f.file_type.to_string())),
+    },
+    FieldSpec {
+        ch: 'D',
+        description: "device number",
+        value: FieldValue::File(|fmt, f| Some(fmt.device(&f.device).into_owned())),
+    },
+    FieldSpec {
+        ch: 's',
+        description: "file size or offset",
+        value: FieldValue::File(|_, f| f.size_off.map(|v| v.to_string())),
+    },
+    FieldSpec {
+        ch: 'i',
+        description: "inode number",
+        value: FieldValue::File(|_, f| Some(f.node.clone())),
+    },
+    FieldSpec {
+        ch: 'l',
+        description: "lock held on the file (W, w, R or r)",
+        value: FieldValue::File(|_, f| f.lock.map(|l| l.code().to_string())),
+    },
+    FieldSpec {
+        ch: 'e',
+        description: "close-on-exec flag (1 or 0)",
+        value: FieldValue::File(|_, f| f.cloexec.map(|c| u8::from(c).to_string())),
+    },
+    FieldSpec {
+        ch: 'm',
+        description: "memory mapping: w/r then s(hared) or p(rivate)",
+        value: FieldValue::File(|_, f| f.mapping.map(|m| m.code())),
+    },
+    FieldSpec {
+        ch: 'n',
+        description: "file name",
+        value: FieldValue::File(|_, f| Some(f.name.clone())),
+    },
+];
+
+/// Look up a `-F` field character.
+pub fn field_spec(ch: char) -> Option<&'static FieldSpec> {
+    FIELD_TABLE.iter().find(|spec| spec.ch == ch)
+}
+
+// ---------------------------------------------------------------------------
+// Output templates (`--format`)
+// ---------------------------------------------------------------------------
+
+/// A field that can be referenced as `{name}` in a `--format` template.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TemplateField {
+    Pid,
+    Ppid,
+    Comm,
+    Command,
+    User,
+    Uid,
+    Fd,
+    Type,
+    Device,
+    Size,
+    Node,
+    Name,
+}
+
+impl TemplateField {
+    /// Placeholder names accepted in templates, in documentation order.
+    pub const NAMES: &'static [&'static str] = &[
+        "pid", "ppid", "comm", "command", "user", "uid", "fd", "type", "device", "size", "node",
+        "name",
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pid" => Some(TemplateField::Pid),
+            "ppid" => Some(TemplateField::Ppid),
+            "comm" => Some(TemplateField::Comm),
+            "command" => Some(TemplateField::Command),
+            "user" => Some(TemplateField::User),
+            "uid" => Some(TemplateField::Uid),
+            "fd" => Some(TemplateField::Fd),
+            "type" => Some(TemplateField::Type),
+            "device" => Some(TemplateField::Device),
+            "size" => Some(TemplateField::Size),
+            "node" => Some(TemplateField::Node),
+            "name" => Some(TemplateField::Name),
+            _ => None,
+        }
+    }
+
+    fn value(&self, proc: &ProcessInfo, file: &OpenFileInfo) -> String {
+        match self {
+            TemplateField::Pid => proc.pid.to_string(),
+            TemplateField::Ppid => proc.ppid.map(|p| p.to_string()).unwrap_or_default(),
+            TemplateField::Comm => proc.comm.clone(),
+            TemplateField::Command => proc.command.clone(),
+            TemplateField::User => proc.user.clone(),
+            TemplateField::Uid => proc.uid.to_string(),
+            TemplateField::Fd => file.fd.to_string(),
+            TemplateField::Type => file.file_type.to_string(),
+            TemplateField::Device => file.device.clone(),
+            TemplateField::Size => format_size_off(file.size_off),
+            TemplateField::Node => file.node.clone(),
+            TemplateField::Name => file.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TemplateToken {
+    Literal(String),
+    Field(TemplateField),
+}
+
+/// A parsed `--format` template such as `{pid} {comm} {fd} {name}`.
+///
+/// Placeholders are validated when parsing, so rendering never fails.
+/// Use `{{` and `}}` for literal braces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputTemplate {
+    tokens: Vec<TemplateToken>,
+}
+
+impl OutputTemplate {
+    /// Tokenize a template string, rejecting unknown or unterminated placeholders.
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut tokens = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(ch) => name.push(ch),
+                            None => {
+                                return Err(LoofError::Parse(format!(
+                                    "unterminated placeholder '{{{}' in format template",
+                                    name
+                                )))
+                            }
                         }
                     }
-                    'i' => println!("i{}", file.node),
-                    'n' => println!("n{}", file.name),
-                    _ => {}
+                    let field = TemplateField::from_name(name.trim()).ok_or_else(|| {
+                        LoofError::Parse(format!(
+                            "unknown placeholder '{{{}}}' in format template (available: {})",
+                            name,
+                            TemplateField::NAMES.join(", ")
+                        ))
+                    })?;
+                    if !literal.is_empty() {
+                        tokens.push(TemplateToken::Literal(std::mem::take(&mut literal)));
+                    }
+                    tokens.push(TemplateToken::Field(field));
                 }
+                '}' => {
+                    return Err(LoofError::Parse(
+                        "unmatched '}' in format template (use '}}' for a literal brace)"
+                            .to_string(),
+                    ))
+                }
+                _ => literal.push(c),
             }
         }
+        if !literal.is_empty() {
+            tokens.push(TemplateToken::Literal(literal));
+        }
+
+        Ok(OutputTemplate { tokens })
+    }
+
+    /// Expand the template for a single open file of `proc`.
+    pub fn render(&self, proc: &ProcessInfo, file: &OpenFileInfo) -> String {
+        let mut out = String::new();
+        for token in &self.tokens {
+            match token {
+                TemplateToken::Literal(s) => out.push_str(s),
+                TemplateToken::Field(f) => out.push_str(&f.value(proc, file)),
+            }
+        }
+        out
     }
 }
 
@@ -190,15 +815,79 @@ impl OutputFormatter {
 // Helpers
 // ---------------------------------------------------------------------------
 
-/// Truncate or pad a string to exactly `width` characters.
+/// Truncate or pad a string to exactly `width` characters. A width of 0
+/// means unlimited (`+c 0`): the string is returned as-is, unpadded.
 fn fit_str(s: &str, width: usize) -> String {
-    if s.len() > width {
-        s[..width].to_string()
+    if width == 0 {
+        s.to_string()
+    } else if s.chars().count() > width {
+        s.chars().take(width).collect()
     } else {
         format!("{:<width$}", s, width = width)
     }
 }
 
+/// A container ID shortened to the 12 characters `docker ps` shows.
+pub fn short_container_id(id: &str) -> &str {
+    id.get(..12).unwrap_or(id)
+}
+
+/// The FD column: the FD and its mode, followed by the lock character
+/// when the file is locked through it (e.g. `3uW`).
+fn fd_label(file: &OpenFileInfo) -> String {
+    match file.lock {
+        Some(lock) => format!("{}{}", file.fd, lock.code()),
+        None => file.fd.to_string(),
+    }
+}
+
+/// Quote and escape a string as a JSON string literal.
+pub fn json_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// A CSV field, quoted (with `"` doubled) when it holds a comma, quote or
+/// line break.
+pub fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// A JSON number, or `null` when absent.
+pub fn json_opt<T: std::fmt::Display>(v: Option<T>) -> String {
+    v.map(|v| v.to_string())
+        .unwrap_or_else(|| "null".to_string())
+}
+
+/// Rewrite a decimal `major,minor` device as `0xMAJ,MIN` in hex. Anything
+/// else (e.g. an empty device for sockets) is returned unchanged.
+fn hex_device(device: &str) -> String {
+    let parsed = device
+        .split_once(',')
+        .and_then(|(major, minor)| Some((major.parse::<u64>().ok()?, minor.parse::<u64>().ok()?)));
+    match parsed {
+        Some((major, minor)) => format!("0x{:x},{:x}", major, minor),
+        None => device.to_string(),
+    }
+}
+
 /// Format the SIZE/OFF column value.
 fn format_size_off(size: Option<u64>) -> String {
     match size {
@@ -214,13 +903,148 @@ fn format_size_off(size: Option<u64>) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::fixtures;
     use crate::model::{FdType, FileType, OpenFileInfo};
 
+    #[test]
+    fn test_cmd_width_zero_is_unlimited() {
+        let comm = "a-rather-long-daemon-name-30ch";
+        assert_eq!(comm.len(), 30);
+        let fmt = OutputFormatter {
+            cmd_width: 0,
+            ..Default::default()
+        };
+        let proc = fixtures::process(7, comm, Vec::new());
+        assert!(fmt.header_line().starts_with("COMMAND "));
+        assert!(fmt.process_lines(&proc)[0].starts_with(&format!("{} ", comm)));
+        assert_eq!(fit_str("nginx", 3), "ngi");
+        assert!(OutputFormatter::default().sized_for(&[proc]).is_none());
+        assert_eq!(fit_str("ñandú", 4), "ñand");
+    }
+
+    #[test]
+    fn test_cmd_width_zero_aligns_to_longest_command() {
+        let proc = |pid, comm: &str| fixtures::process(pid, comm, Vec::new());
+        let procs = [
+            proc(1, "sh"),
+            proc(22, "postgres-autovacuum"),
+            proc(333, "nginx"),
+        ];
+        let fmt = OutputFormatter {
+            cmd_width: 0,
+            ..Default::default()
+        }
+        .sized_for(&procs)
+        .unwrap();
+        assert_eq!(fmt.cmd_width, "postgres-autovacuum".len());
+
+        let mut lines = vec![fmt.header_line()];
+        lines.extend(procs.iter().flat_map(|p| fmt.process_lines(p)));
+        // The USER column starts at the same offset on every line.
+        let user_col: Vec<usize> = lines
+            .iter()
+            .map(|l| l.find("USER").or_else(|| l.find("root")).unwrap())
+            .collect();
+        assert!(user_col.iter().all(|&c| c == user_col[0]), "{:?}", lines);
+    }
+
+    #[test]
+    fn test_nspid_widens_pid_column() {
+        let mut procs = vec![
+            fixtures::process(7, "sh", Vec::new()),
+            fixtures::process(123456, "nginx", Vec::new()),
+        ];
+        procs[1].nspid = Some(4321);
+        let fmt = OutputFormatter {
+            show_nspid: true,
+            ..Default::default()
+        };
+        let fmt = fmt.sized_for(&procs).unwrap();
+        assert_eq!(fmt.pid_width, "123456/4321".len());
+
+        let mut lines = vec![fmt.header_line()];
+        lines.extend(procs.iter().flat_map(|p| fmt.process_lines(p)));
+        let user_col: Vec<usize> = lines
+            .iter()
+            .map(|l| l.find("USER").or_else(|| l.find("root")).unwrap())
+            .collect();
+        assert!(user_col.iter().all(|&c| c == user_col[0]), "{:?}", lines);
+        assert!(OutputFormatter::default().sized_for(&procs).is_none());
+    }
+
+    #[test]
+    fn test_hex_device_rewrites_decimal_pairs() {
+        assert_eq!(hex_device("1,16"), "0x1,10");
+        assert_eq!(hex_device("259,5"), "0x103,5");
+        assert_eq!(hex_device(""), "");
+        assert_eq!(hex_device("0x1,10"), "0x1,10");
+    }
+
     #[test]
     fn test_fit_str_truncate() {
         assert_eq!(fit_str("longcommandname", 9), "longcomma");
     }
 
+    #[test]
+    fn test_missing_tag_only_with_missing_flag() {
+        let file = OpenFileInfo {
+            resolved: Some(false),
+            ..fixtures::file(
+                FdType::Numbered(3, crate::model::FdMode::Read),
+                FileType::Reg,
+                "/var/log/app.log",
+            )
+        };
+        let proc = fixtures::process(9, "app", vec![file]);
+
+        let lines = OutputFormatter::default().process_lines(&proc);
+        assert!(lines[0].ends_with(" /var/log/app.log"));
+        let fmt = OutputFormatter {
+            mark_missing: true,
+            ..Default::default()
+        };
+        assert!(fmt.process_lines(&proc)[0].ends_with(" /var/log/app.log (missing)"));
+    }
+
+    #[test]
+    fn test_max_name_width_truncates_long_names() {
+        let fmt = OutputFormatter {
+            max_name_width: Some(40),
+            ..Default::default()
+        };
+        let file = |name: String| OpenFileInfo {
+            device: "8,1".to_string(),
+            size_off: Some(0),
+            node: "12".to_string(),
+            ..fixtures::file(
+                FdType::Numbered(3, crate::model::FdMode::Read),
+                FileType::Reg,
+                &name,
+            )
+        };
+        let long = format!("/data/{}", "é".repeat(194));
+        assert_eq!(long.chars().count(), 200);
+        let proc = fixtures::process(
+            9,
+            "app",
+            vec![
+                file(long.clone()),
+                file("/etc/hosts".to_string()),
+                file(format!("/srv/{}", "日".repeat(30))),
+            ],
+        );
+
+        let lines = fmt.process_lines(&proc);
+        let name = lines[0].rsplit(' ').next().unwrap();
+        assert_eq!(name.chars().count(), 40);
+        assert!(name.ends_with('…'));
+        assert!(long.starts_with(name.trim_end_matches('…')));
+        assert!(lines[1].ends_with(" /etc/hosts"));
+        // Wide characters take two columns each.
+        let wide = lines[2].rsplit(' ').next().unwrap();
+        assert_eq!(wide, format!("/srv/{}…", "日".repeat(17)));
+    }
+
     #[test]
     fn test_fit_str_pad() {
         assert_eq!(fit_str("sh", 9), "sh       ");
@@ -244,9 +1068,22 @@ mod tests {
             no_portname: false,
             list_uid: false,
             show_ppid: false,
+            pid_width: 5,
+            show_nspid: false,
+            wide: false,
+            show_context: false,
             terse: false,
             field_output: None,
             tcp_info: None,
+            suppress_warnings: false,
+            template: None,
+            json: false,
+            json_flat: false,
+            hex_device: false,
+            mark_missing: false,
+            max_name_width: None,
+            field_sep: '\n',
+            record_sep: None,
         };
         assert_eq!(fmt.cmd_width, 9);
     }
@@ -259,36 +1096,36 @@ mod tests {
             no_portname: false,
             list_uid: false,
             show_ppid: false,
+            pid_width: 5,
+            show_nspid: false,
+            wide: false,
+            show_context: false,
             terse: true,
             field_output: None,
             tcp_info: None,
+            suppress_warnings: false,
+            template: None,
+            json: false,
+            json_flat: false,
+            hex_device: false,
+            mark_missing: false,
+            max_name_width: None,
+            field_sep: '\n',
+            record_sep: None,
         };
 
         let procs = vec![
+            fixtures::process(100, "bash", Vec::new()),
             ProcessInfo {
-                pid: 100,
-                ppid: None,
-                pgid: None,
-                command: "bash".to_string(),
-                comm: "bash".to_string(),
-                user: "root".to_string(),
-                uid: 0,
-                open_files: Vec::new(),
-            },
-            ProcessInfo {
-                pid: 200,
-                ppid: None,
-                pgid: None,
-                command: "nginx".to_string(),
-                comm: "nginx".to_string(),
                 user: "www".to_string(),
                 uid: 33,
-                open_files: Vec::new(),
+                ..fixtures::process(200, "nginx", Vec::new())
             },
         ];
 
-        // Capture output by calling the method (we just verify it doesn't panic)
-        fmt.print_terse(&procs);
+        let mut out = Vec::new();
+        fmt.write_terse(&mut out, &procs).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "100\n200\n");
     }
 
     #[test]
@@ -299,34 +1136,327 @@ mod tests {
             no_portname: false,
             list_uid: false,
             show_ppid: false,
+            pid_width: 5,
+            show_nspid: false,
+            wide: false,
+            show_context: false,
             terse: false,
             field_output: Some("pcun".to_string()),
             tcp_info: None,
+            suppress_warnings: false,
+            template: None,
+            json: false,
+            json_flat: false,
+            hex_device: false,
+            mark_missing: false,
+            max_name_width: None,
+            field_sep: '\n',
+            record_sep: None,
         };
 
         let proc = ProcessInfo {
-            pid: 1234,
             ppid: Some(1),
-            pgid: None,
             command: "/usr/sbin/nginx".to_string(),
-            comm: "nginx".to_string(),
-            user: "root".to_string(),
-            uid: 0,
-            open_files: vec![OpenFileInfo {
-                fd: FdType::Cwd,
-                file_type: FileType::Dir,
-                device: "1,16".to_string(),
-                size_off: Some(704),
-                node: "2".to_string(),
-                name: "/".to_string(),
-                mode: None,
-                link_target: None,
-                send_queue: None,
-                recv_queue: None,
-            }],
+            ..fixtures::process(
+                1234,
+                "nginx",
+                vec![OpenFileInfo {
+                    device: "1,16".to_string(),
+                    size_off: Some(704),
+                    node: "2".to_string(),
+                    ..fixtures::file(FdType::Cwd, FileType::Dir, "/")
+                }],
+            )
+        };
+
+        let mut out = Vec::new();
+        fmt.write_field_output(&mut out, &proc).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "p1234\ncnginx\nuroot\nn/\n"
+        );
+    }
+
+    #[test]
+    fn test_queue_sizes_shown_for_unix_sockets() {
+        let fmt = OutputFormatter {
+            tcp_info: Some("q".to_string()),
+            ..Default::default()
+        };
+        let socket = |file_type, name: &str| OpenFileInfo {
+            fd: FdType::Numbered(5, crate::model::FdMode::ReadWrite),
+            file_type,
+            node: "4711".to_string(),
+            name: name.to_string(),
+            send_queue: Some(12),
+            recv_queue: Some(3),
+            ..Default::default()
+        };
+        let proc = fixtures::process(
+            88,
+            "dbus-daemon",
+            vec![
+                socket(FileType::Unix, "/run/dbus/system_bus_socket"),
+                socket(FileType::IPv4, "0.0.0.0:5353 -> 0.0.0.0:0 (UDP)"),
+                socket(FileType::Reg, "/var/log/dbus.log"),
+            ],
+        );
+
+        let lines = fmt.process_lines(&proc);
+        assert!(lines[0].ends_with("/run/dbus/system_bus_socket QR=3 QS=12"));
+        assert!(lines[1].ends_with("(UDP) QR=3 QS=12"));
+        assert!(lines[2].ends_with("/var/log/dbus.log"));
+    }
+
+    #[test]
+    fn test_field_output_custom_separators_round_trip() {
+        let fmt = OutputFormatter {
+            field_output: Some("pcfn".to_string()),
+            field_sep: '\t',
+            record_sep: Some('\n'),
+            ..Default::default()
+        };
+        let file = |fd, name: &str| OpenFileInfo {
+            fd: FdType::Numbered(fd, crate::model::FdMode::Read),
+            file_type: FileType::Reg,
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let proc = |pid, comm: &str, files| fixtures::process(pid, comm, files);
+        let procs = vec![
+            proc(1, "init", vec![file(3, "/etc/fstab")]),
+            proc(
+                42,
+                "my app",
+                vec![file(0, "/dev/null"), file(5, "/var/log/a b")],
+            ),
+        ];
+        let mut out = Vec::new();
+        fmt.write_report(&mut out, &procs).unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        let records: Vec<Vec<&str>> = text
+            .lines()
+            .map(|line| line.split_terminator('\t').collect())
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                vec!["p1", "cinit", "f3r", "n/etc/fstab"],
+                vec![
+                    "p42",
+                    "cmy app",
+                    "f0r",
+                    "n/dev/null",
+                    "f5r",
+                    "n/var/log/a b"
+                ],
+            ]
+        );
+        assert!(fmt.separator_warnings().is_empty());
+
+        let clash = OutputFormatter {
+            field_sep: 'x',
+            record_sep: Some('x'),
+            ..fmt
+        };
+        assert_eq!(clash.separator_warnings().len(), 3);
+    }
+
+    #[test]
+    fn test_field_output_ignores_cmd_width() {
+        let fmt = OutputFormatter {
+            cmd_width: 4,
+            field_output: Some("cn".to_string()),
+            ..Default::default()
+        };
+        let proc = ProcessInfo {
+            command: "postgres: writer".to_string(),
+            user: "postgres".to_string(),
+            uid: 70,
+            ..fixtures::process(
+                7,
+                "postgres-writer",
+                vec![OpenFileInfo {
+                    device: "254,0".to_string(),
+                    size_off: Some(8192),
+                    node: "2619".to_string(),
+                    ..fixtures::file(
+                        FdType::Numbered(3, crate::model::FdMode::Write),
+                        FileType::Reg,
+                        "/var/lib/postgresql/data/base/16384/2619",
+                    )
+                }],
+            )
+        };
+
+        let mut out = Vec::new();
+        fmt.write_field_output(&mut out, &proc).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "cpostgres-writer\nn/var/lib/postgresql/data/base/16384/2619\n"
+        );
+        // The column view still honours +c.
+        assert!(fmt.process_lines(&proc)[0].starts_with("post "));
+    }
+
+    fn report_bytes(fmt: &OutputFormatter, processes: &[ProcessInfo]) -> String {
+        let mut out = Vec::new();
+        fmt.write_report(&mut out, processes).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_write_report_exact_bytes() {
+        let proc = ProcessInfo {
+            ppid: Some(1),
+            command: "/usr/bin/python3 app.py".to_string(),
+            user: "alice".to_string(),
+            uid: 1000,
+            ..fixtures::process(
+                4321,
+                "python3",
+                vec![OpenFileInfo {
+                    device: "254,0".to_string(),
+                    size_off: Some(1024),
+                    node: "131".to_string(),
+                    ..fixtures::file(
+                        FdType::Numbered(3, crate::model::FdMode::Write),
+                        FileType::Reg,
+                        "/var/log/app.log",
+                    )
+                }],
+            )
         };
+        let procs = [proc];
+
+        assert_eq!(
+            report_bytes(&OutputFormatter::default(), &procs),
+            "COMMAND     PID USER       FD    TYPE   DEVICE  SIZE/OFF  NODE NAME\n\
+             python3    4321 alice      3w  REG    254,0      1024   131 /var/log/app.log\n"
+        );
+        let json = OutputFormatter {
+            json: true,
+            json_flat: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            report_bytes(&json, &procs),
+            "[{\"pid\":4321,\"ppid\":1,\"command\":\"python3\",\"user\":\"alice\",\"uid\":1000,\
+             \"files\":[{\"fd\":\"3w\",\"type\":\"REG\",\"device\":\"254,0\",\"size_off\":1024,\
+             \"node\":\"131\",\"name\":\"/var/log/app.log\",\"lock\":null}]}]\n"
+        );
+        let template = OutputFormatter {
+            template: Some(OutputTemplate::parse("{pid}:{fd}:{name}").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            report_bytes(&template, &procs),
+            "4321:3w:/var/log/app.log\n"
+        );
+    }
+
+    #[test]
+    fn test_json_escapes_strings() {
+        assert_eq!(json_str("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+        assert_eq!(json_opt::<u32>(None), "null");
+    }
+
+    #[test]
+    fn test_split_output_writes_one_file_per_process() {
+        use crate::filter::FilterConfig;
+        use crate::platform::mock::MockProvider;
+        use crate::scan::{scan, ScanOptions};
+
+        let make = |pid: u32, comm: &str| {
+            fixtures::process(
+                pid,
+                comm,
+                vec![OpenFileInfo {
+                    fd: FdType::Cwd,
+                    file_type: FileType::Dir,
+                    device: "1,16".to_string(),
+                    size_off: Some(704),
+                    node: "2".to_string(),
+                    name: format!("/home/{}", comm),
+                    ..Default::default()
+                }],
+            )
+        };
+        let provider = MockProvider::new(vec![make(10, "ssh"), make(20, "vim")]);
+        let procs = scan(&provider, &FilterConfig::default(), &ScanOptions::default())
+            .unwrap()
+            .processes;
+
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("capture");
+        let fmt = OutputFormatter::default();
+        fmt.write_split_output(&out, &procs).unwrap();
+
+        let ssh = std::fs::read_to_string(out.join("10.txt")).unwrap();
+        let mut lines = ssh.lines();
+        assert!(lines.next().unwrap().starts_with("COMMAND"));
+        let row = lines.next().unwrap();
+        assert!(row.starts_with("ssh "));
+        assert!(row.ends_with(" /home/ssh"));
+        assert!(lines.next().is_none());
+        assert!(out.join("20.txt").exists());
 
-        // Verify it doesn't panic
-        fmt.print_field_output(&proc);
+        let json = OutputFormatter {
+            json: true,
+            hex_device: false,
+            ..Default::default()
+        };
+        json.write_split_output(&out, &procs).unwrap();
+        let vim = std::fs::read_to_string(out.join("20.json")).unwrap();
+        assert!(vim.starts_with("{\"pid\":20,"));
+        assert!(vim.contains("\"name\":\"/home/vim\""));
+    }
+
+    #[test]
+    fn test_json_document_is_versioned() {
+        let procs = [fixtures::process(7, "init", Vec::new())];
+        let json = OutputFormatter {
+            json: true,
+            ..Default::default()
+        };
+        let doc = json.json_document(&procs);
+        let prefix = format!(
+            "{{\"schema_version\":1,\"tool_version\":\"{}\",\"processes\":[",
+            env!("CARGO_PKG_VERSION")
+        );
+        assert!(doc.starts_with(&prefix), "{}", doc);
+        assert!(doc.ends_with("]}"));
+        assert_eq!(JSON_SCHEMA_VERSION, 1);
+
+        let flat = OutputFormatter {
+            json_flat: true,
+            ..json
+        };
+        assert!(flat.json_document(&procs).starts_with("[{\"pid\":7,"));
+    }
+
+    #[test]
+    fn test_template_render() {
+        let tpl = OutputTemplate::parse("{pid}:{comm} fd={fd} [{type}] {{{name}}}").unwrap();
+        let proc = ProcessInfo {
+            command: "/bin/sh".to_string(),
+            ..fixtures::process(42, "sh", Vec::new())
+        };
+        let file = OpenFileInfo {
+            device: "1,16".to_string(),
+            size_off: Some(704),
+            node: "2".to_string(),
+            ..fixtures::file(FdType::Cwd, FileType::Dir, "/tmp")
+        };
+        assert_eq!(tpl.render(&proc, &file), "42:sh fd=cwd [DIR] {/tmp}");
+    }
+
+    #[test]
+    fn test_template_rejects_unknown_placeholder() {
+        let err = OutputTemplate::parse("{pid} {bogus}").unwrap_err();
+        assert!(err.to_string().contains("{bogus}"));
+        assert!(OutputTemplate::parse("{pid").is_err());
+        assert!(OutputTemplate::parse("pid}").is_err());
     }
 }
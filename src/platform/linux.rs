@@ -1,12 +1,16 @@
-use super::{PlatformProvider, ProviderConfig};
+use super::{display_ip, system_user_names, PlatformProvider, ProviderConfig};
 use crate::error::{LoofError, Result};
 use crate::model::*;
+use crate::trace;
 
 use std::collections::HashMap;
 use std::fs;
 use std::net::SocketAddr;
 use std::os::unix::fs::MetadataExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
 // ---------------------------------------------------------------------------
 // Helper types
@@ -20,11 +24,24 @@ struct SocketNetInfo {
     local_port: u16,
     remote_addr: String,
     remote_port: u16,
+    /// The far end as read from the table; `None` for Unix sockets.
+    remote: Option<SocketAddr>,
     state: TcpState,
     tx_queue: Option<u64>,
     rx_queue: Option<u64>,
 }
 
+impl SocketNetInfo {
+    /// The far end (and TCP state) for an IP socket's `OpenFileInfo`.
+    fn connection(&self) -> Option<Connection> {
+        let tcp = matches!(self.protocol, Protocol::Tcp | Protocol::Tcp6);
+        self.remote.map(|remote| Connection {
+            remote,
+            tcp_state: tcp.then(|| self.state.clone()),
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Helper functions
 // ---------------------------------------------------------------------------
@@ -66,34 +83,139 @@ fn fd_mode_from_flags(flags: u32) -> FdMode {
     }
 }
 
-/// Read the `flags:` field from `/proc/[pid]/fdinfo/[fd]`.
-fn read_fd_flags(pid: u32, fd: i32) -> FdMode {
-    let path = format!("/proc/{}/fdinfo/{}", pid, fd);
-    match fs::read_to_string(&path) {
-        Ok(content) => {
-            for line in content.lines() {
-                if let Some(rest) = line.strip_prefix("flags:") {
-                    let trimmed = rest.trim();
-                    // The flags field is an octal number (e.g. "0100002").
-                    // Parse it as octal.
-                    let digits = trimmed.trim_start_matches('0');
-                    let octal_str = if digits.is_empty() { "0" } else { digits };
-                    if let Ok(val) = u32::from_str_radix(octal_str, 8) {
-                        return fd_mode_from_flags(val);
-                    }
-                    // Fallback: try decimal parse
-                    if let Ok(val) = trimmed.parse::<u32>() {
-                        return fd_mode_from_flags(val);
-                    }
-                }
+/// `O_CLOEXEC` as it appears in the fdinfo `flags:` field, where the
+/// kernel folds in the FD's close-on-exec bit.
+const O_CLOEXEC: u32 = 0o2000000;
+
+/// Parse the `flags:` field of an fdinfo file. It is an octal number
+/// (e.g. "0100002").
+fn parse_fdinfo_flags(content: &str) -> Option<u32> {
+    let trimmed = content
+        .lines()
+        .find_map(|l| l.strip_prefix("flags:"))?
+        .trim();
+    u32::from_str_radix(trimmed, 8)
+        .ok()
+        // Fallback: try decimal parse
+        .or_else(|| trimmed.parse().ok())
+}
+
+/// Read the open flags from `/proc/[pid]/fdinfo/[fd]`.
+fn read_fd_flags(pid: u32, fd: i32) -> Option<u32> {
+    fs::read_to_string(format!("/proc/{}/fdinfo/{}", pid, fd))
+        .ok()
+        .and_then(|c| parse_fdinfo_flags(&c))
+}
+
+/// Extract the target PID from a pidfd's fdinfo (`Pid:` line). The kernel
+/// reports `-1` once the target has exited.
+fn parse_fdinfo_pid(content: &str) -> Option<i32> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("Pid:"))
+        .and_then(|rest| rest.trim().parse().ok())
+}
+
+/// Signals a signalfd watches, from the hex `sigmask:` line of its fdinfo
+/// (bit N-1 set for signal N). Real-time signals show as numbers.
+fn parse_signalfd_mask(content: &str) -> Option<Vec<String>> {
+    let hex = content
+        .lines()
+        .find_map(|line| line.strip_prefix("sigmask:"))?
+        .trim();
+    let mask = u64::from_str_radix(hex, 16).ok()?;
+    Some(
+        (1..=64)
+            .filter(|n| mask & (1u64 << (n - 1)) != 0)
+            .map(|n| match nix::sys::signal::Signal::try_from(n) {
+                Ok(sig) => sig.as_str().to_string(),
+                Err(_) => n.to_string(),
+            })
+            .collect(),
+    )
+}
+
+/// A `(seconds, nanoseconds)` pair from timerfd fdinfo as e.g. `1.5s`.
+fn format_timespec(sec: u64, nsec: u64) -> String {
+    if nsec == 0 {
+        return format!("{}s", sec);
+    }
+    let frac = format!("{:09}", nsec);
+    format!("{}.{}s", sec, frac.trim_end_matches('0'))
+}
+
+/// A timerfd's schedule from the `it_value:`/`it_interval:` lines of its
+/// fdinfo: time to the next expiry and the repeat period, or `disarmed`.
+fn parse_timerfd_schedule(content: &str) -> Option<String> {
+    let field = |key: &str| -> Option<(u64, u64)> {
+        let rest = content.lines().find_map(|line| line.strip_prefix(key))?;
+        let (sec, nsec) = rest
+            .trim()
+            .trim_start_matches('(')
+            .trim_end_matches(')')
+            .split_once(',')?;
+        Some((sec.trim().parse().ok()?, nsec.trim().parse().ok()?))
+    };
+    let value = field("it_value:")?;
+    let interval = field("it_interval:")?;
+    if value == (0, 0) {
+        return Some("disarmed".to_string());
+    }
+    let mut schedule = format!("next={}", format_timespec(value.0, value.1));
+    if interval != (0, 0) {
+        schedule.push_str(&format!(
+            ",every={}",
+            format_timespec(interval.0, interval.1)
+        ));
+    }
+    Some(schedule)
+}
+
+/// Map an anon inode description (e.g. `[pidfd]`, `bpf-map`) to a file type
+/// and display name. pidfds, signalfds and timerfds are annotated from their
+/// fdinfo when it is readable.
+fn classify_anon_inode(desc: &str, pid: u32, fd: u32) -> (FileType, String) {
+    let kind = desc.trim_start_matches('[').trim_end_matches(']');
+    let fdinfo = || fs::read_to_string(format!("/proc/{}/fdinfo/{}", pid, fd)).ok();
+    let label = match kind {
+        "signalfd" => {
+            if let Some(signals) = fdinfo().and_then(|c| parse_signalfd_mask(&c)) {
+                let name = format!("signalfd:[{}]", signals.join(","));
+                return (FileType::Unknown("a_inode".to_string()), name);
             }
-            FdMode::Unknown
+            "a_inode"
         }
-        Err(_) => FdMode::Unknown,
-    }
+        "timerfd" => {
+            if let Some(schedule) = fdinfo().and_then(|c| parse_timerfd_schedule(&c)) {
+                let name = format!("timerfd:[{}]", schedule);
+                return (FileType::Unknown("a_inode".to_string()), name);
+            }
+            "a_inode"
+        }
+        "pidfd" => {
+            let target = fdinfo().and_then(|c| parse_fdinfo_pid(&c));
+            let name = match target {
+                Some(t) if t > 0 => format!("anon_inode:[pidfd] pid={}", t),
+                Some(_) => "anon_inode:[pidfd] (exited)".to_string(),
+                None => "anon_inode:[pidfd]".to_string(),
+            };
+            return (FileType::Unknown("PIDFD".to_string()), name);
+        }
+        "bpf-map" => "BPFMAP",
+        "bpf-prog" => "BPFPROG",
+        "bpf_link" | "bpf-link" => "BPFLINK",
+        "io_uring" => "IOURING",
+        _ => "a_inode",
+    };
+    (
+        FileType::Unknown(label.to_string()),
+        format!("anon_inode:[{}]", kind),
+    )
 }
 
-/// Format a device number as "major,minor" using the Linux encoding.
+/// Format a device number as decimal "major,minor" using the Linux
+/// encoding (12-bit major and 20-bit minor, split across the word as in
+/// glibc's `gnu_dev_major`/`gnu_dev_minor`).
 fn format_device(dev: u64) -> String {
     let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
     let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
@@ -118,31 +240,242 @@ fn map_tcp_state(state: &procfs::net::TcpState) -> TcpState {
     }
 }
 
-/// Format a `SocketAddr` into its IP string.
-fn addr_ip_string(addr: &SocketAddr) -> String {
-    addr.ip().to_string()
+/// A connected UDP socket reports `ESTABLISHED`; any other has no state.
+fn map_udp_state(state: &procfs::net::UdpState) -> TcpState {
+    match state {
+        procfs::net::UdpState::Established => TcpState::Established,
+        _ => TcpState::None,
+    }
+}
+
+/// Format a `SocketAddr` into its IP string (see [`display_ip`]).
+fn addr_ip_string(addr: &SocketAddr, raw_addr: bool) -> String {
+    display_ip(addr.ip(), raw_addr)
+}
+
+/// The innermost namespace PID from the `NSpid:` line of
+/// `/proc/[pid]/status`, which lists the PID in every nested namespace
+/// from ours inwards. `None` when the process lives in our namespace.
+fn parse_nspid(status: &str) -> Option<u32> {
+    let line = status.lines().find_map(|l| l.strip_prefix("NSpid:"))?;
+    let pids: Vec<u32> = line
+        .split_whitespace()
+        .map(str::parse)
+        .collect::<std::result::Result<_, _>>()
+        .ok()?;
+    match pids.as_slice() {
+        [_, .., inner] => Some(*inner),
+        _ => None,
+    }
+}
+
+/// The command line from `/proc/[pid]/cmdline`'s NUL-separated parts.
+/// `raw` keeps every part, empty ones included, joined by single spaces.
+/// Otherwise runs of whitespace collapse to one space, so processes that
+/// pad their rewritten argv (postgres, nginx workers) and empty trailing
+/// arguments don't leave long blank tails.
+fn join_cmdline(parts: &[String], raw: bool) -> String {
+    if raw {
+        return parts.join(" ");
+    }
+    parts
+        .iter()
+        .flat_map(|part| part.split_whitespace())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Cgroup name prefixes the container runtimes put before the ID, as in
+/// `docker-<id>.scope`. Podman's `libpod-conmon-` monitor scopes are not
+/// containers and are left out on purpose.
+const CONTAINER_SCOPE_PREFIXES: &[&str] = &["docker-", "cri-containerd-", "crio-", "libpod-"];
+
+/// The container ID from `/proc/[pid]/cgroup`: a 64-hex-digit path
+/// component, either bare (`/docker/<id>`, cgroupfs kubepods) or wrapped in
+/// a systemd scope (`.../docker-<id>.scope`, `cri-containerd-`, `crio-`,
+/// `libpod-`). The innermost match wins.
+fn parse_container_id(cgroup: &str) -> Option<String> {
+    let is_id = |s: &str| s.len() == 64 && s.bytes().all(|b| b.is_ascii_hexdigit());
+    cgroup
+        .lines()
+        .filter_map(|line| line.splitn(3, ':').nth(2))
+        .find_map(|path| {
+            path.rsplit('/').find_map(|component| {
+                let name = component.strip_suffix(".scope").unwrap_or(component);
+                let id = CONTAINER_SCOPE_PREFIXES
+                    .iter()
+                    .find_map(|prefix| name.strip_prefix(prefix))
+                    .unwrap_or(name);
+                is_id(id).then(|| id.to_string())
+            })
+        })
+}
+
+/// Parse `/proc/[pid]/maps` one entry at a time, so a line torn by a
+/// concurrent mmap/munmap (JITs, browsers) is skipped instead of making the
+/// whole table unreadable.
+fn parse_maps_lenient(content: &str) -> Vec<procfs::process::MemoryMap> {
+    use procfs::FromBufRead;
+
+    content
+        .lines()
+        .filter_map(|line| procfs::process::MemoryMaps::from_buf_read(line.as_bytes()).ok())
+        .flat_map(|maps| maps.0)
+        .collect()
+}
+
+/// Read a maps table with `read`, retrying once since a read racing with
+/// the process's own mapping changes can fail transiently. `None` when
+/// both attempts fail; the caller then lists FDs without `mem` entries.
+fn read_maps(
+    mut read: impl FnMut() -> std::io::Result<String>,
+) -> Option<Vec<procfs::process::MemoryMap>> {
+    let content = read().or_else(|_| read()).ok()?;
+    Some(parse_maps_lenient(&content))
+}
+
+/// Soft and hard limits from the "Max open files" row of
+/// `/proc/[pid]/limits`, with `unlimited` as `u64::MAX`.
+fn parse_fd_limit(content: &str) -> Option<(u64, u64)> {
+    let row = content
+        .lines()
+        .find_map(|line| line.strip_prefix("Max open files"))?;
+    let mut values = row.split_whitespace().map(|v| match v {
+        "unlimited" => Some(u64::MAX),
+        _ => v.parse().ok(),
+    });
+    Some((values.next()??, values.next()??))
+}
+
+/// The label in `/proc/[pid]/attr/current`. The kernel may end it with a
+/// newline or NUL; an empty value means no label, and without an LSM the
+/// read itself fails.
+fn parse_security_context(raw: &[u8]) -> Option<String> {
+    let label = String::from_utf8_lossy(raw);
+    let label = label.trim_end_matches(['\0', '\n']).trim();
+    (!label.is_empty()).then(|| label.to_string())
+}
+
+/// The files in a process's `maps`, once each in first-mapped order, with
+/// the riskiest way each is mapped (see [`MapInfo::rank`]).
+fn mapped_files(maps: &[procfs::process::MemoryMap]) -> Vec<(PathBuf, MapInfo)> {
+    use procfs::process::{MMPermissions, MMapPath};
+
+    let mut files: Vec<(PathBuf, MapInfo)> = Vec::new();
+    for map in maps {
+        let MMapPath::Path(ref path) = map.pathname else {
+            continue;
+        };
+        let info = MapInfo {
+            shared: map.perms.contains(MMPermissions::SHARED),
+            writable: map.perms.contains(MMPermissions::WRITE),
+        };
+        match files.iter_mut().find(|(p, _)| p == path) {
+            Some((_, kept)) if info.rank() > kept.rank() => *kept = info,
+            Some(_) => {}
+            None => files.push((path.clone(), info)),
+        }
+    }
+    files
+}
+
+/// Parse the contents of `/proc/[pid]/mountinfo`.
+///
+/// Each line looks like:
+/// `36 35 98:0 /mnt1 /mnt/parent rw,noatime master:1 - ext3 /dev/root rw`
+/// where the optional fields end at the `-` separator, followed by the
+/// filesystem type.
+fn parse_mountinfo(content: &str) -> Vec<MountInfo> {
+    let mut mounts = Vec::new();
+    for line in content.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        // Six fixed fields (ID to mount options) come before the `-`.
+        if fields.len() < 6 {
+            continue;
+        }
+        let sep = match fields.iter().skip(6).position(|f| *f == "-") {
+            Some(i) => i + 6,
+            None => continue,
+        };
+        let fstype = match fields.get(sep + 1) {
+            Some(t) => t.to_string(),
+            None => continue,
+        };
+        mounts.push(MountInfo {
+            device: fields[2].replace(':', ","),
+            mount_point: fields[4].to_string(),
+            fstype,
+            options: fields[5].split(',').map(str::to_string).collect(),
+        });
+    }
+    mounts
 }
 
 // ---------------------------------------------------------------------------
 // Socket inode map builder
 // ---------------------------------------------------------------------------
 
-/// Build a map from socket inode -> SocketNetInfo by reading
-/// /proc/net/tcp, tcp6, udp, udp6, and unix.
-fn build_socket_inode_map() -> HashMap<u64, SocketNetInfo> {
+/// Which `/proc` socket tables describe a process's sockets. `/proc/net`
+/// shows our own network namespace, so a process in another one (a
+/// container) needs its own `/proc/[pid]/net` instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NetTables {
+    Own,
+    OfPid(i32),
+}
+
+impl NetTables {
+    /// The tables for `pid`, comparing its `ns/net` link to ours. When
+    /// either link can't be read (no permission) our own tables are used.
+    fn for_pid(pid: i32) -> Self {
+        let ns = |who: &str| fs::read_link(format!("/proc/{}/ns/net", who)).ok();
+        Self::choose(pid, ns("self"), ns(&pid.to_string()))
+    }
+
+    fn choose(pid: i32, own_ns: Option<PathBuf>, proc_ns: Option<PathBuf>) -> Self {
+        match (own_ns, proc_ns) {
+            (Some(own), Some(theirs)) if own != theirs => NetTables::OfPid(pid),
+            _ => NetTables::Own,
+        }
+    }
+}
+
+/// Build a map from socket inode -> SocketNetInfo by reading tcp, tcp6,
+/// udp, udp6 and unix from `/proc/net`, or from `/proc/[pid]/net` for a
+/// process in another network namespace.
+fn build_socket_inode_map(raw_addr: bool, tables: NetTables) -> HashMap<u64, SocketNetInfo> {
+    let mut span = trace::span("build_socket_inode_map");
     let mut map = HashMap::new();
 
+    let process = match tables {
+        NetTables::Own => None,
+        NetTables::OfPid(pid) => match procfs::process::Process::new(pid) {
+            Ok(process) => Some(process),
+            Err(_) => return map,
+        },
+    };
+    // Each table from our own /proc/net or from the process's.
+    macro_rules! table {
+        ($name:ident) => {
+            match &process {
+                Some(p) => p.$name(),
+                None => procfs::net::$name(),
+            }
+        };
+    }
+
     // TCP (IPv4)
-    if let Ok(entries) = procfs::net::tcp() {
+    if let Ok(entries) = table!(tcp) {
         for entry in entries {
             map.insert(
                 entry.inode,
                 SocketNetInfo {
                     protocol: Protocol::Tcp,
-                    local_addr: addr_ip_string(&entry.local_address),
+                    local_addr: addr_ip_string(&entry.local_address, raw_addr),
                     local_port: entry.local_address.port(),
-                    remote_addr: addr_ip_string(&entry.remote_address),
+                    remote_addr: addr_ip_string(&entry.remote_address, raw_addr),
                     remote_port: entry.remote_address.port(),
+                    remote: Some(entry.remote_address),
                     state: map_tcp_state(&entry.state),
                     tx_queue: Some(entry.tx_queue as u64),
                     rx_queue: Some(entry.rx_queue as u64),
@@ -152,16 +485,17 @@ fn build_socket_inode_map() -> HashMap<u64, SocketNetInfo> {
     }
 
     // TCP6 (IPv6)
-    if let Ok(entries) = procfs::net::tcp6() {
+    if let Ok(entries) = table!(tcp6) {
         for entry in entries {
             map.insert(
                 entry.inode,
                 SocketNetInfo {
                     protocol: Protocol::Tcp6,
-                    local_addr: addr_ip_string(&entry.local_address),
+                    local_addr: addr_ip_string(&entry.local_address, raw_addr),
                     local_port: entry.local_address.port(),
-                    remote_addr: addr_ip_string(&entry.remote_address),
+                    remote_addr: addr_ip_string(&entry.remote_address, raw_addr),
                     remote_port: entry.remote_address.port(),
+                    remote: Some(entry.remote_address),
                     state: map_tcp_state(&entry.state),
                     tx_queue: Some(entry.tx_queue as u64),
                     rx_queue: Some(entry.rx_queue as u64),
@@ -171,20 +505,18 @@ fn build_socket_inode_map() -> HashMap<u64, SocketNetInfo> {
     }
 
     // UDP (IPv4)
-    if let Ok(entries) = procfs::net::udp() {
+    if let Ok(entries) = table!(udp) {
         for entry in entries {
-            let state = match entry.state {
-                procfs::net::UdpState::Established => TcpState::Established,
-                _ => TcpState::Unknown("NONE".to_string()),
-            };
+            let state = map_udp_state(&entry.state);
             map.insert(
                 entry.inode,
                 SocketNetInfo {
                     protocol: Protocol::Udp,
-                    local_addr: addr_ip_string(&entry.local_address),
+                    local_addr: addr_ip_string(&entry.local_address, raw_addr),
                     local_port: entry.local_address.port(),
-                    remote_addr: addr_ip_string(&entry.remote_address),
+                    remote_addr: addr_ip_string(&entry.remote_address, raw_addr),
                     remote_port: entry.remote_address.port(),
+                    remote: Some(entry.remote_address),
                     state,
                     tx_queue: Some(entry.tx_queue as u64),
                     rx_queue: Some(entry.rx_queue as u64),
@@ -194,20 +526,18 @@ fn build_socket_inode_map() -> HashMap<u64, SocketNetInfo> {
     }
 
     // UDP6 (IPv6)
-    if let Ok(entries) = procfs::net::udp6() {
+    if let Ok(entries) = table!(udp6) {
         for entry in entries {
-            let state = match entry.state {
-                procfs::net::UdpState::Established => TcpState::Established,
-                _ => TcpState::Unknown("NONE".to_string()),
-            };
+            let state = map_udp_state(&entry.state);
             map.insert(
                 entry.inode,
                 SocketNetInfo {
                     protocol: Protocol::Udp6,
-                    local_addr: addr_ip_string(&entry.local_address),
+                    local_addr: addr_ip_string(&entry.local_address, raw_addr),
                     local_port: entry.local_address.port(),
-                    remote_addr: addr_ip_string(&entry.remote_address),
+                    remote_addr: addr_ip_string(&entry.remote_address, raw_addr),
                     remote_port: entry.remote_address.port(),
+                    remote: Some(entry.remote_address),
                     state,
                     tx_queue: Some(entry.tx_queue as u64),
                     rx_queue: Some(entry.rx_queue as u64),
@@ -217,7 +547,7 @@ fn build_socket_inode_map() -> HashMap<u64, SocketNetInfo> {
     }
 
     // Unix domain sockets
-    if let Ok(entries) = procfs::net::unix() {
+    if let Ok(entries) = table!(unix) {
         for entry in entries {
             let path_str = entry
                 .path
@@ -232,7 +562,8 @@ fn build_socket_inode_map() -> HashMap<u64, SocketNetInfo> {
                     local_port: 0,
                     remote_addr: String::new(),
                     remote_port: 0,
-                    state: TcpState::Unknown("NONE".to_string()),
+                    remote: None,
+                    state: TcpState::None,
                     tx_queue: None,
                     rx_queue: None,
                 },
@@ -240,13 +571,203 @@ fn build_socket_inode_map() -> HashMap<u64, SocketNetInfo> {
         }
     }
 
+    span.record("sockets", map.len());
     map
 }
 
+// ---------------------------------------------------------------------------
+// File locks (/proc/locks)
+// ---------------------------------------------------------------------------
+
+/// A held lock from `/proc/locks`. `pid` is `None` for OFD locks, which
+/// belong to an open file description rather than a process.
+#[derive(Debug, Clone, PartialEq)]
+struct HeldLock {
+    pid: Option<u32>,
+    info: LockInfo,
+}
+
+/// Held locks by (device "major,minor", inode).
+type LockTable = HashMap<(String, String), Vec<HeldLock>>;
+
+/// Parse `/proc/locks` into (device "major,minor", inode) -> held locks.
+/// Blocked waiters (`->` lines) and leases are skipped.
+///
+/// ```text
+/// 1: POSIX  ADVISORY  WRITE 1234 08:01:5678 0 EOF
+/// ```
+fn parse_locks(content: &str) -> LockTable {
+    let mut map: HashMap<(String, String), Vec<HeldLock>> = HashMap::new();
+    for line in content.lines() {
+        let fields: Vec<&str> = line.split_whitespace().skip(1).collect();
+        let [kind, _mode, access, pid, id, start, end] = fields[..] else {
+            continue;
+        };
+        if !matches!(kind, "POSIX" | "FLOCK" | "OFDLCK") {
+            continue;
+        }
+        let mut parts = id.split(':');
+        let (Some(major), Some(minor), Some(inode), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let (Ok(major), Ok(minor)) = (
+            u64::from_str_radix(major, 16),
+            u64::from_str_radix(minor, 16),
+        ) else {
+            continue;
+        };
+        map.entry((format!("{},{}", major, minor), inode.to_string()))
+            .or_default()
+            .push(HeldLock {
+                pid: pid.parse().ok(),
+                info: LockInfo {
+                    write: access == "WRITE",
+                    whole_file: start == "0" && end == "EOF",
+                },
+            });
+    }
+    map
+}
+
+/// The lock `pid` holds on `file`, if any.
+fn lock_for(locks: &LockTable, pid: u32, file: &OpenFileInfo) -> Option<LockInfo> {
+    locks
+        .get(&(file.device.clone(), file.node.clone()))?
+        .iter()
+        .find(|l| l.pid.is_none_or(|p| p == pid))
+        .map(|l| l.info)
+}
+
+/// `AF_NETLINK` and `AF_PACKET` from `<sys/socket.h>`.
+const AF_NETLINK: u32 = 16;
+const AF_PACKET: u32 = 17;
+
+/// Parse a `/proc/net/{netlink,packet}`-style table into
+/// inode -> (family, protocol). `proto_col` is read in `proto_radix`.
+fn parse_raw_socket_table(
+    content: &str,
+    family: u32,
+    proto_col: usize,
+    proto_radix: u32,
+    inode_col: usize,
+) -> HashMap<u64, (u32, u32)> {
+    let mut map = HashMap::new();
+    for line in content.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let proto = fields
+            .get(proto_col)
+            .and_then(|p| u32::from_str_radix(p, proto_radix).ok());
+        let inode = fields.get(inode_col).and_then(|i| i.parse::<u64>().ok());
+        if let (Some(proto), Some(inode)) = (proto, inode) {
+            map.insert(inode, (family, proto));
+        }
+    }
+    map
+}
+
+/// Family and protocol for sockets not covered by the inet/unix tables
+/// (used by `--raw-proto`).
+fn build_raw_socket_map() -> HashMap<u64, (u32, u32)> {
+    let mut map = HashMap::new();
+    if let Ok(content) = fs::read_to_string("/proc/net/netlink") {
+        // sk Eth Pid Groups Rmem Wmem Dump Locks Drops Inode
+        map.extend(parse_raw_socket_table(&content, AF_NETLINK, 1, 10, 9));
+    }
+    if let Ok(content) = fs::read_to_string("/proc/net/packet") {
+        // sk RefCnt Type Proto Iface R Rmem User Inode (Proto is hex)
+        map.extend(parse_raw_socket_table(&content, AF_PACKET, 3, 16, 8));
+    }
+    map
+}
+
+/// Display name for an unmapped socket with its numeric family/protocol.
+fn raw_socket_name(inode: u64, family_proto: Option<(u32, u32)>) -> String {
+    match family_proto {
+        Some((family, proto)) => {
+            format!("socket:[{}] family={} proto={}", inode, family, proto)
+        }
+        None => format!("socket:[{}] family=?", inode),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Build an OpenFileInfo from a path (used for cwd, root, exe, and Path FDs)
 // ---------------------------------------------------------------------------
 
+/// File type, device, size, inode, link target and access time of a
+/// stat'ed path, and whether the path resolved (see [`resolution`]).
+type StatFields = (
+    FileType,
+    String,
+    Option<u64>,
+    String,
+    Option<String>,
+    Option<SystemTime>,
+    Option<bool>,
+);
+
+/// What a failed `stat` says about the path: `Some(false)` when it is gone
+/// (`ENOENT`, `ENOTDIR`), `None` when the failure (permissions, I/O) says
+/// nothing either way.
+fn resolution(err: &std::io::Error) -> Option<bool> {
+    if err.kind() == std::io::ErrorKind::NotFound || err.raw_os_error() == Some(nix::libc::ENOTDIR)
+    {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Stat `path` following symlinks (`-L`).
+///
+/// Broken links fall back to `symlink_metadata` so the entry keeps the
+/// link's own identity and records the dangling target instead of losing
+/// everything. Only `stat`-family calls are made, never `open`, so FIFOs
+/// and devices behind a link cannot block.
+fn stat_following(path: &Path) -> StatFields {
+    let link_meta = match fs::symlink_metadata(path) {
+        Ok(m) => m,
+        Err(e) => {
+            return (
+                FileType::Unknown("?".to_string()),
+                String::new(),
+                None,
+                String::new(),
+                None,
+                None,
+                resolution(&e),
+            )
+        }
+    };
+    let link_target = if link_meta.file_type().is_symlink() {
+        fs::read_link(path)
+            .ok()
+            .map(|p| p.to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    let (meta, resolved) = if link_target.is_some() {
+        match fs::metadata(path) {
+            Ok(meta) => (meta, Some(true)),
+            Err(e) => (link_meta, resolution(&e)),
+        }
+    } else {
+        (link_meta, Some(true))
+    };
+    (
+        classify_file_type(&meta),
+        format_device(meta.dev()),
+        Some(meta.size()),
+        meta.ino().to_string(),
+        link_target,
+        meta.accessed().ok(),
+        resolved,
+    )
+}
+
 /// Create an `OpenFileInfo` for a special entry (cwd, rtd, txt) from a path.
 fn open_file_from_path(path: &Path, fd_type: FdType, config: &ProviderConfig) -> OpenFileInfo {
     let name = path.to_string_lossy().to_string();
@@ -256,65 +777,53 @@ fn open_file_from_path(path: &Path, fd_type: FdType, config: &ProviderConfig) ->
         return OpenFileInfo {
             fd: fd_type,
             file_type: FileType::Unknown("".into()),
-            device: String::new(),
-            size_off: None,
-            node: String::new(),
             name,
-            mode: None,
-            link_target: None,
-            send_queue: None,
-            recv_queue: None,
+            ..Default::default()
         };
     }
 
-    // When follow_symlinks is true, use metadata() (follows symlinks) instead
-    // of symlink_metadata().
-    let (file_type, device, size_off, node, link_target) = if config.follow_symlinks {
-        match fs::metadata(path) {
-            Ok(meta) => {
-                let ft = classify_file_type(&meta);
-                let dev = format_device(meta.dev());
-                let size = Some(meta.size());
-                let ino = meta.ino().to_string();
-                (ft, dev, size, ino, None)
-            }
-            Err(_) => (
-                FileType::Unknown("?".to_string()),
-                String::new(),
-                None,
-                String::new(),
-                None,
-            ),
-        }
-    } else {
-        // Try symlink_metadata first (does not follow symlinks), then metadata.
-        match fs::symlink_metadata(path) {
-            Ok(meta) => {
-                let ft = classify_file_type(&meta);
-                let dev = format_device(meta.dev());
-                let size = Some(meta.size());
-                let ino = meta.ino().to_string();
-                let lt = if ft == FileType::Link {
-                    fs::read_link(path)
-                        .ok()
-                        .map(|p| p.to_string_lossy().to_string())
-                } else {
-                    None
-                };
-                (ft, dev, size, ino, lt)
-            }
-            Err(_) => {
-                // Cannot stat -- still record the entry with what we know.
-                (
-                    FileType::Unknown("?".to_string()),
-                    String::new(),
-                    None,
-                    String::new(),
-                    None,
-                )
+    // When follow_symlinks is true, report the link target, falling back to
+    // the link itself when it dangles.
+    let (file_type, device, size_off, node, link_target, atime, resolved) =
+        if config.follow_symlinks {
+            stat_following(path)
+        } else {
+            // Try symlink_metadata first (does not follow symlinks), then metadata.
+            match fs::symlink_metadata(path) {
+                Ok(meta) => {
+                    let ft = classify_file_type(&meta);
+                    let dev = format_device(meta.dev());
+                    let size = Some(meta.size());
+                    let ino = meta.ino().to_string();
+                    let lt = if ft == FileType::Link {
+                        fs::read_link(path)
+                            .ok()
+                            .map(|p| p.to_string_lossy().to_string())
+                    } else {
+                        None
+                    };
+                    // A link resolves only if its target does.
+                    let resolved = if ft == FileType::Link {
+                        fs::metadata(path).map_or_else(|e| resolution(&e), |_| Some(true))
+                    } else {
+                        Some(true)
+                    };
+                    (ft, dev, size, ino, lt, meta.accessed().ok(), resolved)
+                }
+                Err(e) => {
+                    // Cannot stat -- still record the entry with what we know.
+                    (
+                        FileType::Unknown("?".to_string()),
+                        String::new(),
+                        None,
+                        String::new(),
+                        None,
+                        None,
+                        resolution(&e),
+                    )
+                }
             }
-        }
-    };
+        };
 
     OpenFileInfo {
         fd: fd_type,
@@ -323,10 +832,10 @@ fn open_file_from_path(path: &Path, fd_type: FdType, config: &ProviderConfig) ->
         size_off,
         node,
         name,
-        mode: None,
         link_target,
-        send_queue: None,
-        recv_queue: None,
+        atime,
+        resolved,
+        ..Default::default()
     }
 }
 
@@ -344,45 +853,18 @@ fn open_file_from_fd_path(
         return OpenFileInfo {
             fd: FdType::Numbered(fd_num, mode),
             file_type: FileType::Unknown("".into()),
-            device: String::new(),
-            size_off: None,
-            node: String::new(),
             name,
             mode: Some(mode),
-            link_target: None,
-            send_queue: None,
-            recv_queue: None,
+            ..Default::default()
         };
     }
 
-    // When follow_symlinks is true and the path is a symlink, use metadata()
-    // to follow the link. Otherwise use the normal metadata -> symlink_metadata
-    // fallback chain.
-    let (file_type, device, size_off, node, link_target) = if config.follow_symlinks {
-        // Check if it's a symlink first; if so, follow it with metadata().
-        let is_symlink = fs::symlink_metadata(path)
-            .map(|m| m.file_type().is_symlink())
-            .unwrap_or(false);
-        if is_symlink {
-            match fs::metadata(path) {
-                Ok(meta) => {
-                    let ft = classify_file_type(&meta);
-                    let dev = format_device(meta.dev());
-                    let size = Some(meta.size());
-                    let ino = meta.ino().to_string();
-                    let lt = fs::read_link(path)
-                        .ok()
-                        .map(|p| p.to_string_lossy().to_string());
-                    (ft, dev, size, ino, lt)
-                }
-                Err(_) => (
-                    FileType::Unknown("?".to_string()),
-                    String::new(),
-                    None,
-                    String::new(),
-                    None,
-                ),
-            }
+    // When follow_symlinks is true, report the link target, falling back to
+    // the link itself when it dangles. Otherwise use the normal metadata ->
+    // symlink_metadata fallback chain.
+    let (file_type, device, size_off, node, link_target, atime, resolved) =
+        if config.follow_symlinks {
+            stat_following(path)
         } else {
             match fs::metadata(path) {
                 Ok(meta) => {
@@ -390,54 +872,39 @@ fn open_file_from_fd_path(
                     let dev = format_device(meta.dev());
                     let size = Some(meta.size());
                     let ino = meta.ino().to_string();
-                    (ft, dev, size, ino, None)
+                    let lt = if ft == FileType::Link {
+                        fs::read_link(path)
+                            .ok()
+                            .map(|p| p.to_string_lossy().to_string())
+                    } else {
+                        None
+                    };
+                    (ft, dev, size, ino, lt, meta.accessed().ok(), Some(true))
                 }
-                Err(_) => (
-                    FileType::Unknown("?".to_string()),
-                    String::new(),
-                    None,
-                    String::new(),
-                    None,
-                ),
-            }
-        }
-    } else {
-        match fs::metadata(path) {
-            Ok(meta) => {
-                let ft = classify_file_type(&meta);
-                let dev = format_device(meta.dev());
-                let size = Some(meta.size());
-                let ino = meta.ino().to_string();
-                let lt = if ft == FileType::Link {
-                    fs::read_link(path)
-                        .ok()
-                        .map(|p| p.to_string_lossy().to_string())
-                } else {
-                    None
-                };
-                (ft, dev, size, ino, lt)
-            }
-            Err(_) => {
-                // Fallback: try symlink_metadata (the fd link itself).
-                match fs::symlink_metadata(path) {
-                    Ok(meta) => {
-                        let ft = classify_file_type(&meta);
-                        let dev = format_device(meta.dev());
-                        let size = Some(meta.size());
-                        let ino = meta.ino().to_string();
-                        (ft, dev, size, ino, None)
+                Err(e) => {
+                    // Fallback: try symlink_metadata (the fd link itself).
+                    match fs::symlink_metadata(path) {
+                        Ok(meta) => {
+                            let ft = classify_file_type(&meta);
+                            let dev = format_device(meta.dev());
+                            let size = Some(meta.size());
+                            let ino = meta.ino().to_string();
+                            let resolved = resolution(&e);
+                            (ft, dev, size, ino, None, meta.accessed().ok(), resolved)
+                        }
+                        Err(_) => (
+                            FileType::Unknown("?".to_string()),
+                            String::new(),
+                            None,
+                            String::new(),
+                            None,
+                            None,
+                            resolution(&e),
+                        ),
                     }
-                    Err(_) => (
-                        FileType::Unknown("?".to_string()),
-                        String::new(),
-                        None,
-                        String::new(),
-                        None,
-                    ),
                 }
             }
-        }
-    };
+        };
 
     OpenFileInfo {
         fd: FdType::Numbered(fd_num, mode),
@@ -448,8 +915,9 @@ fn open_file_from_fd_path(
         name,
         mode: Some(mode),
         link_target,
-        send_queue: None,
-        recv_queue: None,
+        atime,
+        resolved,
+        ..Default::default()
     }
 }
 
@@ -457,19 +925,79 @@ fn open_file_from_fd_path(
 // LinuxProvider
 // ---------------------------------------------------------------------------
 
+/// How long rarely-changing tables are reused across scans (`-r`).
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Tables kept between scans (the uid -> name map and the mount table)
+/// and for one scan (the lock table). Process and socket state is always
+/// read fresh.
+#[derive(Default)]
+struct CachedTables {
+    user_names: HashMap<u32, String>,
+    mounts: Option<Vec<MountInfo>>,
+    /// `/proc/locks`, read by the first `list_open_files` of a scan and
+    /// dropped by `list_processes`, which starts the next one.
+    locks: Option<Arc<LockTable>>,
+}
+
 pub struct LinuxProvider {
     config: ProviderConfig,
+    /// Since when, and what, is cached; dropped once older than `ttl`.
+    cache: Mutex<(Instant, CachedTables)>,
+    ttl: Duration,
+    /// Times `/proc/self/mountinfo` was read and parsed.
+    mountinfo_reads: AtomicUsize,
+    /// Times `/proc/locks` was read and parsed.
+    locks_reads: AtomicUsize,
 }
 
 impl LinuxProvider {
     pub fn new(config: ProviderConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            cache: Mutex::new((Instant::now(), CachedTables::default())),
+            ttl: CACHE_TTL,
+            mountinfo_reads: AtomicUsize::new(0),
+            locks_reads: AtomicUsize::new(0),
+        }
+    }
+
+    /// Run `f` on the cached tables, clearing them first if they have
+    /// outlived the TTL. A poisoned lock just means starting empty.
+    fn with_cache<T>(&self, f: impl FnOnce(&mut CachedTables) -> T) -> T {
+        let mut guard = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        let (since, tables) = &mut *guard;
+        if since.elapsed() >= self.ttl {
+            *since = Instant::now();
+            *tables = CachedTables::default();
+        }
+        f(tables)
+    }
+
+    /// This scan's lock table, parsed on first use.
+    fn scan_locks(&self) -> Arc<LockTable> {
+        self.with_cache(|c| {
+            c.locks
+                .get_or_insert_with(|| {
+                    self.locks_reads.fetch_add(1, Ordering::Relaxed);
+                    let locks = fs::read_to_string("/proc/locks")
+                        .map(|c| parse_locks(&c))
+                        .unwrap_or_default();
+                    Arc::new(locks)
+                })
+                .clone()
+        })
     }
 }
 
 impl PlatformProvider for LinuxProvider {
     fn list_processes(&self) -> Result<Vec<ProcessInfo>> {
         let mut processes = Vec::new();
+        let cached_names = self.with_cache(|c| {
+            c.locks = None;
+            std::mem::take(&mut c.user_names)
+        });
+        let mut user_names = system_user_names().with_cache(cached_names);
         let all_procs =
             procfs::process::all_processes().map_err(|e| LoofError::Platform(e.to_string()))?;
 
@@ -485,11 +1013,9 @@ impl PlatformProvider for LinuxProvider {
             };
 
             let uid = proc.uid().unwrap_or(0);
-            let user = users::get_user_by_uid(uid)
-                .map(|u| u.name().to_string_lossy().to_string())
-                .unwrap_or_else(|| uid.to_string());
+            let user = user_names.name(uid);
 
-            let cmdline = proc.cmdline().unwrap_or_default().join(" ");
+            let cmdline = join_cmdline(&proc.cmdline().unwrap_or_default(), self.config.argv_raw);
             let command = if cmdline.is_empty() {
                 format!("[{}]", stat.comm)
             } else {
@@ -500,14 +1026,45 @@ impl PlatformProvider for LinuxProvider {
                 pid: stat.pid as u32,
                 ppid: Some(stat.ppid as u32),
                 pgid: Some(stat.pgrp as u32),
+                nspid: if self.config.ns_pids {
+                    fs::read_to_string(format!("/proc/{}/status", stat.pid))
+                        .ok()
+                        .and_then(|status| parse_nspid(&status))
+                } else {
+                    None
+                },
+                state: Some(ProcState::from_code(stat.state)),
+                container: if self.config.containers {
+                    fs::read_to_string(format!("/proc/{}/cgroup", stat.pid))
+                        .ok()
+                        .and_then(|cgroup| parse_container_id(&cgroup))
+                } else {
+                    None
+                },
+                security_context: if self.config.security_contexts {
+                    fs::read(format!("/proc/{}/attr/current", stat.pid))
+                        .ok()
+                        .and_then(|raw| parse_security_context(&raw))
+                } else {
+                    None
+                },
+                fd_limit: if self.config.fd_limits {
+                    fs::read_to_string(format!("/proc/{}/limits", stat.pid))
+                        .ok()
+                        .and_then(|limits| parse_fd_limit(&limits))
+                } else {
+                    None
+                },
                 command,
                 comm: stat.comm.clone(),
                 user,
                 uid,
-                open_files: Vec::new(),
+                ..Default::default()
             });
         }
 
+        let names = user_names.into_cache();
+        self.with_cache(|c| c.user_names = names);
         Ok(processes)
     }
 
@@ -534,41 +1091,49 @@ impl PlatformProvider for LinuxProvider {
             results.push(open_file_from_path(&exe_path, FdType::Txt, &self.config));
         }
 
-        // --- Memory-mapped files (mem entries) ---
-        if let Ok(maps) = process.maps() {
-            let mut seen_paths = std::collections::HashSet::new();
-            for map in maps.iter() {
-                if let procfs::process::MMapPath::Path(ref p) = map.pathname {
-                    let path_str = p.to_string_lossy().to_string();
-                    if seen_paths.insert(path_str.clone()) {
-                        let (file_type, device, size_off, node) = match fs::metadata(p) {
-                            Ok(meta) => (
-                                classify_file_type(&meta),
-                                format_device(meta.dev()),
-                                Some(meta.size()),
-                                meta.ino().to_string(),
-                            ),
-                            Err(_) => (FileType::Reg, String::new(), None, String::new()),
-                        };
-                        results.push(OpenFileInfo {
-                            fd: FdType::Mem,
-                            file_type,
-                            device,
-                            size_off,
-                            node,
-                            name: path_str,
-                            mode: Some(FdMode::Read),
-                            link_target: None,
-                            send_queue: None,
-                            recv_queue: None,
-                        });
-                    }
-                }
+        // --- Memory-mapped files (mem entries), unless --no-mmap ---
+        let maps = if self.config.no_mmap {
+            None
+        } else {
+            read_maps(|| fs::read_to_string(format!("/proc/{}/maps", pid)))
+        };
+        if let Some(maps) = maps {
+            for (path, mapping) in mapped_files(&maps) {
+                let (file_type, device, size_off, node) = match fs::metadata(&path) {
+                    Ok(meta) => (
+                        classify_file_type(&meta),
+                        format_device(meta.dev()),
+                        Some(meta.size()),
+                        meta.ino().to_string(),
+                    ),
+                    Err(_) => (FileType::Reg, String::new(), None, String::new()),
+                };
+                results.push(OpenFileInfo {
+                    fd: FdType::Mem,
+                    file_type,
+                    device,
+                    size_off,
+                    node,
+                    name: path.to_string_lossy().to_string(),
+                    mode: Some(if mapping.writable {
+                        FdMode::ReadWrite
+                    } else {
+                        FdMode::Read
+                    }),
+                    mapping: Some(mapping),
+                    ..Default::default()
+                });
             }
         }
 
         // --- Build socket inode map for resolving socket FDs ---
-        let socket_map = build_socket_inode_map();
+        let socket_map =
+            build_socket_inode_map(self.config.raw_addr, NetTables::for_pid(pid as i32));
+        let raw_socket_map = if self.config.raw_proto {
+            build_raw_socket_map()
+        } else {
+            HashMap::new()
+        };
 
         // --- Enumerate numbered FDs ---
         let fds = match process.fd() {
@@ -583,7 +1148,9 @@ impl PlatformProvider for LinuxProvider {
             };
 
             let fd_num = fd_info.fd as u32;
-            let mode = read_fd_flags(pid, fd_info.fd);
+            let flags = read_fd_flags(pid, fd_info.fd);
+            let mode = flags.map(fd_mode_from_flags).unwrap_or(FdMode::Unknown);
+            let first_new = results.len();
 
             match fd_info.target {
                 procfs::process::FDTarget::Path(ref path) => {
@@ -647,28 +1214,28 @@ impl PlatformProvider for LinuxProvider {
                         results.push(OpenFileInfo {
                             fd: FdType::Numbered(fd_num, mode),
                             file_type,
-                            device: String::new(),
-                            size_off: None,
                             node: inode.to_string(),
                             name,
                             mode: Some(mode),
-                            link_target: None,
                             send_queue: sock_info.tx_queue,
                             recv_queue: sock_info.rx_queue,
+                            connection: sock_info.connection(),
+                            ..Default::default()
                         });
                     } else {
                         // Socket inode not found in /proc/net tables.
+                        let name = if self.config.raw_proto {
+                            raw_socket_name(inode, raw_socket_map.get(&inode).copied())
+                        } else {
+                            format!("socket:[{}]", inode)
+                        };
                         results.push(OpenFileInfo {
                             fd: FdType::Numbered(fd_num, mode),
                             file_type: FileType::Sock,
-                            device: String::new(),
-                            size_off: None,
                             node: inode.to_string(),
-                            name: format!("socket:[{}]", inode),
+                            name,
                             mode: Some(mode),
-                            link_target: None,
-                            send_queue: None,
-                            recv_queue: None,
+                            ..Default::default()
                         });
                     }
                 }
@@ -725,27 +1292,22 @@ impl PlatformProvider for LinuxProvider {
                         results.push(OpenFileInfo {
                             fd: FdType::Numbered(fd_num, mode),
                             file_type,
-                            device: String::new(),
-                            size_off: None,
                             node: inode.to_string(),
                             name,
                             mode: Some(mode),
-                            link_target: None,
                             send_queue: sock_info.tx_queue,
                             recv_queue: sock_info.rx_queue,
+                            connection: sock_info.connection(),
+                            ..Default::default()
                         });
                     } else {
                         results.push(OpenFileInfo {
                             fd: FdType::Numbered(fd_num, mode),
                             file_type: FileType::Sock,
-                            device: String::new(),
-                            size_off: None,
                             node: inode.to_string(),
                             name: format!("net:[{}]", inode),
                             mode: Some(mode),
-                            link_target: None,
-                            send_queue: None,
-                            recv_queue: None,
+                            ..Default::default()
                         });
                     }
                 }
@@ -753,66 +1315,62 @@ impl PlatformProvider for LinuxProvider {
                     results.push(OpenFileInfo {
                         fd: FdType::Numbered(fd_num, mode),
                         file_type: FileType::Pipe,
-                        device: String::new(),
-                        size_off: None,
                         node: inode.to_string(),
                         name: format!("pipe:[{}]", inode),
                         mode: Some(mode),
-                        link_target: None,
-                        send_queue: None,
-                        recv_queue: None,
+                        ..Default::default()
                     });
                 }
                 procfs::process::FDTarget::AnonInode(ref desc) => {
+                    let (file_type, name) = classify_anon_inode(desc, pid, fd_num);
                     results.push(OpenFileInfo {
                         fd: FdType::Numbered(fd_num, mode),
-                        file_type: FileType::Unknown(desc.clone()),
-                        device: String::new(),
-                        size_off: None,
-                        node: String::new(),
-                        name: format!("anon_inode:[{}]", desc),
+                        file_type,
+                        name,
                         mode: Some(mode),
-                        link_target: None,
-                        send_queue: None,
-                        recv_queue: None,
+                        ..Default::default()
                     });
                 }
                 procfs::process::FDTarget::MemFD(ref name_str) => {
                     results.push(OpenFileInfo {
                         fd: FdType::Numbered(fd_num, mode),
                         file_type: FileType::Reg,
-                        device: String::new(),
-                        size_off: None,
-                        node: String::new(),
                         name: format!("memfd:{}", name_str),
                         mode: Some(mode),
-                        link_target: None,
-                        send_queue: None,
-                        recv_queue: None,
+                        ..Default::default()
                     });
                 }
                 procfs::process::FDTarget::Other(ref name_str, inode) => {
                     results.push(OpenFileInfo {
                         fd: FdType::Numbered(fd_num, mode),
                         file_type: FileType::Unknown(name_str.clone()),
-                        device: String::new(),
-                        size_off: None,
                         node: inode.to_string(),
                         name: format!("{}:[{}]", name_str, inode),
                         mode: Some(mode),
-                        link_target: None,
-                        send_queue: None,
-                        recv_queue: None,
+                        ..Default::default()
                     });
                 }
             }
+            for file in &mut results[first_new..] {
+                file.cloexec = flags.map(|f| f & O_CLOEXEC != 0);
+            }
+        }
+
+        let locks = self.scan_locks();
+        if !locks.is_empty() {
+            for file in &mut results {
+                if let FdType::Numbered(..) = file.fd {
+                    file.lock = lock_for(&locks, pid, file);
+                }
+            }
         }
 
         Ok(results)
     }
 
     fn list_network_connections(&self, pid: Option<u32>) -> Result<Vec<NetworkInfo>> {
-        let socket_map = build_socket_inode_map();
+        let tables = pid.map_or(NetTables::Own, |p| NetTables::for_pid(p as i32));
+        let socket_map = build_socket_inode_map(self.config.raw_addr, tables);
 
         match pid {
             Some(target_pid) => {
@@ -940,4 +1498,597 @@ impl PlatformProvider for LinuxProvider {
         proc_info.open_files = self.list_open_files(pid)?;
         Ok(proc_info)
     }
+
+    fn fd_count_hint(&self, pid: u32) -> Option<usize> {
+        fs::read_dir(format!("/proc/{}/fd", pid))
+            .ok()
+            .map(|entries| entries.count())
+    }
+
+    fn exe_path(&self, pid: u32) -> Option<String> {
+        procfs::process::Process::new(pid as i32)
+            .ok()?
+            .exe()
+            .ok()
+            .map(|p| p.to_string_lossy().to_string())
+    }
+
+    fn list_mounts(&self) -> Result<Vec<MountInfo>> {
+        if let Some(mounts) = self.with_cache(|c| c.mounts.clone()) {
+            return Ok(mounts);
+        }
+        let content = fs::read_to_string("/proc/self/mountinfo")?;
+        self.mountinfo_reads.fetch_add(1, Ordering::Relaxed);
+        let mounts = parse_mountinfo(&content);
+        self.with_cache(|c| c.mounts = Some(mounts.clone()));
+        Ok(mounts)
+    }
+
+    fn socket_inode_map(&self) -> Result<Vec<(u64, NetworkInfo)>> {
+        let mut entries: Vec<(u64, NetworkInfo)> =
+            build_socket_inode_map(self.config.raw_addr, NetTables::Own)
+                .into_iter()
+                .map(|(inode, s)| {
+                    (
+                        inode,
+                        NetworkInfo {
+                            protocol: s.protocol,
+                            local_addr: s.local_addr,
+                            local_port: s.local_port,
+                            remote_addr: s.remote_addr,
+                            remote_port: s.remote_port,
+                            state: s.state,
+                            pid: None,
+                            command: None,
+                        },
+                    )
+                })
+                .collect();
+        entries.sort_by_key(|(inode, _)| *inode);
+        Ok(entries)
+    }
+
+    fn start_time(&self, pid: u32) -> Option<u64> {
+        // `starttime` is in clock ticks since boot; only equality matters.
+        procfs::process::Process::new(pid as i32)
+            .ok()?
+            .stat()
+            .ok()
+            .map(|s| s.starttime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures;
+
+    #[test]
+    fn test_parse_locks_and_correlate() {
+        let locks = parse_locks(
+            "1: POSIX  ADVISORY  WRITE 1234 08:01:5678 0 EOF\n\
+             2: FLOCK  ADVISORY  READ  77 00:2a:99 0 99\n\
+             3: -> POSIX ADVISORY WRITE 999 08:01:5678 0 EOF\n\
+             4: OFDLCK ADVISORY  WRITE -1 103:05:42 0 EOF\n",
+        );
+        assert_eq!(locks.len(), 3);
+        assert_eq!(locks[&("8,1".to_string(), "5678".to_string())].len(), 1);
+
+        let mut file = OpenFileInfo {
+            device: "8,1".into(),
+            node: "5678".into(),
+            ..fixtures::file(
+                FdType::Numbered(3, FdMode::ReadWrite),
+                FileType::Reg,
+                "/var/lib/app/lock",
+            )
+        };
+        let lock = lock_for(&locks, 1234, &file).unwrap();
+        assert_eq!(lock.code(), 'W');
+        // The blocked waiter doesn't hold it, and neither does anyone else.
+        assert_eq!(lock_for(&locks, 999, &file), None);
+
+        file.device = "0,42".into();
+        file.node = "99".into();
+        assert_eq!(lock_for(&locks, 77, &file).unwrap().code(), 'r');
+
+        // OFD locks have no owning PID; any opener of the file matches.
+        file.device = "259,5".into();
+        file.node = "42".into();
+        assert_eq!(lock_for(&locks, 5, &file).unwrap().code(), 'W');
+    }
+
+    #[test]
+    fn test_format_device_decodes_major_minor() {
+        assert_eq!(format_device(nix::libc::makedev(8, 1)), "8,1");
+        assert_eq!(format_device(nix::libc::makedev(259, 5)), "259,5");
+        // Minors above 255 spill into the high bits of dev_t.
+        assert_eq!(format_device(nix::libc::makedev(0, 1048575)), "0,1048575");
+    }
+
+    #[test]
+    fn test_follow_symlinks_keeps_broken_link() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("dangling");
+        std::os::unix::fs::symlink(dir.path().join("missing"), &link).unwrap();
+        let config = ProviderConfig {
+            follow_symlinks: true,
+            ..Default::default()
+        };
+
+        let info = open_file_from_path(&link, FdType::Cwd, &config);
+        assert_eq!(info.file_type, FileType::Link);
+        assert!(!info.node.is_empty());
+        assert!(info.link_target.unwrap().ends_with("missing"));
+
+        let info = open_file_from_fd_path(&link, 3, FdMode::Read, &config);
+        assert_eq!(info.file_type, FileType::Link);
+        assert!(info.link_target.unwrap().ends_with("missing"));
+    }
+
+    #[test]
+    fn test_walked_dir_tree_finds_open_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let path = dir.path().join("sub").join("held.log");
+        let _held = std::fs::File::create(&path).unwrap();
+        let mut filter = crate::filter::FilterConfig {
+            dir_tree: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        filter.walk_dir_tree().unwrap();
+        let walked = filter.walked.as_ref().unwrap();
+        assert_eq!(walked.len(), 3);
+
+        let provider = LinuxProvider::new(ProviderConfig::default());
+        let files = provider.list_open_files(std::process::id()).unwrap();
+        let canonical = std::fs::canonicalize(&path).unwrap();
+        let held: Vec<_> = files.iter().filter(|f| filter.matches_file(f)).collect();
+        assert!(held
+            .iter()
+            .any(|f| Path::new(&f.name) == canonical.as_path()));
+        assert!(held.iter().all(|f| walked.contains(&f.name)));
+    }
+
+    #[test]
+    fn test_failed_stat_marks_entry_unresolved() {
+        let dir = tempfile::tempdir().unwrap();
+        let gone = dir.path().join("gone.log");
+        let config = ProviderConfig::default();
+
+        let info = open_file_from_fd_path(&gone, 4, FdMode::Write, &config);
+        assert_eq!(info.resolved, Some(false));
+        let missing = crate::filter::FilterConfig {
+            missing: true,
+            ..Default::default()
+        };
+        assert!(missing.matches_file(&info));
+
+        let link = dir.path().join("dangling");
+        std::os::unix::fs::symlink(&gone, &link).unwrap();
+        let info = open_file_from_path(&link, FdType::Cwd, &config);
+        assert_eq!(info.resolved, Some(false));
+
+        let info = open_file_from_path(dir.path(), FdType::Cwd, &config);
+        assert_eq!(info.resolved, Some(true));
+        assert!(!missing.matches_file(&info));
+
+        // A path through a regular file fails with ENOTDIR: also gone.
+        let file = dir.path().join("plain");
+        std::fs::write(&file, b"").unwrap();
+        let info = open_file_from_path(&file.join("child"), FdType::Cwd, &config);
+        assert_eq!(info.resolved, Some(false));
+    }
+
+    #[test]
+    fn test_resolution_only_reports_missing_paths() {
+        use std::io::{Error, ErrorKind};
+        assert_eq!(resolution(&Error::from(ErrorKind::NotFound)), Some(false));
+        assert_eq!(
+            resolution(&Error::from_raw_os_error(nix::libc::ENOTDIR)),
+            Some(false)
+        );
+        assert_eq!(resolution(&Error::from(ErrorKind::PermissionDenied)), None);
+        assert_eq!(resolution(&Error::from_raw_os_error(nix::libc::EIO)), None);
+    }
+
+    #[test]
+    fn test_socket_dump_has_loopback_listener() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let provider = LinuxProvider::new(ProviderConfig::default());
+        let entries = provider.socket_inode_map().unwrap();
+        assert!(entries.iter().any(|(_, n)| n.local_addr == "127.0.0.1"
+            && n.local_port == port
+            && n.state == TcpState::Listen));
+    }
+
+    #[test]
+    fn test_tcp_socket_carries_remote_end() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let server = listener.local_addr().unwrap();
+        let _client = std::net::TcpStream::connect(server).unwrap();
+        let files = LinuxProvider::new(ProviderConfig::default())
+            .list_open_files(std::process::id())
+            .unwrap();
+        assert!(files.iter().any(|f| f.connection
+            == Some(Connection {
+                remote: server,
+                tcp_state: Some(TcpState::Established),
+            })));
+        assert!(files
+            .iter()
+            .filter(|f| f.file_type == FileType::Reg)
+            .all(|f| f.connection.is_none()));
+    }
+
+    #[test]
+    fn test_unconnected_udp_has_no_state() {
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = socket.local_addr().unwrap().port();
+        let provider = LinuxProvider::new(ProviderConfig::default());
+        let entries = provider.socket_inode_map().unwrap();
+        let (_, udp) = entries
+            .iter()
+            .find(|(_, n)| n.protocol == Protocol::Udp && n.local_port == port)
+            .unwrap();
+        assert_eq!(udp.state, TcpState::None);
+        assert_eq!(udp.state.to_string(), "NONE");
+    }
+
+    #[test]
+    fn test_no_mmap_skips_mem_entries() {
+        let pid = std::process::id();
+        let has_mem = |config: ProviderConfig| {
+            LinuxProvider::new(config)
+                .list_open_files(pid)
+                .unwrap()
+                .iter()
+                .any(|f| f.fd == FdType::Mem)
+        };
+        assert!(has_mem(ProviderConfig::default()));
+        assert!(!has_mem(ProviderConfig {
+            no_mmap: true,
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn test_raw_socket_family_shown() {
+        let netlink = "sk               Eth Pid        Groups   Rmem     Wmem     Dump  Locks    Drops    Inode\n\
+                       0000000005d82726 0   0          00000000 0        0        0     2        0        4\n\
+                       000000004f576b62 15  812        00000001 0        0        0     2        0        589\n";
+        let map = parse_raw_socket_table(netlink, AF_NETLINK, 1, 10, 9);
+        assert_eq!(map.get(&589), Some(&(16, 15)));
+        assert_eq!(
+            raw_socket_name(589, map.get(&589).copied()),
+            "socket:[589] family=16 proto=15"
+        );
+
+        let packet = "sk       RefCnt Type Proto  Iface R Rmem   User   Inode\n\
+                      ffff8881 3      3    88cc   2     1 0      0      7734\n";
+        let map = parse_raw_socket_table(packet, AF_PACKET, 3, 16, 8);
+        assert_eq!(map.get(&7734), Some(&(17, 0x88cc)));
+        assert_eq!(raw_socket_name(1, None), "socket:[1] family=?");
+    }
+
+    #[test]
+    fn test_cloexec_from_fdinfo_flags() {
+        use std::os::fd::AsRawFd;
+
+        assert_eq!(
+            parse_fdinfo_flags("pos:\t0\nflags:\t02100002\n"),
+            Some(0o2100002)
+        );
+        assert_eq!(parse_fdinfo_flags("pos:\t0\n"), None);
+
+        // std opens with O_CLOEXEC; dup() gives a copy without it.
+        let file = fs::File::open("/proc/self/status").unwrap();
+        let inherited = unsafe { nix::libc::dup(file.as_raw_fd()) };
+        assert!(inherited >= 0);
+
+        let pid = std::process::id();
+        let files = LinuxProvider::new(ProviderConfig::default())
+            .list_open_files(pid)
+            .unwrap();
+        let cloexec_of = |fd: i32| {
+            files
+                .iter()
+                .find(|f| matches!(f.fd, FdType::Numbered(n, _) if n == fd as u32))
+                .and_then(|f| f.cloexec)
+        };
+        assert_eq!(cloexec_of(file.as_raw_fd()), Some(true));
+        assert_eq!(cloexec_of(inherited), Some(false));
+        unsafe { nix::libc::close(inherited) };
+    }
+
+    #[test]
+    fn test_parse_fdinfo_pid() {
+        let sample =
+            "pos:\t0\nflags:\t02000002\nmnt_id:\t15\nino:\t1057\nPid:\t4242\nNSpid:\t4242\n";
+        assert_eq!(parse_fdinfo_pid(sample), Some(4242));
+        assert_eq!(parse_fdinfo_pid("pos:\t0\nPid:\t-1\n"), Some(-1));
+        assert_eq!(parse_fdinfo_pid("pos:\t0\nflags:\t02\n"), None);
+    }
+
+    #[test]
+    fn test_fd_limit_read_only_when_asked() {
+        let me = std::process::id();
+        let limit = |fd_limits| {
+            let provider = LinuxProvider::new(ProviderConfig {
+                fd_limits,
+                ..Default::default()
+            });
+            let procs = provider.list_processes().unwrap();
+            procs.into_iter().find(|p| p.pid == me).unwrap().fd_limit
+        };
+        assert!(limit(false).is_none());
+        assert!(limit(true).is_some());
+    }
+
+    #[test]
+    fn test_parse_fd_limit() {
+        let limits = "\
+Limit                     Soft Limit           Hard Limit           Units     
+Max cpu time              unlimited            unlimited            seconds   
+Max processes             63348                63348                processes 
+Max open files            1024                 524288               files     
+Max locked memory         8388608              8388608              bytes     
+";
+        assert_eq!(parse_fd_limit(limits), Some((1024, 524288)));
+        assert_eq!(
+            parse_fd_limit(
+                "Max open files            unlimited            unlimited            files\n"
+            ),
+            Some((u64::MAX, u64::MAX))
+        );
+        assert_eq!(parse_fd_limit("Max processes 10 10 processes\n"), None);
+    }
+
+    #[test]
+    fn test_parse_signalfd_mask() {
+        let content = "pos:\t0\nflags:\t02\nmnt_id:\t15\nino:\t1057\nsigmask:\t0000000000004002\n";
+        assert_eq!(
+            parse_signalfd_mask(content),
+            Some(vec!["SIGINT".to_string(), "SIGTERM".to_string()])
+        );
+        assert_eq!(
+            parse_signalfd_mask("sigmask:\t0000000200000000\n"),
+            Some(vec!["34".to_string()])
+        );
+        assert_eq!(parse_signalfd_mask("pos:\t0\n"), None);
+    }
+
+    #[test]
+    fn test_parse_timerfd_schedule() {
+        let armed = "pos:\t0\nflags:\t02000002\nmnt_id:\t15\nino:\t1057\nclockid: 1\n\
+                     ticks: 0\nsettime flags: 00\nit_value: (0, 250000000)\nit_interval: (5, 0)\n";
+        assert_eq!(
+            parse_timerfd_schedule(armed).as_deref(),
+            Some("next=0.25s,every=5s")
+        );
+        let oneshot = "clockid: 0\nit_value: (12, 0)\nit_interval: (0, 0)\n";
+        assert_eq!(parse_timerfd_schedule(oneshot).as_deref(), Some("next=12s"));
+        let idle = "clockid: 0\nit_value: (0, 0)\nit_interval: (0, 0)\n";
+        assert_eq!(parse_timerfd_schedule(idle).as_deref(), Some("disarmed"));
+        assert_eq!(parse_timerfd_schedule("pos:\t0\n"), None);
+    }
+
+    #[test]
+    fn test_classify_anon_inode_kinds() {
+        let (ft, name) = classify_anon_inode("bpf-map", 0, 0);
+        assert_eq!(ft, FileType::Unknown("BPFMAP".to_string()));
+        assert_eq!(name, "anon_inode:[bpf-map]");
+        let (ft, name) = classify_anon_inode("[eventfd]", 0, 0);
+        assert_eq!(ft, FileType::Unknown("a_inode".to_string()));
+        assert_eq!(name, "anon_inode:[eventfd]");
+    }
+
+    #[test]
+    fn test_mapped_files_flags_writable_shared_maps() {
+        use procfs::FromBufRead;
+
+        let maps = procfs::process::MemoryMaps::from_buf_read(
+            "7f0000000000-7f0000001000 r--p 00000000 08:01 100 /usr/lib/libc.so.6\n\
+             7f0000001000-7f0000002000 r-xp 00001000 08:01 100 /usr/lib/libc.so.6\n\
+             7f0000100000-7f0000200000 r--s 00000000 00:19 200 /dev/shm/ring\n\
+             7f0000200000-7f0000300000 rw-s 00000000 00:19 200 /dev/shm/ring\n\
+             7ffd00000000-7ffd00021000 rw-p 00000000 00:00 0 [stack]\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        let files = mapped_files(&maps.0);
+        let summary: Vec<(&str, String)> = files
+            .iter()
+            .map(|(p, m)| (p.to_str().unwrap(), m.code()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("/usr/lib/libc.so.6", "rp".to_string()),
+                ("/dev/shm/ring", "ws".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_maps_errors_are_tolerated() {
+        let table = "7f0000000000-7f0000001000 r--p 00000000 08:01 100 /usr/lib/libc.so.6\n\
+                     7f0000001000-7f00\n\
+                     7f0000100000-7f0000200000 rw-s 00000000 00:19 200 /dev/shm/ring\n";
+        let maps = parse_maps_lenient(table);
+        assert_eq!(maps.len(), 2);
+
+        // A failed first read is retried; two failures mean no mem entries.
+        let mut attempts = 0;
+        let maps = read_maps(|| {
+            attempts += 1;
+            if attempts == 1 {
+                Err(std::io::Error::other("torn read"))
+            } else {
+                Ok(table.to_string())
+            }
+        });
+        assert_eq!(maps.map(|m| m.len()), Some(2));
+        assert!(read_maps(|| Err(std::io::Error::other("gone"))).is_none());
+
+        // FD enumeration does not depend on the maps table.
+        let files = LinuxProvider::new(ProviderConfig::default())
+            .list_open_files(std::process::id())
+            .unwrap();
+        assert!(files.iter().any(|f| matches!(f.fd, FdType::Numbered(..))));
+    }
+
+    #[test]
+    fn test_net_tables_follow_the_network_namespace() {
+        let host = || Some(PathBuf::from("net:[4026531840]"));
+        let container = Some(PathBuf::from("net:[4026532512]"));
+        assert_eq!(NetTables::choose(42, host(), host()), NetTables::Own);
+        assert_eq!(
+            NetTables::choose(42, host(), container),
+            NetTables::OfPid(42)
+        );
+        // Unreadable links (another user's process): keep our own tables.
+        assert_eq!(NetTables::choose(42, host(), None), NetTables::Own);
+        assert_eq!(
+            NetTables::for_pid(std::process::id() as i32),
+            NetTables::Own
+        );
+
+        // Reading a PID's own tables finds its sockets, as /proc/net does.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let has_port = |tables| {
+            build_socket_inode_map(false, tables)
+                .values()
+                .any(|s| s.local_port == port)
+        };
+        assert!(has_port(NetTables::Own));
+        assert!(has_port(NetTables::OfPid(std::process::id() as i32)));
+    }
+
+    #[test]
+    fn test_join_cmdline_collapses_whitespace() {
+        let parts = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let padded = parts(&["postgres: checkpointer      ", "", ""]);
+        assert_eq!(join_cmdline(&padded, false), "postgres: checkpointer");
+        assert_eq!(
+            join_cmdline(&padded, true),
+            "postgres: checkpointer        "
+        );
+
+        let argv = parts(&["/usr/bin/python3", "-c", "print('a  b')"]);
+        assert_eq!(
+            join_cmdline(&argv, false),
+            "/usr/bin/python3 -c print('a b')"
+        );
+        assert_eq!(
+            join_cmdline(&argv, true),
+            "/usr/bin/python3 -c print('a  b')"
+        );
+        assert_eq!(join_cmdline(&[], false), "");
+    }
+
+    #[test]
+    fn test_parse_security_context() {
+        assert_eq!(
+            parse_security_context(b"system_u:system_r:httpd_t:s0\0").as_deref(),
+            Some("system_u:system_r:httpd_t:s0")
+        );
+        assert_eq!(parse_security_context(b"_\n").as_deref(), Some("_"));
+        assert_eq!(parse_security_context(b"\0"), None);
+        assert_eq!(parse_security_context(b""), None);
+    }
+
+    #[test]
+    fn test_parse_container_id() {
+        let id = "3f4e9b1c2d7a8e6f5b4c3d2e1f0a9b8c7d6e5f4a3b2c1d0e9f8a7b6c5d4e3f2a";
+        let cases = [
+            format!("12:pids:/docker/{id}\n0::/docker/{id}\n"),
+            format!("0::/system.slice/docker-{id}.scope\n"),
+            format!(
+                "0::/kubepods.slice/kubepods-burstable.slice/\
+                 kubepods-burstable-pod1a2b.slice/cri-containerd-{id}.scope\n"
+            ),
+            format!("0::/kubepods.slice/kubepods-pod1a2b.slice/crio-{id}.scope\n"),
+            format!("0::/machine.slice/libpod-{id}.scope/container\n"),
+            format!("11:devices:/kubepods/besteffort/pod1a2b-3c4d/{id}\n"),
+        ];
+        for cgroup in &cases {
+            assert_eq!(
+                parse_container_id(cgroup).as_deref(),
+                Some(id),
+                "{}",
+                cgroup
+            );
+        }
+
+        assert_eq!(
+            parse_container_id("0::/user.slice/user-1000.slice/session-2.scope\n"),
+            None
+        );
+        assert_eq!(parse_container_id("0::/\n"), None);
+        let conmon = format!("0::/machine.slice/libpod-conmon-{id}.scope\n");
+        assert_eq!(parse_container_id(&conmon), None);
+    }
+
+    #[test]
+    fn test_parse_nspid() {
+        let status = "Name:\tnginx\nPid:\t4242\nNSpid:\t4242\t17\t1\nNStgid:\t4242\t17\t1\n";
+        assert_eq!(parse_nspid(status), Some(1));
+        assert_eq!(parse_nspid("NSpid:\t4242\n"), None);
+        assert_eq!(parse_nspid("Pid:\t4242\n"), None);
+    }
+
+    #[test]
+    fn test_parse_mountinfo() {
+        let sample = "\
+22 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw,errors=remount-ro
+40 22 0:35 / /mnt/share rw,relatime shared:20 master:3 - nfs srv:/export rw,vers=4.2
+41 22 0:36 / /tmp rw,nosuid,nodev - tmpfs tmpfs rw
+";
+        let mounts = parse_mountinfo(sample);
+        assert_eq!(mounts.len(), 3);
+        assert_eq!(mounts[0].device, "8,1");
+        assert_eq!(mounts[0].fstype, "ext4");
+        assert_eq!(mounts[1].mount_point, "/mnt/share");
+        assert_eq!(mounts[1].fstype, "nfs");
+        assert_eq!(mounts[2].fstype, "tmpfs");
+        assert_eq!(mounts[2].options, vec!["rw", "nosuid", "nodev"]);
+
+        // Truncated lines, with the separator too early or no type after it.
+        let malformed = "22 1 8:1 - ext4\n22 1 8:1 / / - ext4\n41 22 0:36 / /tmp rw -\n";
+        assert!(parse_mountinfo(malformed).is_empty());
+    }
+
+    #[test]
+    fn test_mountinfo_cached_within_ttl() {
+        let provider = LinuxProvider::new(ProviderConfig::default());
+        let first = provider.list_mounts().unwrap();
+        let second = provider.list_mounts().unwrap();
+        assert_eq!(provider.mountinfo_reads.load(Ordering::Relaxed), 1);
+        assert_eq!(first.len(), second.len());
+
+        let provider = LinuxProvider {
+            ttl: Duration::ZERO,
+            ..LinuxProvider::new(ProviderConfig::default())
+        };
+        provider.list_mounts().unwrap();
+        provider.list_mounts().unwrap();
+        assert_eq!(provider.mountinfo_reads.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_locks_read_once_per_scan() {
+        let provider = LinuxProvider::new(ProviderConfig::default());
+        let me = std::process::id();
+        provider.list_processes().unwrap();
+        provider.list_open_files(me).unwrap();
+        provider.list_open_files(me).unwrap();
+        assert_eq!(provider.locks_reads.load(Ordering::Relaxed), 1);
+
+        provider.list_processes().unwrap();
+        provider.list_open_files(me).unwrap();
+        assert_eq!(provider.locks_reads.load(Ordering::Relaxed), 2);
+    }
 }
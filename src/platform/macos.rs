@@ -1,13 +1,15 @@
-use super::{PlatformProvider, ProviderConfig};
+use super::{display_ip, system_user_names, PlatformProvider, ProviderConfig};
 use crate::error::{LoofError, Result};
 use crate::model::*;
 use sysinfo::System;
 
 use std::ffi::CStr;
 use std::mem;
-use std::net::{Ipv4Addr, Ipv6Addr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::os::raw::{c_int, c_void};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use libproc::libproc::bsd_info::BSDInfo;
 use libproc::libproc::file_info::{pidfdinfo, ListFDs, ProcFDInfo, ProcFDType};
 use libproc::libproc::net_info::{SocketFDInfo, SocketInfoKind, TcpSIState};
 use libproc::libproc::proc_pid::{listpidinfo, pidinfo, pidpath};
@@ -42,8 +44,8 @@ const FWRITE: u32 = 0x0002;
 
 // Minimal repr(C) structs mirroring the Darwin kernel structures we need.
 // We only define the fields we actually read; padding is handled by the
-// overall struct size being correct (verified by mem::size_of checks at
-// compile time would be ideal, but we rely on matching the kernel layout).
+// overall struct size being correct, which the assertions below check
+// against the sizes of the <sys/proc_info.h> definitions.
 
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -142,6 +144,26 @@ struct KqueueFdInfo {
     kqueueinfo: KqueueInfo,
 }
 
+// Sizes of the kernel structs, as passed in `buffersize`. The kernel
+// rejects a buffer of the wrong size, so a mismatch here means every
+// vnode/pipe/kqueue FD would silently come back empty.
+const VINFO_STAT_SIZE: usize = 136;
+const VNODE_INFO_SIZE: usize = 152;
+const VNODE_INFO_PATH_SIZE: usize = VNODE_INFO_SIZE + MAXPATHLEN;
+const PROC_FILEINFO_SIZE: usize = 24;
+const VNODE_FDINFOWITHPATH_SIZE: usize = 1200;
+const PIPE_FDINFO_SIZE: usize = 184;
+const KQUEUE_FDINFO_SIZE: usize = 168;
+
+const _: () = assert!(mem::size_of::<VInfoStat>() == VINFO_STAT_SIZE);
+const _: () = assert!(mem::size_of::<FsId>() == 8);
+const _: () = assert!(mem::size_of::<VnodeInfo>() == VNODE_INFO_SIZE);
+const _: () = assert!(mem::size_of::<VnodeInfoPath>() == VNODE_INFO_PATH_SIZE);
+const _: () = assert!(mem::size_of::<ProcFileInfo>() == PROC_FILEINFO_SIZE);
+const _: () = assert!(mem::size_of::<VnodeFdInfoWithPath>() == VNODE_FDINFOWITHPATH_SIZE);
+const _: () = assert!(mem::size_of::<PipeFdInfo>() == PIPE_FDINFO_SIZE);
+const _: () = assert!(mem::size_of::<KqueueFdInfo>() == KQUEUE_FDINFO_SIZE);
+
 // IPPROTO_UDP from <netinet/in.h>
 const IPPROTO_UDP: c_int = 17;
 
@@ -177,6 +199,9 @@ fn path_from_c_buf(buf: &[i8]) -> String {
     unsafe { CStr::from_ptr(ptr).to_string_lossy().into_owned() }
 }
 
+/// `fi_status` bit set when the FD is close-on-exec.
+const PROC_FP_CLEXEC: u32 = 2;
+
 /// Determine the `FdMode` from the kernel `fi_openflags` field.
 fn fd_mode_from_openflags(flags: u32) -> FdMode {
     let r = flags & FREAD != 0;
@@ -204,11 +229,14 @@ fn file_type_from_vtype(vtype: i32) -> FileType {
     }
 }
 
-/// Format a device number as "major,minor".
+/// Format a device number as decimal "major,minor", matching Linux.
+///
+/// Darwin packs `dev_t` as an 8-bit major over a 24-bit minor (the
+/// `major()`/`minor()` macros in `<sys/types.h>`).
 fn format_device(dev: u32) -> String {
     let major = (dev >> 24) & 0xff;
     let minor = dev & 0xffffff;
-    format!("0x{:x},{:x}", major, minor)
+    format!("{},{}", major, minor)
 }
 
 /// Convert a network-byte-order port (stored in an i32) to host-byte-order u16.
@@ -274,15 +302,24 @@ fn open_file_from_vnode(fd_num: i32, pid: i32) -> Option<OpenFileInfo> {
         name: path,
         mode: Some(mode),
         link_target,
-        send_queue: None,
-        recv_queue: None,
+        atime: stat_atime(stat),
+        cloexec: Some(info.pfi.fi_status & PROC_FP_CLEXEC != 0),
+        ..Default::default()
     })
 }
 
+/// The access time recorded in a `vinfo_stat`.
+fn stat_atime(stat: &VInfoStat) -> Option<SystemTime> {
+    let secs = u64::try_from(stat.vst_atime).ok()?;
+    let nanos = u32::try_from(stat.vst_atimensec).ok()?;
+    UNIX_EPOCH.checked_add(Duration::new(secs, nanos))
+}
+
 /// Build an `OpenFileInfo` from a socket FD.
-fn open_file_from_socket(fd_num: i32, pid: i32) -> Option<OpenFileInfo> {
+fn open_file_from_socket(fd_num: i32, pid: i32, config: &ProviderConfig) -> Option<OpenFileInfo> {
     let sock: SocketFDInfo = pidfdinfo(pid, fd_num).ok()?;
     let si = &sock.psi;
+    let cloexec = Some(sock.pfi.fi_status & PROC_FP_CLEXEC != 0);
     let kind: SocketInfoKind = si.soi_kind.into();
 
     match kind {
@@ -292,19 +329,20 @@ fn open_file_from_socket(fd_num: i32, pid: i32) -> Option<OpenFileInfo> {
             let is_v6 = ini.insi_vflag & INI_IPV6 != 0;
             let state = tcp_state_from_si(TcpSIState::from(tcp.tcpsi_state));
 
-            let (local_addr, remote_addr, file_type) = if is_v6 {
+            let (local_addr, remote_ip, file_type) = if is_v6 {
                 (
-                    ipv6_from_insi(&ini.insi_laddr).to_string(),
-                    ipv6_from_insi(&ini.insi_faddr).to_string(),
+                    display_ip(ipv6_from_insi(&ini.insi_laddr).into(), config.raw_addr),
+                    IpAddr::from(ipv6_from_insi(&ini.insi_faddr)),
                     FileType::IPv6,
                 )
             } else {
                 (
                     ipv4_from_insi(&ini.insi_laddr).to_string(),
-                    ipv4_from_insi(&ini.insi_faddr).to_string(),
+                    IpAddr::from(ipv4_from_insi(&ini.insi_faddr)),
                     FileType::IPv4,
                 )
             };
+            let remote_addr = display_ip(remote_ip, config.raw_addr);
 
             let lport = port_from_nbo(ini.insi_lport);
             let fport = port_from_nbo(ini.insi_fport);
@@ -317,14 +355,15 @@ fn open_file_from_socket(fd_num: i32, pid: i32) -> Option<OpenFileInfo> {
             Some(OpenFileInfo {
                 fd: FdType::Numbered(fd_num as u32, FdMode::ReadWrite),
                 file_type,
-                device: String::new(),
-                size_off: None,
                 node: "TCP".to_string(),
                 name,
                 mode: Some(FdMode::ReadWrite),
-                link_target: None,
-                send_queue: None,
-                recv_queue: None,
+                connection: Some(Connection {
+                    remote: SocketAddr::new(remote_ip, fport),
+                    tcp_state: Some(state),
+                }),
+                cloexec,
+                ..Default::default()
             })
         }
         SocketInfoKind::In => {
@@ -332,19 +371,20 @@ fn open_file_from_socket(fd_num: i32, pid: i32) -> Option<OpenFileInfo> {
             let ini = unsafe { si.soi_proto.pri_in };
             let is_v6 = ini.insi_vflag & INI_IPV6 != 0;
 
-            let (local_addr, remote_addr, file_type) = if is_v6 {
+            let (local_addr, remote_ip, file_type) = if is_v6 {
                 (
-                    ipv6_from_insi(&ini.insi_laddr).to_string(),
-                    ipv6_from_insi(&ini.insi_faddr).to_string(),
+                    display_ip(ipv6_from_insi(&ini.insi_laddr).into(), config.raw_addr),
+                    IpAddr::from(ipv6_from_insi(&ini.insi_faddr)),
                     FileType::IPv6,
                 )
             } else {
                 (
                     ipv4_from_insi(&ini.insi_laddr).to_string(),
-                    ipv4_from_insi(&ini.insi_faddr).to_string(),
+                    IpAddr::from(ipv4_from_insi(&ini.insi_faddr)),
                     FileType::IPv4,
                 )
             };
+            let remote_addr = display_ip(remote_ip, config.raw_addr);
 
             let lport = port_from_nbo(ini.insi_lport);
             let fport = port_from_nbo(ini.insi_fport);
@@ -363,14 +403,15 @@ fn open_file_from_socket(fd_num: i32, pid: i32) -> Option<OpenFileInfo> {
             Some(OpenFileInfo {
                 fd: FdType::Numbered(fd_num as u32, FdMode::ReadWrite),
                 file_type,
-                device: String::new(),
-                size_off: None,
                 node: proto_label.to_string(),
                 name,
                 mode: Some(FdMode::ReadWrite),
-                link_target: None,
-                send_queue: None,
-                recv_queue: None,
+                connection: Some(Connection {
+                    remote: SocketAddr::new(remote_ip, fport),
+                    tcp_state: None,
+                }),
+                cloexec,
+                ..Default::default()
             })
         }
         SocketInfoKind::Un => {
@@ -394,14 +435,11 @@ fn open_file_from_socket(fd_num: i32, pid: i32) -> Option<OpenFileInfo> {
             Some(OpenFileInfo {
                 fd: FdType::Numbered(fd_num as u32, FdMode::ReadWrite),
                 file_type: FileType::Unix,
-                device: String::new(),
-                size_off: None,
                 node: "unix".to_string(),
                 name,
                 mode: Some(FdMode::ReadWrite),
-                link_target: None,
-                send_queue: None,
-                recv_queue: None,
+                cloexec,
+                ..Default::default()
             })
         }
         SocketInfoKind::KernCtl => {
@@ -414,14 +452,11 @@ fn open_file_from_socket(fd_num: i32, pid: i32) -> Option<OpenFileInfo> {
             Some(OpenFileInfo {
                 fd: FdType::Numbered(fd_num as u32, FdMode::ReadWrite),
                 file_type: FileType::Systm,
-                device: String::new(),
-                size_off: None,
                 node: "kctl".to_string(),
                 name: ctl_name,
                 mode: Some(FdMode::ReadWrite),
-                link_target: None,
-                send_queue: None,
-                recv_queue: None,
+                cloexec,
+                ..Default::default()
             })
         }
         _ => {
@@ -429,14 +464,18 @@ fn open_file_from_socket(fd_num: i32, pid: i32) -> Option<OpenFileInfo> {
             Some(OpenFileInfo {
                 fd: FdType::Numbered(fd_num as u32, FdMode::ReadWrite),
                 file_type: FileType::Sock,
-                device: String::new(),
-                size_off: None,
                 node: format!("{:?}", kind),
-                name: format!("socket (kind={:?})", kind),
+                name: if config.raw_proto {
+                    format!(
+                        "socket (kind={:?}) family={} proto={}",
+                        kind, si.soi_family, si.soi_protocol
+                    )
+                } else {
+                    format!("socket (kind={:?})", kind)
+                },
                 mode: Some(FdMode::ReadWrite),
-                link_target: None,
-                send_queue: None,
-                recv_queue: None,
+                cloexec,
+                ..Default::default()
             })
         }
     }
@@ -452,7 +491,6 @@ fn open_file_from_pipe(fd_num: i32, pid: i32) -> Option<OpenFileInfo> {
     Some(OpenFileInfo {
         fd: FdType::Numbered(fd_num as u32, mode),
         file_type: FileType::Pipe,
-        device: String::new(),
         size_off: Some(stat.vst_size as u64),
         node: stat.vst_ino.to_string(),
         name: format!(
@@ -460,9 +498,8 @@ fn open_file_from_pipe(fd_num: i32, pid: i32) -> Option<OpenFileInfo> {
             info.pipeinfo.pipe_handle, info.pipeinfo.pipe_peerhandle
         ),
         mode: Some(mode),
-        link_target: None,
-        send_queue: None,
-        recv_queue: None,
+        cloexec: Some(info.pfi.fi_status & PROC_FP_CLEXEC != 0),
+        ..Default::default()
     })
 }
 
@@ -475,23 +512,25 @@ fn open_file_from_kqueue(fd_num: i32, pid: i32) -> Option<OpenFileInfo> {
     Some(OpenFileInfo {
         fd: FdType::Numbered(fd_num as u32, mode),
         file_type: FileType::Kqueue,
-        device: String::new(),
-        size_off: None,
         node: "kqueue".to_string(),
         name: format!(
             "count={}, state=0x{:x}",
             info.kqueueinfo.kq_stat.vst_size, info.kqueueinfo.kq_state
         ),
         mode: Some(mode),
-        link_target: None,
-        send_queue: None,
-        recv_queue: None,
+        cloexec: Some(info.pfi.fi_status & PROC_FP_CLEXEC != 0),
+        ..Default::default()
     })
 }
 
 /// Collect `NetworkInfo` entries from a single socket FD.
 #[allow(dead_code)]
-fn network_info_from_socket(fd_num: i32, pid: i32, command: Option<&str>) -> Option<NetworkInfo> {
+fn network_info_from_socket(
+    fd_num: i32,
+    pid: i32,
+    command: Option<&str>,
+    raw_addr: bool,
+) -> Option<NetworkInfo> {
     let sock: SocketFDInfo = pidfdinfo(pid, fd_num).ok()?;
     let si = &sock.psi;
     let kind: SocketInfoKind = si.soi_kind.into();
@@ -505,8 +544,8 @@ fn network_info_from_socket(fd_num: i32, pid: i32, command: Option<&str>) -> Opt
 
             let (local_addr, remote_addr, protocol) = if is_v6 {
                 (
-                    ipv6_from_insi(&ini.insi_laddr).to_string(),
-                    ipv6_from_insi(&ini.insi_faddr).to_string(),
+                    display_ip(ipv6_from_insi(&ini.insi_laddr).into(), raw_addr),
+                    display_ip(ipv6_from_insi(&ini.insi_faddr).into(), raw_addr),
                     Protocol::Tcp6,
                 )
             } else {
@@ -534,8 +573,8 @@ fn network_info_from_socket(fd_num: i32, pid: i32, command: Option<&str>) -> Opt
 
             let (local_addr, remote_addr, protocol) = if is_v6 {
                 (
-                    ipv6_from_insi(&ini.insi_laddr).to_string(),
-                    ipv6_from_insi(&ini.insi_faddr).to_string(),
+                    display_ip(ipv6_from_insi(&ini.insi_laddr).into(), raw_addr),
+                    display_ip(ipv6_from_insi(&ini.insi_faddr).into(), raw_addr),
                     Protocol::Udp6,
                 )
             } else {
@@ -552,7 +591,7 @@ fn network_info_from_socket(fd_num: i32, pid: i32, command: Option<&str>) -> Opt
                 local_port: port_from_nbo(ini.insi_lport),
                 remote_addr,
                 remote_port: port_from_nbo(ini.insi_fport),
-                state: TcpState::Unknown("NONE".to_string()),
+                state: TcpState::None,
                 pid: Some(pid as u32),
                 command: command.map(|s| s.to_string()),
             })
@@ -575,7 +614,7 @@ fn network_info_from_socket(fd_num: i32, pid: i32, command: Option<&str>) -> Opt
                 local_port: 0,
                 remote_addr: String::new(),
                 remote_port: 0,
-                state: TcpState::Unknown("NONE".to_string()),
+                state: TcpState::None,
                 pid: Some(pid as u32),
                 command: command.map(|s| s.to_string()),
             })
@@ -612,13 +651,12 @@ impl PlatformProvider for MacosProvider {
         sys.refresh_processes();
 
         let mut processes = Vec::new();
+        let mut user_names = system_user_names();
 
         for (pid, proc_info) in sys.processes() {
             let pid_val = pid.as_u32();
             let uid = proc_info.user_id().map(|u| **u).unwrap_or(0);
-            let user = users::get_user_by_uid(uid)
-                .map(|u| u.name().to_string_lossy().to_string())
-                .unwrap_or_else(|| uid.to_string());
+            let user = user_names.name(uid);
 
             let comm = proc_info.name().to_string();
             let cmd_parts: Vec<String> = proc_info.cmd().iter().map(|s| s.to_string()).collect();
@@ -633,12 +671,14 @@ impl PlatformProvider for MacosProvider {
             processes.push(ProcessInfo {
                 pid: pid_val,
                 ppid,
-                pgid: None,
+                state: pidinfo::<BSDInfo>(pid_val as i32, 0)
+                    .ok()
+                    .and_then(|info| ProcState::from_bsd_status(info.pbi_status)),
                 command,
                 comm,
                 user,
                 uid,
-                open_files: Vec::new(),
+                ..Default::default()
             });
         }
 
@@ -655,14 +695,9 @@ impl PlatformProvider for MacosProvider {
             results.push(OpenFileInfo {
                 fd: FdType::Txt,
                 file_type: FileType::Reg,
-                device: String::new(),
-                size_off: None,
-                node: String::new(),
                 name: exe_path,
                 mode: Some(FdMode::Read),
-                link_target: None,
-                send_queue: None,
-                recv_queue: None,
+                ..Default::default()
             });
         }
 
@@ -694,6 +729,7 @@ impl PlatformProvider for MacosProvider {
                                         let dev = meta.dev() as u32;
                                         e.device = format_device(dev);
                                         e.node = meta.ino().to_string();
+                                        e.atime = meta.accessed().ok();
                                         // Keep name as "path -> target" format
                                         e.name = format!("{} -> {}", e.name, target);
                                     }
@@ -703,22 +739,18 @@ impl PlatformProvider for MacosProvider {
                     }
                     entry
                 }
-                ProcFDType::Socket => open_file_from_socket(fd_num, pid_i32),
+                ProcFDType::Socket => open_file_from_socket(fd_num, pid_i32, &self.config),
                 ProcFDType::Pipe => open_file_from_pipe(fd_num, pid_i32),
                 ProcFDType::KQueue => open_file_from_kqueue(fd_num, pid_i32),
                 _ => {
-                    // PSHM, PSEM, FSEvents, ATalk, etc. -- record as unknown
+                    // PSHM, PSEM, FSEvents, ATalk, etc. -- record as unknown.
+                    // No fdinfo is read for these, so cloexec stays unknown.
                     Some(OpenFileInfo {
                         fd: FdType::Numbered(fd_num as u32, FdMode::Unknown),
                         file_type: FileType::Unknown(format!("{:?}", fd_type)),
-                        device: String::new(),
-                        size_off: None,
-                        node: String::new(),
                         name: format!("{:?} fd={}", fd_type, fd_num),
                         mode: Some(FdMode::Unknown),
-                        link_target: None,
-                        send_queue: None,
-                        recv_queue: None,
+                        ..Default::default()
                     })
                 }
             };
@@ -746,7 +778,12 @@ impl PlatformProvider for MacosProvider {
                 for fd in &fds {
                     let fd_type: ProcFDType = fd.proc_fdtype.into();
                     if let ProcFDType::Socket = fd_type {
-                        if let Some(net) = network_info_from_socket(fd.proc_fd, pid_i32, comm_ref) {
+                        if let Some(net) = network_info_from_socket(
+                            fd.proc_fd,
+                            pid_i32,
+                            comm_ref,
+                            self.config.raw_addr,
+                        ) {
                             connections.push(net);
                         }
                     }
@@ -761,9 +798,12 @@ impl PlatformProvider for MacosProvider {
                     for fd in &fds {
                         let fd_type: ProcFDType = fd.proc_fdtype.into();
                         if let ProcFDType::Socket = fd_type {
-                            if let Some(net) =
-                                network_info_from_socket(fd.proc_fd, pid_i32, Some(&p.comm))
-                            {
+                            if let Some(net) = network_info_from_socket(
+                                fd.proc_fd,
+                                pid_i32,
+                                Some(&p.comm),
+                                self.config.raw_addr,
+                            ) {
                                 connections.push(net);
                             }
                         }
@@ -786,4 +826,79 @@ impl PlatformProvider for MacosProvider {
         proc_info.open_files = self.list_open_files(pid)?;
         Ok(proc_info)
     }
+
+    fn fd_count_hint(&self, pid: u32) -> Option<usize> {
+        let fds = get_fd_list(pid as i32);
+        if fds.is_empty() {
+            None
+        } else {
+            Some(fds.len())
+        }
+    }
+
+    fn exe_path(&self, pid: u32) -> Option<String> {
+        pidpath(pid as i32).ok()
+    }
+
+    fn list_mounts(&self) -> Result<Vec<MountInfo>> {
+        use std::os::unix::fs::MetadataExt;
+
+        // sysinfo enumerates mounted volumes; stat each mount point to get
+        // the device number its files will report.
+        let disks = sysinfo::Disks::new_with_refreshed_list();
+        let mut mounts = Vec::new();
+        for disk in disks.list() {
+            let mount_point = disk.mount_point();
+            if let Ok(meta) = std::fs::metadata(mount_point) {
+                mounts.push(MountInfo {
+                    device: format_device(meta.dev() as u32),
+                    mount_point: mount_point.to_string_lossy().into_owned(),
+                    fstype: disk.file_system().to_string_lossy().into_owned(),
+                    options: Vec::new(),
+                });
+            }
+        }
+        Ok(mounts)
+    }
+
+    fn socket_inode_map(&self) -> Result<Vec<(u64, NetworkInfo)>> {
+        // Sockets are resolved per FD through libproc; there is no global
+        // inode table to dump.
+        Err(LoofError::Platform(
+            "socket inode map is only available on Linux".to_string(),
+        ))
+    }
+
+    fn start_time(&self, pid: u32) -> Option<u64> {
+        pidinfo::<BSDInfo>(pid as i32, 0)
+            .ok()
+            .map(|info| info.pbi_start_tvsec * 1_000_000 + info.pbi_start_tvusec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_device_splits_darwin_dev_t() {
+        assert_eq!(format_device(0x0100_0004), "1,4");
+        assert_eq!(format_device(0x1000_0000), "16,0");
+        assert_eq!(format_device(0x00ff_ffff), "0,16777215");
+    }
+
+    #[test]
+    fn test_ffi_struct_sizes_match_kernel() {
+        assert_eq!(mem::size_of::<VInfoStat>(), 136);
+        assert_eq!(mem::size_of::<VnodeInfo>(), 152);
+        assert_eq!(mem::size_of::<VnodeInfoPath>(), 1176);
+        assert_eq!(mem::size_of::<ProcFileInfo>(), 24);
+        assert_eq!(mem::size_of::<VnodeFdInfoWithPath>(), 1200);
+        assert_eq!(mem::size_of::<PipeFdInfo>(), 184);
+        assert_eq!(mem::size_of::<KqueueFdInfo>(), 168);
+        // Field offsets the parser relies on.
+        assert_eq!(mem::offset_of!(VnodeFdInfoWithPath, pvip), 24);
+        assert_eq!(mem::offset_of!(VnodeInfoPath, vip_path), 152);
+        assert_eq!(mem::offset_of!(VInfoStat, vst_size), 88);
+    }
 }
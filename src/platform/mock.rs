@@ -0,0 +1,111 @@
+//! In-memory provider for unit tests.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use super::PlatformProvider;
+use crate::error::{LoofError, Result};
+use crate::model::{FdType, MountInfo, NetworkInfo, OpenFileInfo, ProcessInfo};
+
+/// A provider that serves a fixed set of processes and their open files.
+#[derive(Default)]
+pub struct MockProvider {
+    pub processes: Vec<ProcessInfo>,
+    pub mounts: Vec<MountInfo>,
+    pub network: Vec<NetworkInfo>,
+    /// PIDs whose open files fail to read, as if permission were denied.
+    pub unreadable: Vec<u32>,
+    /// PIDs whose start time changes on every read, as if reused mid-scan.
+    pub reused: Vec<u32>,
+    /// Number of `list_open_files` calls made so far.
+    pub open_file_reads: AtomicUsize,
+    clock: AtomicU64,
+}
+
+impl MockProvider {
+    pub fn new(processes: Vec<ProcessInfo>) -> Self {
+        Self {
+            processes,
+            ..Default::default()
+        }
+    }
+}
+
+impl PlatformProvider for MockProvider {
+    fn list_processes(&self) -> Result<Vec<ProcessInfo>> {
+        Ok(self
+            .processes
+            .iter()
+            .map(|p| ProcessInfo {
+                open_files: Vec::new(),
+                ..p.clone()
+            })
+            .collect())
+    }
+
+    fn list_open_files(&self, pid: u32) -> Result<Vec<OpenFileInfo>> {
+        self.open_file_reads.fetch_add(1, Ordering::Relaxed);
+        if self.unreadable.contains(&pid) {
+            return Err(LoofError::PermissionDenied(format!("PID {}", pid)));
+        }
+        self.processes
+            .iter()
+            .find(|p| p.pid == pid)
+            .map(|p| p.open_files.clone())
+            .ok_or(LoofError::ProcessNotFound(pid))
+    }
+
+    fn list_network_connections(&self, pid: Option<u32>) -> Result<Vec<NetworkInfo>> {
+        Ok(self
+            .network
+            .iter()
+            .filter(|n| pid.is_none() || n.pid == pid)
+            .cloned()
+            .collect())
+    }
+
+    fn get_process_detail(&self, pid: u32) -> Result<ProcessInfo> {
+        self.processes
+            .iter()
+            .find(|p| p.pid == pid)
+            .cloned()
+            .ok_or(LoofError::ProcessNotFound(pid))
+    }
+
+    fn fd_count_hint(&self, pid: u32) -> Option<usize> {
+        let proc = self.processes.iter().find(|p| p.pid == pid)?;
+        Some(
+            proc.open_files
+                .iter()
+                .filter(|f| matches!(f.fd, FdType::Numbered(..)))
+                .count(),
+        )
+    }
+
+    fn exe_path(&self, pid: u32) -> Option<String> {
+        let proc = self.processes.iter().find(|p| p.pid == pid)?;
+        proc.open_files
+            .iter()
+            .find(|f| f.fd == FdType::Txt)
+            .map(|f| f.name.clone())
+    }
+
+    fn list_mounts(&self) -> Result<Vec<MountInfo>> {
+        Ok(self.mounts.clone())
+    }
+
+    fn socket_inode_map(&self) -> Result<Vec<(u64, NetworkInfo)>> {
+        Ok(self
+            .network
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (i as u64 + 1, n.clone()))
+            .collect())
+    }
+
+    fn start_time(&self, pid: u32) -> Option<u64> {
+        if self.reused.contains(&pid) {
+            return Some(self.clock.fetch_add(1, Ordering::Relaxed));
+        }
+        self.processes.iter().any(|p| p.pid == pid).then_some(0)
+    }
+}
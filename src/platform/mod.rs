@@ -1,13 +1,28 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+
 use crate::error::Result;
-use crate::model::{NetworkInfo, OpenFileInfo, ProcessInfo};
+use crate::model::{MountInfo, NetworkInfo, OpenFileInfo, ProcessInfo};
 
 pub trait PlatformProvider: Send + Sync {
     fn list_processes(&self) -> Result<Vec<ProcessInfo>>;
     fn list_open_files(&self, pid: u32) -> Result<Vec<OpenFileInfo>>;
-    #[allow(dead_code)]
     fn list_network_connections(&self, pid: Option<u32>) -> Result<Vec<NetworkInfo>>;
     #[allow(dead_code)]
     fn get_process_detail(&self, pid: u32) -> Result<ProcessInfo>;
+    /// Cheap estimate of the number of open numbered FDs, without
+    /// resolving them.
+    fn fd_count_hint(&self, pid: u32) -> Option<usize>;
+    /// Path of the process executable, if readable.
+    fn exe_path(&self, pid: u32) -> Option<String>;
+    /// Mounted filesystems, used to map file devices to filesystem types.
+    fn list_mounts(&self) -> Result<Vec<MountInfo>>;
+    /// Opaque process start time, used to detect PID reuse. Two reads that
+    /// differ for the same PID mean it now belongs to a different process.
+    fn start_time(&self, pid: u32) -> Option<u64>;
+    /// Raw socket inode table as the OS reports it, for `--dump-sockets`.
+    /// `pid`/`command` are not resolved.
+    fn socket_inode_map(&self) -> Result<Vec<(u64, NetworkInfo)>>;
 }
 
 #[derive(Debug, Clone, Default)]
@@ -15,12 +30,98 @@ pub struct ProviderConfig {
     #[allow(dead_code)]
     pub avoid_stat: bool,
     pub follow_symlinks: bool,
+    /// Show numeric family/protocol for socket kinds without a decoder.
+    pub raw_proto: bool,
+    /// Skip memory-mapped (`mem`) entries; they dominate scan time for
+    /// large programs.
+    pub no_mmap: bool,
+    /// Read each process's namespace-local PID (`--show-nspid`, `--nspid`).
+    pub ns_pids: bool,
+    /// Resolve each process's container ID from its cgroups (`--container`,
+    /// `--wide`, the TUI).
+    pub containers: bool,
+    /// Read each process's security module label (`--context`,
+    /// `--context-filter`).
+    pub security_contexts: bool,
+    /// Read each process's open-file limit from `/proc/[pid]/limits` (the
+    /// TUI's FD usage column).
+    pub fd_limits: bool,
+    /// Show addresses exactly as the kernel reports them, without folding
+    /// IPv4-mapped IPv6 addresses to IPv4 (`--raw-addr`).
+    pub raw_addr: bool,
+    /// Keep command lines exactly as the NUL-separated argv, instead of
+    /// collapsing whitespace (`--argv-raw`).
+    pub argv_raw: bool,
+}
+
+/// Text form of `ip` for display. IPv4-mapped IPv6 addresses
+/// (`::ffff:a.b.c.d`, as dual-stack sockets report IPv4 peers) are shown
+/// as plain IPv4 unless `raw` is set.
+pub fn display_ip(ip: IpAddr, raw: bool) -> String {
+    match ip {
+        IpAddr::V6(v6) if !raw => match v6.to_ipv4_mapped() {
+            Some(v4) => v4.to_string(),
+            None => v6.to_string(),
+        },
+        ip => ip.to_string(),
+    }
+}
+
+/// Per-scan uid -> login name cache. Processes are mostly owned by a
+/// handful of users, and each passwd lookup may go through NSS (or the
+/// network), so each distinct UID is resolved once.
+pub struct UserNames<F> {
+    lookup: F,
+    cache: HashMap<u32, String>,
+}
+
+impl<F: FnMut(u32) -> Option<String>> UserNames<F> {
+    pub fn new(lookup: F) -> Self {
+        Self {
+            lookup,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Start from the names an earlier scan resolved.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub fn with_cache(mut self, cache: HashMap<u32, String>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// The names resolved so far, to seed the next scan.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub fn into_cache(self) -> HashMap<u32, String> {
+        self.cache
+    }
+
+    /// Login name for `uid`, or the numeric UID when it has no passwd entry.
+    pub fn name(&mut self, uid: u32) -> String {
+        let lookup = &mut self.lookup;
+        self.cache
+            .entry(uid)
+            .or_insert_with(|| lookup(uid).unwrap_or_else(|| uid.to_string()))
+            .clone()
+    }
+}
+
+/// A `UserNames` cache backed by the system passwd database.
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos")), allow(dead_code))]
+pub fn system_user_names() -> UserNames<impl FnMut(u32) -> Option<String>> {
+    UserNames::new(|uid| {
+        users::get_user_by_uid(uid).map(|u| u.name().to_string_lossy().to_string())
+    })
 }
 
 #[cfg(target_os = "linux")]
 mod linux;
 #[cfg(target_os = "macos")]
 mod macos;
+#[cfg(test)]
+pub mod mock;
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+mod unsupported;
 
 pub fn create_provider(config: ProviderConfig) -> Box<dyn PlatformProvider> {
     #[cfg(target_os = "linux")]
@@ -31,4 +132,77 @@ pub fn create_provider(config: ProviderConfig) -> Box<dyn PlatformProvider> {
     {
         Box::new(macos::MacosProvider::new(config))
     }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        Box::new(unsupported::UnsupportedProvider::new(config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_provider_on_every_target() {
+        // Compiles on all targets; the fallback provider errors at runtime.
+        let provider = create_provider(ProviderConfig::default());
+        let _ = provider.list_processes();
+    }
+
+    #[test]
+    fn test_display_ip_folds_mapped_ipv6_unless_raw() {
+        let mapped: IpAddr = "::ffff:127.0.0.1".parse().unwrap();
+        assert_eq!(display_ip(mapped, false), "127.0.0.1");
+        assert_eq!(display_ip(mapped, true), "::ffff:127.0.0.1");
+
+        let v6: IpAddr = "fe80::1".parse().unwrap();
+        assert_eq!(display_ip(v6, false), "fe80::1");
+        let v4: IpAddr = "10.0.0.1".parse().unwrap();
+        assert_eq!(display_ip(v4, false), "10.0.0.1");
+    }
+
+    #[test]
+    fn test_user_names_resolves_each_uid_once() {
+        let mut calls = Vec::new();
+        let mut names = UserNames::new(|uid| {
+            calls.push(uid);
+            (uid == 0).then(|| "root".to_string())
+        });
+        let resolved: Vec<String> = [0, 1000, 0, 0, 1000, 7]
+            .iter()
+            .map(|&uid| names.name(uid))
+            .collect();
+        drop(names);
+        assert_eq!(resolved, vec!["root", "1000", "root", "root", "1000", "7"]);
+        assert_eq!(calls, vec![0, 1000, 7]);
+    }
+
+    /// Timing comparison of cached vs uncached lookups. Run with
+    /// `cargo test bench_user_names -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_user_names() {
+        use std::time::Instant;
+        let uids: Vec<u32> = (0..5000).map(|i| [0, 1, 65534][i % 3]).collect();
+
+        let start = Instant::now();
+        for &uid in &uids {
+            let _ = users::get_user_by_uid(uid);
+        }
+        let uncached = start.elapsed();
+
+        let start = Instant::now();
+        let mut names = system_user_names();
+        for &uid in &uids {
+            let _ = names.name(uid);
+        }
+        let cached = start.elapsed();
+
+        println!(
+            "{} lookups: uncached {:?}, cached {:?}",
+            uids.len(),
+            uncached,
+            cached
+        );
+    }
 }
@@ -0,0 +1,58 @@
+//! Fallback provider for targets without a native implementation.
+//!
+//! Lets the crate build everywhere; every query fails with a clear
+//! platform error at runtime.
+
+use super::{PlatformProvider, ProviderConfig};
+use crate::error::{LoofError, Result};
+use crate::model::{MountInfo, NetworkInfo, OpenFileInfo, ProcessInfo};
+
+pub struct UnsupportedProvider;
+
+impl UnsupportedProvider {
+    pub fn new(_config: ProviderConfig) -> Self {
+        UnsupportedProvider
+    }
+}
+
+fn unsupported<T>() -> Result<T> {
+    Err(LoofError::Platform("unsupported platform".to_string()))
+}
+
+impl PlatformProvider for UnsupportedProvider {
+    fn list_processes(&self) -> Result<Vec<ProcessInfo>> {
+        unsupported()
+    }
+
+    fn list_open_files(&self, _pid: u32) -> Result<Vec<OpenFileInfo>> {
+        unsupported()
+    }
+
+    fn list_network_connections(&self, _pid: Option<u32>) -> Result<Vec<NetworkInfo>> {
+        unsupported()
+    }
+
+    fn get_process_detail(&self, _pid: u32) -> Result<ProcessInfo> {
+        unsupported()
+    }
+
+    fn fd_count_hint(&self, _pid: u32) -> Option<usize> {
+        None
+    }
+
+    fn exe_path(&self, _pid: u32) -> Option<String> {
+        None
+    }
+
+    fn list_mounts(&self) -> Result<Vec<MountInfo>> {
+        unsupported()
+    }
+
+    fn start_time(&self, _pid: u32) -> Option<u64> {
+        None
+    }
+
+    fn socket_inode_map(&self) -> Result<Vec<(u64, NetworkInfo)>> {
+        unsupported()
+    }
+}
@@ -0,0 +1,1455 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::io::{self, Write};
+
+use crate::error::Result;
+use crate::filter::FilterConfig;
+use crate::model::{
+    FdMode, FdType, FileType, NetworkInfo, OpenFileInfo, ProcessInfo, Protocol, TcpState,
+};
+use crate::output::{json_opt, json_str, FIELD_TABLE};
+use crate::platform::PlatformProvider;
+
+// ---------------------------------------------------------------------------
+// Port roles (--port)
+// ---------------------------------------------------------------------------
+
+/// How a socket relates to the port being inspected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PortRole {
+    /// Listening socket bound to the port.
+    Listener,
+    /// Connection accepted by the listener (local side is the port).
+    Accepted,
+    /// Outgoing connection whose remote side is the port.
+    Client,
+}
+
+impl PortRole {
+    fn label(&self) -> &'static str {
+        match self {
+            PortRole::Listener => "listener",
+            PortRole::Accepted => "accepted",
+            PortRole::Client => "client",
+        }
+    }
+}
+
+/// Classify a TCP socket against `port`, or `None` if it doesn't involve it.
+pub fn port_role(conn: &NetworkInfo, port: u16) -> Option<PortRole> {
+    if !matches!(conn.protocol, Protocol::Tcp | Protocol::Tcp6) {
+        return None;
+    }
+    if conn.local_port == port {
+        if conn.state == TcpState::Listen {
+            Some(PortRole::Listener)
+        } else {
+            Some(PortRole::Accepted)
+        }
+    } else if conn.remote_port == port && conn.state != TcpState::Listen {
+        Some(PortRole::Client)
+    } else {
+        None
+    }
+}
+
+/// Select the sockets involved with `port`, grouped listener first, then
+/// accepted connections, then clients.
+pub fn classify_port(connections: &[NetworkInfo], port: u16) -> Vec<(PortRole, &NetworkInfo)> {
+    let mut roles: Vec<(PortRole, &NetworkInfo)> = connections
+        .iter()
+        .filter_map(|c| port_role(c, port).map(|r| (r, c)))
+        .collect();
+    roles.sort_by_key(|(role, c)| (*role, c.pid));
+    roles
+}
+
+/// Write the `--port` report.
+pub fn print_port_report(
+    out: &mut dyn Write,
+    connections: &[NetworkInfo],
+    port: u16,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "{:<9} {:<9} {:>5} {:<5} {:<24} {:<24} STATE",
+        "ROLE", "COMMAND", "PID", "PROTO", "LOCAL", "REMOTE"
+    )?;
+    for (role, c) in classify_port(connections, port) {
+        writeln!(
+            out,
+            "{:<9} {:<9} {:>5} {:<5} {:<24} {:<24} {}",
+            role.label(),
+            c.command.as_deref().unwrap_or("-"),
+            c.pid.map(|p| p.to_string()).unwrap_or_else(|| "-".into()),
+            c.protocol.to_string(),
+            format!("{}:{}", c.local_addr, c.local_port),
+            format!("{}:{}", c.remote_addr, c.remote_port),
+            c.state,
+        )?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Network listing (-N / --net)
+// ---------------------------------------------------------------------------
+
+/// A listening socket and the process that owns it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Listener {
+    pub port: u16,
+    pub protocol: Protocol,
+    pub addr: String,
+    pub pid: Option<u32>,
+    pub command: Option<String>,
+}
+
+/// Extract LISTEN-state sockets, sorted by port (then address and PID).
+/// Sockets shared by several FDs of one process appear once.
+pub fn listening_ports(connections: &[NetworkInfo]) -> Vec<Listener> {
+    let mut listeners: Vec<Listener> = connections
+        .iter()
+        .filter(|c| c.state == TcpState::Listen)
+        .map(|c| Listener {
+            port: c.local_port,
+            protocol: c.protocol.clone(),
+            addr: c.local_addr.clone(),
+            pid: c.pid,
+            command: c.command.clone(),
+        })
+        .collect();
+    listeners.sort_by(|a, b| {
+        (a.port, &a.addr, a.pid, a.protocol.to_string()).cmp(&(
+            b.port,
+            &b.addr,
+            b.pid,
+            b.protocol.to_string(),
+        ))
+    });
+    listeners.dedup();
+    listeners
+}
+
+/// Number of TCP sockets in one state and their share of all TCP sockets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateShare {
+    pub state: TcpState,
+    pub count: usize,
+    pub percent: f64,
+}
+
+/// Group TCP sockets (v4 and v6) by state, most common first (ties in
+/// first-seen order).
+pub fn tcp_state_summary(connections: &[NetworkInfo]) -> Vec<StateShare> {
+    let tcp: Vec<&NetworkInfo> = connections
+        .iter()
+        .filter(|c| matches!(c.protocol, Protocol::Tcp | Protocol::Tcp6))
+        .collect();
+    let mut shares: Vec<StateShare> = Vec::new();
+    for c in &tcp {
+        match shares.iter_mut().find(|s| s.state == c.state) {
+            Some(share) => share.count += 1,
+            None => shares.push(StateShare {
+                state: c.state.clone(),
+                count: 1,
+                percent: 0.0,
+            }),
+        }
+    }
+    for share in &mut shares {
+        share.percent = share.count as f64 * 100.0 / tcp.len() as f64;
+    }
+    shares.sort_by_key(|s| Reverse(s.count));
+    shares
+}
+
+/// A warning when TIME_WAIT sockets exceed `threshold_pct` percent of all
+/// TCP sockets, which usually means connections are churned instead of
+/// reused.
+pub fn time_wait_alert(summary: &[StateShare], threshold_pct: f64) -> Option<String> {
+    let share = summary.iter().find(|s| s.state == TcpState::TimeWait)?;
+    (share.percent > threshold_pct).then(|| {
+        format!(
+            "TIME_WAIT is {:.1}% of TCP sockets (over {}%): connections may not be reused",
+            share.percent, threshold_pct
+        )
+    })
+}
+
+/// Write every connection, then the TCP state and listening-ports
+/// summaries. `time_wait_pct` is the `--warn-time-wait` threshold.
+pub fn print_net_report(
+    out: &mut dyn Write,
+    connections: &[NetworkInfo],
+    time_wait_pct: f64,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "{:<9} {:>5} {:<5} {:<24} {:<24} STATE",
+        "COMMAND", "PID", "PROTO", "LOCAL", "REMOTE"
+    )?;
+    for c in connections {
+        writeln!(
+            out,
+            "{:<9} {:>5} {:<5} {:<24} {:<24} {}",
+            c.command.as_deref().unwrap_or("-"),
+            c.pid.map(|p| p.to_string()).unwrap_or_else(|| "-".into()),
+            c.protocol.to_string(),
+            format!("{}:{}", c.local_addr, c.local_port),
+            format!("{}:{}", c.remote_addr, c.remote_port),
+            c.state,
+        )?;
+    }
+
+    let summary = tcp_state_summary(connections);
+    writeln!(out)?;
+    writeln!(out, "TCP states:")?;
+    for share in &summary {
+        writeln!(
+            out,
+            "{:<12} {:>6} {:>5.1}%",
+            share.state.to_string(),
+            share.count,
+            share.percent
+        )?;
+    }
+    if let Some(alert) = time_wait_alert(&summary, time_wait_pct) {
+        writeln!(out, "!! {}", alert)?;
+    }
+
+    let listeners = listening_ports(connections);
+    writeln!(out)?;
+    writeln!(out, "Listening ports: {}", listeners.len())?;
+    writeln!(
+        out,
+        "{:>5} {:<5} {:<24} {:>5} COMMAND",
+        "PORT", "PROTO", "ADDRESS", "PID"
+    )?;
+    for l in &listeners {
+        writeln!(
+            out,
+            "{:>5} {:<5} {:<24} {:>5} {}",
+            l.port,
+            l.protocol.to_string(),
+            l.addr,
+            l.pid.map(|p| p.to_string()).unwrap_or_else(|| "-".into()),
+            l.command.as_deref().unwrap_or("-"),
+        )?;
+    }
+    Ok(())
+}
+
+/// A connection as a JSON object.
+fn connection_json(c: &NetworkInfo) -> String {
+    format!(
+        "{{\"command\":{},\"pid\":{},\"protocol\":{},\"local_addr\":{},\"local_port\":{},\"remote_addr\":{},\"remote_port\":{},\"state\":{}}}",
+        c.command.as_deref().map(json_str).unwrap_or_else(|| "null".into()),
+        json_opt(c.pid),
+        json_str(&c.protocol.to_string()),
+        json_str(&c.local_addr),
+        c.local_port,
+        json_str(&c.remote_addr),
+        c.remote_port,
+        json_str(&c.state.to_string()),
+    )
+}
+
+/// The `-N --json` document: `{"connections":[...],"listening":[...]}`.
+pub fn net_report_json(connections: &[NetworkInfo]) -> String {
+    let conns: Vec<String> = connections.iter().map(connection_json).collect();
+    let listening: Vec<String> = listening_ports(connections)
+        .iter()
+        .map(|l| {
+            format!(
+                "{{\"port\":{},\"protocol\":{},\"addr\":{},\"pid\":{},\"command\":{}}}",
+                l.port,
+                json_str(&l.protocol.to_string()),
+                json_str(&l.addr),
+                json_opt(l.pid),
+                l.command
+                    .as_deref()
+                    .map(json_str)
+                    .unwrap_or_else(|| "null".into()),
+            )
+        })
+        .collect();
+    format!(
+        "{{\"connections\":[{}],\"listening\":[{}]}}",
+        conns.join(","),
+        listening.join(",")
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Top FD consumers (--top)
+// ---------------------------------------------------------------------------
+
+/// The `n` processes with the most open FDs, most first (ties by PID).
+///
+/// Counts come from the provider's cheap FD count hint, so no FD is
+/// resolved; processes whose count can't be read are skipped. A bounded
+/// min-heap keeps only the current top `n` while scanning.
+pub fn top_fd_consumers(
+    provider: &dyn PlatformProvider,
+    processes: Vec<ProcessInfo>,
+    n: usize,
+) -> Vec<(usize, ProcessInfo)> {
+    if n == 0 {
+        return Vec::new();
+    }
+    // Min-heap on (count, Reverse(pid)): the root is the entry to evict.
+    let mut heap: BinaryHeap<Reverse<(usize, Reverse<u32>, usize)>> = BinaryHeap::new();
+    for (idx, proc) in processes.iter().enumerate() {
+        let count = match provider.fd_count_hint(proc.pid) {
+            Some(c) => c,
+            None => continue,
+        };
+        let entry = Reverse((count, Reverse(proc.pid), idx));
+        if heap.len() < n {
+            heap.push(entry);
+        } else if heap.peek().is_some_and(|min| entry < *min) {
+            heap.pop();
+            heap.push(entry);
+        }
+    }
+
+    let mut top: Vec<(usize, usize)> = heap
+        .into_iter()
+        .map(|Reverse((count, _, idx))| (count, idx))
+        .collect();
+    top.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then(processes[a.1].pid.cmp(&processes[b.1].pid))
+    });
+    let mut slots: Vec<Option<ProcessInfo>> = processes.into_iter().map(Some).collect();
+    top.into_iter()
+        .filter_map(|(count, idx)| slots[idx].take().map(|p| (count, p)))
+        .collect()
+}
+
+/// FD counts for `--count-only`, in process order. Like
+/// [`top_fd_consumers`] this uses only the cheap FD count hint and skips
+/// processes whose count can't be read.
+pub fn fd_counts(
+    provider: &dyn PlatformProvider,
+    processes: Vec<ProcessInfo>,
+) -> Vec<(usize, ProcessInfo)> {
+    processes
+        .into_iter()
+        .filter_map(|p| provider.fd_count_hint(p.pid).map(|count| (count, p)))
+        .collect()
+}
+
+/// Processes with no open numbered FDs, for `--zero-fds`, in process order.
+/// Uses the FD count hint like [`fd_counts`]; processes whose count can't
+/// be read are skipped rather than reported as empty.
+pub fn zero_fd_processes(
+    provider: &dyn PlatformProvider,
+    processes: Vec<ProcessInfo>,
+) -> Vec<ProcessInfo> {
+    processes
+        .into_iter()
+        .filter(|p| provider.fd_count_hint(p.pid) == Some(0))
+        .collect()
+}
+
+/// Write the `--zero-fds` table: `PID PPID STATE USER COMMAND`.
+pub fn print_zero_fd_report(out: &mut dyn Write, processes: &[ProcessInfo]) -> io::Result<()> {
+    writeln!(
+        out,
+        "{:>7} {:>7} {:<5} {:<12} COMMAND",
+        "PID", "PPID", "STATE", "USER"
+    )?;
+    for p in processes {
+        writeln!(
+            out,
+            "{:>7} {:>7} {:<5} {:<12} {}",
+            p.pid,
+            p.ppid.map(|pp| pp.to_string()).unwrap_or_default(),
+            p.state.map(|s| s.to_string()).unwrap_or_default(),
+            p.user,
+            p.comm
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the `--count-only` table: `PID COMMAND USER COUNT`.
+pub fn print_count_report(out: &mut dyn Write, counts: &[(usize, ProcessInfo)]) -> io::Result<()> {
+    writeln!(
+        out,
+        "{:>7} {:<16} {:<12} {:>6}",
+        "PID", "COMMAND", "USER", "COUNT"
+    )?;
+    for (count, p) in counts {
+        writeln!(
+            out,
+            "{:>7} {:<16} {:<12} {:>6}",
+            p.pid, p.comm, p.user, count
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the `--top` table.
+pub fn print_top_report(out: &mut dyn Write, top: &[(usize, ProcessInfo)]) -> io::Result<()> {
+    writeln!(out, "{:>6} {:>7} {:<12} COMMAND", "FDS", "PID", "USER")?;
+    for (count, p) in top {
+        writeln!(out, "{:>6} {:>7} {:<12} {}", count, p.pid, p.user, p.comm)?;
+    }
+    Ok(())
+}
+
+/// The `--top --json` and `--count-only --json` document: an array of
+/// `{pid, fds, ...}` objects in the given order.
+pub fn top_report_json(top: &[(usize, ProcessInfo)]) -> String {
+    let items: Vec<String> = top
+        .iter()
+        .map(|(count, p)| {
+            format!(
+                "{{\"pid\":{},\"fds\":{},\"user\":{},\"comm\":{},\"command\":{}}}",
+                p.pid,
+                count,
+                json_str(&p.user),
+                json_str(&p.comm),
+                json_str(&p.command),
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+// ---------------------------------------------------------------------------
+// Socket inode dump (--dump-sockets)
+// ---------------------------------------------------------------------------
+
+/// Write the provider's socket inode table, one socket per line.
+pub fn print_socket_dump(out: &mut dyn Write, entries: &[(u64, NetworkInfo)]) -> io::Result<()> {
+    writeln!(
+        out,
+        "{:>10} {:<5} {:<24} {:<24} STATE",
+        "INODE", "PROTO", "LOCAL", "REMOTE"
+    )?;
+    for line in socket_dump_lines(entries) {
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+fn socket_dump_lines(entries: &[(u64, NetworkInfo)]) -> Vec<String> {
+    entries
+        .iter()
+        .map(|(inode, s)| {
+            format!(
+                "{:>10} {:<5} {:<24} {:<24} {}",
+                inode,
+                s.protocol.to_string(),
+                format!("{}:{}", s.local_addr, s.local_port),
+                format!("{}:{}", s.remote_addr, s.remote_port),
+                s.state,
+            )
+        })
+        .collect()
+}
+
+/// The `--dump-sockets --json` document: an array of sockets with inodes.
+pub fn socket_dump_json(entries: &[(u64, NetworkInfo)]) -> String {
+    let items: Vec<String> = entries
+        .iter()
+        .map(|(inode, s)| format!("{{\"inode\":{},\"socket\":{}}}", inode, connection_json(s)))
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+// ---------------------------------------------------------------------------
+// Scan cost estimate (--dry-run)
+// ---------------------------------------------------------------------------
+
+/// What a scan with the current filters would cover.
+#[derive(Debug, PartialEq)]
+pub struct DryRunEstimate {
+    /// Processes passing the process-level filters.
+    pub processes: usize,
+    /// Sum of their FD count hints; mem/cwd/txt entries come on top.
+    pub files: usize,
+    /// Matching processes whose FD count couldn't be read.
+    pub uncounted: usize,
+}
+
+/// Estimate a scan's size with `matches_process` and the FD count hint
+/// only; no open file is read.
+pub fn dry_run_estimate(
+    provider: &dyn PlatformProvider,
+    filter: &FilterConfig,
+) -> Result<DryRunEstimate> {
+    let mut estimate = DryRunEstimate {
+        processes: 0,
+        files: 0,
+        uncounted: 0,
+    };
+    for proc in provider.list_processes()? {
+        if !filter.matches_process(&proc) {
+            continue;
+        }
+        estimate.processes += 1;
+        match provider.fd_count_hint(proc.pid) {
+            Some(count) => estimate.files += count,
+            None => estimate.uncounted += 1,
+        }
+    }
+    Ok(estimate)
+}
+
+/// Write the `--dry-run` summary.
+pub fn print_dry_run(out: &mut dyn Write, estimate: &DryRunEstimate) -> io::Result<()> {
+    writeln!(out, "{} matching processes", estimate.processes)?;
+    writeln!(out, "~{} open files to read", estimate.files)?;
+    if estimate.uncounted > 0 {
+        writeln!(
+            out,
+            "{} processes with unreadable FD counts (not included)",
+            estimate.uncounted
+        )?;
+    }
+    Ok(())
+}
+
+/// The `--dry-run --json` document.
+pub fn dry_run_json(estimate: &DryRunEstimate) -> String {
+    format!(
+        "{{\"processes\":{},\"estimated_files\":{},\"uncounted\":{}}}",
+        estimate.processes, estimate.files, estimate.uncounted
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Connection leak finder (--min-connections)
+// ---------------------------------------------------------------------------
+
+/// Processes holding at least `min` network (IPv4/IPv6) sockets, most
+/// first (ties by PID). With `state`, only TCP sockets in that state are
+/// counted.
+pub fn connection_counts(
+    processes: Vec<ProcessInfo>,
+    min: usize,
+    state: Option<&TcpState>,
+) -> Vec<(usize, ProcessInfo)> {
+    let mut counts: Vec<(usize, ProcessInfo)> = processes
+        .into_iter()
+        .map(|p| {
+            let count = p
+                .open_files
+                .iter()
+                .filter(|f| matches!(f.file_type, FileType::IPv4 | FileType::IPv6))
+                .filter(|f| state.is_none_or(|s| f.tcp_state() == Some(s)))
+                .count();
+            (count, p)
+        })
+        .filter(|(count, _)| *count >= min)
+        .collect();
+    counts.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.pid.cmp(&b.1.pid)));
+    counts
+}
+
+/// Write the `--min-connections` table.
+pub fn print_connection_report(
+    out: &mut dyn Write,
+    counts: &[(usize, ProcessInfo)],
+) -> io::Result<()> {
+    writeln!(out, "{:>6} {:>7} {:<12} COMMAND", "CONNS", "PID", "USER")?;
+    for (count, p) in counts {
+        writeln!(out, "{:>6} {:>7} {:<12} {}", count, p.pid, p.user, p.comm)?;
+    }
+    Ok(())
+}
+
+/// The `--min-connections --json` document: an array of
+/// `{pid, connections, ...}` objects, most connections first.
+pub fn connection_report_json(counts: &[(usize, ProcessInfo)]) -> String {
+    let items: Vec<String> = counts
+        .iter()
+        .map(|(count, p)| {
+            format!(
+                "{{\"pid\":{},\"connections\":{},\"user\":{},\"comm\":{},\"command\":{}}}",
+                p.pid,
+                count,
+                json_str(&p.user),
+                json_str(&p.comm),
+                json_str(&p.command),
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+// ---------------------------------------------------------------------------
+// FD leak heuristic (--leak-scan)
+// ---------------------------------------------------------------------------
+
+/// Why an FD looks leaked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LeakKind {
+    /// TCP socket the peer has closed but this process never did.
+    CloseWait,
+    /// File unlinked from the filesystem but still held open.
+    Deleted,
+    /// Pipe whose other end no sampled process holds.
+    OrphanPipe,
+}
+
+impl LeakKind {
+    fn label(self) -> &'static str {
+        match self {
+            LeakKind::CloseWait => "CLOSE_WAIT",
+            LeakKind::Deleted => "DELETED",
+            LeakKind::OrphanPipe => "NO_PEER",
+        }
+    }
+}
+
+/// An FD present in both samples whose state suggests a leak.
+#[derive(Debug, Clone)]
+pub struct LeakSuspect {
+    pub pid: u32,
+    pub comm: String,
+    pub fd: String,
+    pub kind: LeakKind,
+    pub name: String,
+}
+
+/// Compare two samples of the same processes and return the suspicious
+/// FDs that survived from `before` into `after`, ranked by how many each
+/// process has (most first), then by PID and FD.
+///
+/// Pipe peers are looked for in `everyone`, an unfiltered scan taken
+/// alongside `after`, so a pipe to a process the filters hide still has
+/// its peer.
+pub fn classify_leaks(
+    before: &[ProcessInfo],
+    after: &[ProcessInfo],
+    everyone: &[ProcessInfo],
+) -> Vec<LeakSuspect> {
+    let mut pipe_ends: HashMap<&str, usize> = HashMap::new();
+    for f in everyone.iter().flat_map(|p| &p.open_files) {
+        if f.file_type == FileType::Pipe {
+            *pipe_ends.entry(f.node.as_str()).or_default() += 1;
+        }
+    }
+
+    let mut suspects = Vec::new();
+    for proc in after {
+        let Some(earlier) = before.iter().find(|p| p.pid == proc.pid) else {
+            continue;
+        };
+        for f in &proc.open_files {
+            let persisted = earlier
+                .open_files
+                .iter()
+                .any(|e| e.fd == f.fd && e.node == f.node && e.name == f.name);
+            if !persisted {
+                continue;
+            }
+            if let Some(kind) = leak_kind(f, &pipe_ends) {
+                suspects.push(LeakSuspect {
+                    pid: proc.pid,
+                    comm: proc.comm.clone(),
+                    fd: f.fd.to_string(),
+                    kind,
+                    name: f.name.clone(),
+                });
+            }
+        }
+    }
+
+    let mut per_pid: HashMap<u32, usize> = HashMap::new();
+    for s in &suspects {
+        *per_pid.entry(s.pid).or_default() += 1;
+    }
+    // Stable sort keeps each process's FDs in their listed order.
+    suspects.sort_by_key(|s| (Reverse(per_pid[&s.pid]), s.pid));
+    suspects
+}
+
+fn leak_kind(file: &OpenFileInfo, pipe_ends: &HashMap<&str, usize>) -> Option<LeakKind> {
+    if !matches!(file.fd, FdType::Numbered(..)) {
+        return None;
+    }
+    match file.file_type {
+        _ if file.tcp_state() == Some(&TcpState::CloseWait) => Some(LeakKind::CloseWait),
+        FileType::Pipe if pipe_ends.get(file.node.as_str()) == Some(&1) => {
+            Some(LeakKind::OrphanPipe)
+        }
+        _ if file.name.ends_with(" (deleted)") => Some(LeakKind::Deleted),
+        _ => None,
+    }
+}
+
+/// Write the `--leak-scan` report.
+pub fn print_leak_report(out: &mut dyn Write, suspects: &[LeakSuspect]) -> io::Result<()> {
+    if suspects.is_empty() {
+        writeln!(out, "No likely FD leaks found.")?;
+        return Ok(());
+    }
+    writeln!(
+        out,
+        "{:>7} {:<12} {:>5} {:<10} NAME",
+        "PID", "COMMAND", "FD", "KIND"
+    )?;
+    for s in suspects {
+        writeln!(
+            out,
+            "{:>7} {:<12} {:>5} {:<10} {}",
+            s.pid,
+            s.comm,
+            s.fd,
+            s.kind.label(),
+            s.name
+        )?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Provider self-check (--self-check)
+// ---------------------------------------------------------------------------
+
+/// What a correct provider must report for a file we opened ourselves.
+pub struct SelfCheckProbe {
+    pub fd: u32,
+    pub path: String,
+    pub size: u64,
+    pub cwd: String,
+}
+
+/// Read our own open files through the provider and compare them with
+/// what we know to be true. Catches a provider whose parsing has drifted
+/// from the OS (e.g. FFI layouts after an OS update) instead of silently
+/// printing wrong data. Returns `(passed, description)` per check.
+pub fn self_check(provider: &dyn PlatformProvider) -> Result<Vec<(bool, String)>> {
+    let exe = std::fs::canonicalize(std::env::current_exe()?)?;
+    let file = std::fs::File::open(&exe)?;
+    let probe = SelfCheckProbe {
+        fd: std::os::fd::AsRawFd::as_raw_fd(&file) as u32,
+        path: exe.to_string_lossy().into_owned(),
+        size: file.metadata()?.len(),
+        cwd: std::fs::canonicalize(std::env::current_dir()?)?
+            .to_string_lossy()
+            .into_owned(),
+    };
+    let files = provider.list_open_files(std::process::id())?;
+    Ok(self_check_results(&files, &probe))
+}
+
+/// The individual `--self-check` comparisons for `files` against `probe`.
+pub fn self_check_results(files: &[OpenFileInfo], probe: &SelfCheckProbe) -> Vec<(bool, String)> {
+    let mut results = Vec::new();
+    match files
+        .iter()
+        .find(|f| matches!(f.fd, FdType::Numbered(n, _) if n == probe.fd))
+    {
+        Some(f) => {
+            results.push((
+                f.file_type == FileType::Reg,
+                format!("fd {} type is REG (got {})", probe.fd, f.file_type),
+            ));
+            results.push((
+                f.name == probe.path,
+                format!("fd {} path is {} (got {})", probe.fd, probe.path, f.name),
+            ));
+            if let Some(size) = f.size_off {
+                results.push((
+                    size == probe.size,
+                    format!("fd {} size is {} (got {})", probe.fd, probe.size, size),
+                ));
+            }
+        }
+        None => results.push((false, format!("fd {} is listed", probe.fd))),
+    }
+    match files.iter().find(|f| f.fd == FdType::Cwd) {
+        Some(f) => results.push((
+            f.file_type == FileType::Dir && f.name == probe.cwd,
+            format!("cwd is DIR {} (got {} {})", probe.cwd, f.file_type, f.name),
+        )),
+        None => results.push((false, "cwd is listed".to_string())),
+    }
+    results
+}
+
+/// Write `--self-check` results; returns `true` when every check passed.
+pub fn print_self_check(out: &mut dyn Write, results: &[(bool, String)]) -> io::Result<bool> {
+    for (ok, description) in results {
+        writeln!(out, "{} {}", if *ok { "ok  " } else { "FAIL" }, description)?;
+    }
+    Ok(results.iter().all(|(ok, _)| *ok))
+}
+
+// ---------------------------------------------------------------------------
+// File watch (--watch-file)
+// ---------------------------------------------------------------------------
+
+/// A change in who holds the watched file between two snapshots.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchEvent {
+    /// A process that didn't hold the file now has it open.
+    Entering {
+        pid: u32,
+        comm: String,
+        user: String,
+    },
+    /// The last holder closed the file.
+    Released,
+}
+
+/// Compare two scans narrowed to the watched file. Every process in a
+/// snapshot is taken to hold it.
+pub fn watch_events(before: &[ProcessInfo], after: &[ProcessInfo]) -> Vec<WatchEvent> {
+    let held: HashSet<u32> = before.iter().map(|p| p.pid).collect();
+    let mut events: Vec<WatchEvent> = after
+        .iter()
+        .filter(|p| !held.contains(&p.pid))
+        .map(|p| WatchEvent::Entering {
+            pid: p.pid,
+            comm: p.comm.clone(),
+            user: p.user.clone(),
+        })
+        .collect();
+    if !before.is_empty() && after.is_empty() {
+        events.push(WatchEvent::Released);
+    }
+    events
+}
+
+/// The alert line printed for a `--watch-file` event.
+pub fn watch_event_line(path: &str, event: &WatchEvent) -> String {
+    match event {
+        WatchEvent::Entering { pid, comm, user } => {
+            format!("entering {} {} ({}, {})", path, pid, comm, user)
+        }
+        WatchEvent::Released => format!("released {}", path),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Type reference (--list-types, --list-fd-types, --fields-help)
+// ---------------------------------------------------------------------------
+
+/// Lines for `--list-types`: every `TYPE` column value and what it means.
+pub fn type_list_lines() -> Vec<String> {
+    let mut lines: Vec<String> = FileType::VARIANTS
+        .iter()
+        .map(|t| format!("{:<8} {}", t.to_string(), t.description()))
+        .collect();
+    lines.push(format!(
+        "{:<8} {}",
+        "*",
+        FileType::Unknown(String::new()).description()
+    ));
+    lines
+}
+
+/// Lines for `--list-fd-types`: every `FD` column form and what it means.
+pub fn fd_type_list_lines() -> Vec<String> {
+    let mut lines: Vec<String> = FdType::NAMED
+        .iter()
+        .map(|fd| format!("{:<8} {}", fd.to_string(), fd.description()))
+        .collect();
+    lines.push(format!(
+        "{:<8} {}",
+        "N[rwu]",
+        FdType::Numbered(0, FdMode::Unknown).description()
+    ));
+    lines
+}
+
+/// Lines for `--fields-help`: every `-F` field character and what it emits.
+pub fn field_list_lines() -> Vec<String> {
+    FIELD_TABLE
+        .iter()
+        .map(|spec| format!("{:<8} {}", spec.ch, spec.description))
+        .collect()
+}
+
+/// Write one of the reference lists above, a line each.
+pub fn print_list(out: &mut dyn Write, lines: &[String]) -> io::Result<()> {
+    for line in lines {
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Capabilities (--capabilities)
+// ---------------------------------------------------------------------------
+
+/// Name of the platform this binary was built for.
+const PLATFORM: &str = if cfg!(target_os = "linux") {
+    "linux"
+} else if cfg!(target_os = "macos") {
+    "macos"
+} else {
+    "unsupported"
+};
+
+/// Socket protocols the provider decodes on this platform.
+fn socket_protocols() -> &'static [Protocol] {
+    if cfg!(any(target_os = "linux", target_os = "macos")) {
+        &[
+            Protocol::Tcp,
+            Protocol::Tcp6,
+            Protocol::Udp,
+            Protocol::Udp6,
+            Protocol::Unix,
+        ]
+    } else {
+        &[]
+    }
+}
+
+/// `--capabilities`: a JSON description of what this build supports, for
+/// wrappers that need to adapt. Decided at compile time from `cfg` and
+/// cargo features.
+pub fn capabilities_json() -> String {
+    let linux = cfg!(target_os = "linux");
+    let features = [
+        ("sockets", !socket_protocols().is_empty()),
+        ("mmap", linux),
+        ("threads", false),
+        ("namespaces", linux),
+        ("containers", linux),
+        ("security_context", linux),
+        ("locks", linux),
+        ("tui", true),
+        ("serve", cfg!(unix)),
+        ("trace", cfg!(feature = "trace")),
+    ];
+    let protocols: Vec<String> = socket_protocols()
+        .iter()
+        .map(|p| json_str(&p.to_string()))
+        .collect();
+    let features: Vec<String> = features
+        .iter()
+        .map(|(name, on)| format!("{}:{}", json_str(name), on))
+        .collect();
+    let formats: Vec<String> = ["columns", "terse", "fields", "json", "template", "split"]
+        .iter()
+        .map(|f| json_str(f))
+        .collect();
+    format!(
+        "{{\"name\":\"loof\",\"version\":{},\"platform\":{},\"socket_protocols\":[{}],\"features\":{{{}}},\"output_formats\":[{}]}}",
+        json_str(env!("CARGO_PKG_VERSION")),
+        json_str(PLATFORM),
+        protocols.join(","),
+        features.join(","),
+        formats.join(","),
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Unit tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures;
+
+    fn make_conn(pid: u32, local_port: u16, remote_port: u16, state: TcpState) -> NetworkInfo {
+        NetworkInfo {
+            protocol: Protocol::Tcp,
+            local_addr: "127.0.0.1".into(),
+            local_port,
+            remote_addr: "127.0.0.1".into(),
+            remote_port,
+            state,
+            pid: Some(pid),
+            command: Some(format!("proc{}", pid)),
+        }
+    }
+
+    #[test]
+    fn test_classify_listener_and_clients() {
+        let conns = vec![
+            make_conn(30, 51000, 5432, TcpState::Established),
+            make_conn(10, 5432, 0, TcpState::Listen),
+            make_conn(10, 5432, 51000, TcpState::Established),
+            make_conn(20, 51001, 5432, TcpState::Established),
+            make_conn(40, 8080, 0, TcpState::Listen),
+        ];
+        let roles: Vec<(PortRole, u32)> = classify_port(&conns, 5432)
+            .into_iter()
+            .map(|(r, c)| (r, c.pid.unwrap()))
+            .collect();
+        assert_eq!(
+            roles,
+            vec![
+                (PortRole::Listener, 10),
+                (PortRole::Accepted, 10),
+                (PortRole::Client, 20),
+                (PortRole::Client, 30),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_listening_ports_sorted_and_filtered() {
+        let mut udp = make_conn(50, 53, 0, TcpState::Closed);
+        udp.protocol = Protocol::Udp;
+        let conns = vec![
+            make_conn(40, 8080, 0, TcpState::Listen),
+            make_conn(10, 5432, 51000, TcpState::Established),
+            make_conn(10, 5432, 0, TcpState::Listen),
+            make_conn(10, 5432, 0, TcpState::Listen),
+            make_conn(20, 22, 0, TcpState::Listen),
+            make_conn(30, 51000, 5432, TcpState::TimeWait),
+            udp,
+        ];
+        let summary: Vec<(u16, Option<u32>)> = listening_ports(&conns)
+            .iter()
+            .map(|l| (l.port, l.pid))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(22, Some(20)), (5432, Some(10)), (8080, Some(40))]
+        );
+
+        let json = net_report_json(&conns);
+        assert!(json.contains("\"listening\":[{\"port\":22,"));
+    }
+
+    #[test]
+    fn test_socket_dump_formats() {
+        let entries = vec![(4242, make_conn(1, 8080, 0, TcpState::Listen))];
+        let lines = socket_dump_lines(&entries);
+        assert!(lines[0]
+            .trim_start()
+            .starts_with("4242 TCP   127.0.0.1:8080"));
+        assert!(lines[0].ends_with("LISTEN"));
+        assert!(socket_dump_json(&entries).starts_with("[{\"inode\":4242,\"socket\":{"));
+    }
+
+    #[test]
+    fn test_top_fd_consumers_picks_largest() {
+        use crate::model::{FdMode, FdType, FileType, OpenFileInfo};
+        use crate::platform::mock::MockProvider;
+
+        let make = |pid: u32, fds: u32| ProcessInfo {
+            pid,
+            command: format!("cmd{}", pid),
+            comm: format!("cmd{}", pid),
+            user: "root".into(),
+            open_files: (0..fds)
+                .map(|fd| OpenFileInfo {
+                    fd: FdType::Numbered(fd, FdMode::Read),
+                    file_type: FileType::Reg,
+                    name: format!("/tmp/{}", fd),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let provider = MockProvider::new(vec![
+            make(1, 3),
+            make(2, 40),
+            make(3, 7),
+            make(4, 40),
+            make(5, 12),
+        ]);
+        let procs = provider.list_processes().unwrap();
+        let top: Vec<(usize, u32)> = top_fd_consumers(&provider, procs, 3)
+            .into_iter()
+            .map(|(c, p)| (c, p.pid))
+            .collect();
+        assert_eq!(top, vec![(40, 2), (40, 4), (12, 5)]);
+
+        let procs = provider.list_processes().unwrap();
+        let json = top_report_json(&top_fd_consumers(&provider, procs, 2));
+        assert!(json.starts_with("[{\"pid\":2,\"fds\":40,"));
+    }
+
+    #[test]
+    fn test_count_only_does_not_read_open_files() {
+        use crate::model::{FdMode, FdType, FileType};
+        use crate::platform::mock::MockProvider;
+        use std::sync::atomic::Ordering;
+
+        let file = |fd| fixtures::file(FdType::Numbered(fd, FdMode::Read), FileType::Reg, "/tmp/f");
+        let provider = MockProvider::new(vec![
+            fixtures::process(3, "proc3", vec![file(0), file(1)]),
+            fixtures::process(1, "proc1", vec![file(0)]),
+        ]);
+        let procs = provider.list_processes().unwrap();
+        let counts: Vec<(usize, u32)> = fd_counts(&provider, procs)
+            .into_iter()
+            .map(|(c, p)| (c, p.pid))
+            .collect();
+        assert_eq!(counts, vec![(2, 3), (1, 1)]);
+        assert_eq!(provider.open_file_reads.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_zero_fds_selects_processes_without_numbered_fds() {
+        use crate::model::{FdType, FileType};
+        use crate::platform::mock::MockProvider;
+        use std::sync::atomic::Ordering;
+
+        let open = fixtures::file(
+            FdType::Numbered(3, FdMode::ReadWrite),
+            FileType::Reg,
+            "/tmp/f",
+        );
+        let cwd = fixtures::file(FdType::Cwd, FileType::Dir, "/");
+        let provider = MockProvider::new(vec![
+            fixtures::process(10, "proc10", vec![cwd.clone(), open]),
+            fixtures::process(11, "proc11", vec![cwd]),
+        ]);
+        let procs = provider.list_processes().unwrap();
+        let pids: Vec<u32> = zero_fd_processes(&provider, procs)
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        assert_eq!(pids, vec![11]);
+        assert_eq!(provider.open_file_reads.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_dry_run_counts_without_reading_files() {
+        use crate::model::{FdMode, FdType};
+        use crate::platform::mock::MockProvider;
+        use std::sync::atomic::Ordering;
+
+        let file = |fd| fixtures::file(FdType::Numbered(fd, FdMode::Read), FileType::Reg, "/tmp/f");
+        let mut www = fixtures::process(2, "proc2", vec![file(0), file(1), file(2)]);
+        www.user = "www".into();
+        let provider = MockProvider::new(vec![
+            fixtures::process(1, "proc1", vec![file(0)]),
+            www,
+            fixtures::process(3, "proc3", vec![file(0), file(1)]),
+        ]);
+        let filter = FilterConfig::parse_expr("user:root").unwrap();
+        let estimate = dry_run_estimate(&provider, &filter).unwrap();
+        assert_eq!(
+            estimate,
+            DryRunEstimate {
+                processes: 2,
+                files: 3,
+                uncounted: 0,
+            }
+        );
+        assert_eq!(provider.open_file_reads.load(Ordering::Relaxed), 0);
+        assert_eq!(
+            dry_run_json(&estimate),
+            "{\"processes\":2,\"estimated_files\":3,\"uncounted\":0}"
+        );
+    }
+
+    #[test]
+    fn test_time_wait_alert_threshold() {
+        let mut conns: Vec<NetworkInfo> = (0..6)
+            .map(|i| make_conn(1, 80, 5000 + i, TcpState::TimeWait))
+            .collect();
+        conns.extend((0..4).map(|i| make_conn(1, 80, 6000 + i, TcpState::Established)));
+        let mut udp = make_conn(2, 53, 0, TcpState::Closed);
+        udp.protocol = Protocol::Udp;
+        conns.push(udp);
+
+        let summary = tcp_state_summary(&conns);
+        assert_eq!(summary[0].state, TcpState::TimeWait);
+        assert_eq!(summary[0].count, 6);
+        assert!((summary[0].percent - 60.0).abs() < 1e-9);
+        assert_eq!(summary.len(), 2);
+
+        assert!(time_wait_alert(&summary, 50.0).unwrap().contains("60.0%"));
+        assert_eq!(time_wait_alert(&summary, 60.0), None);
+        assert_eq!(time_wait_alert(&summary, 75.0), None);
+        assert_eq!(time_wait_alert(&tcp_state_summary(&[]), 0.0), None);
+    }
+
+    #[test]
+    fn test_port_role_ignores_unix() {
+        let mut conn = make_conn(1, 5432, 0, TcpState::Listen);
+        conn.protocol = Protocol::Unix;
+        assert_eq!(port_role(&conn, 5432), None);
+    }
+
+    #[test]
+    fn test_type_lists_cover_known_values() {
+        let types = type_list_lines();
+        for name in ["REG", "DIR", "IPv4", "IPv6", "unix", "PIPE"] {
+            assert!(
+                types
+                    .iter()
+                    .any(|l| l.split_whitespace().next() == Some(name)),
+                "missing {}",
+                name
+            );
+        }
+        let fds = fd_type_list_lines();
+        assert!(fds.iter().any(|l| l.starts_with("cwd ")));
+        assert!(fds.iter().any(|l| l.starts_with("N[rwu]")));
+    }
+
+    #[test]
+    fn test_field_help_chars_all_have_handlers() {
+        use crate::model::{LockInfo, MapInfo};
+        use crate::output::{field_spec, FieldValue, OutputFormatter};
+
+        let fmt = OutputFormatter::default();
+        let mut file = OpenFileInfo {
+            node: "12".into(),
+            ..fixtures::file(
+                FdType::Numbered(3, FdMode::ReadWrite),
+                FileType::Reg,
+                "/tmp/x",
+            )
+        };
+        file.size_off = Some(4);
+        file.lock = Some(LockInfo {
+            write: true,
+            whole_file: true,
+        });
+        file.cloexec = Some(false);
+        file.mapping = Some(MapInfo {
+            shared: true,
+            writable: true,
+        });
+        let mut proc = fixtures::process(10, "proc10", vec![file.clone()]);
+        proc.ppid = Some(1);
+        proc.pgid = Some(10);
+
+        let lines = field_list_lines();
+        assert_eq!(lines.len(), FIELD_TABLE.len());
+        for line in &lines {
+            let ch = line.chars().next().unwrap();
+            let spec = field_spec(ch).unwrap_or_else(|| panic!("no handler for '{}'", ch));
+            let value = match spec.value {
+                FieldValue::Process(f) => f(&fmt, &proc),
+                FieldValue::File(f) => f(&fmt, &file),
+            };
+            assert!(value.is_some(), "'{}' emitted nothing", ch);
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_capabilities_on_linux() {
+        let caps = capabilities_json();
+        assert!(caps.contains("\"platform\":\"linux\""));
+        assert!(caps.contains("\"socket_protocols\":[\"TCP\",\"TCP6\",\"UDP\",\"UDP6\",\"unix\"]"));
+        assert!(caps.contains("\"namespaces\":true"));
+        assert!(caps.contains("\"json\""));
+    }
+
+    #[test]
+    fn test_classify_leaks_across_snapshots() {
+        let wait = fixtures::tcp_socket(4, "10.0.0.1:80", "10.0.0.2:5000", TcpState::CloseWait);
+        // Named like a CLOSE_WAIT socket, but the state says otherwise.
+        let lookalike = OpenFileInfo {
+            name: "10.0.0.1:80 -> 10.0.0.2:5001 (CLOSE_WAIT)".into(),
+            ..fixtures::tcp_socket(9, "10.0.0.1:80", "10.0.0.2:5001", TcpState::Established)
+        };
+        let deleted = OpenFileInfo {
+            node: "77".into(),
+            ..fixtures::file(
+                FdType::Numbered(5, FdMode::ReadWrite),
+                FileType::Reg,
+                "/tmp/cache (deleted)",
+            )
+        };
+        let orphan = OpenFileInfo {
+            node: "900".into(),
+            ..fixtures::file(
+                FdType::Numbered(6, FdMode::ReadWrite),
+                FileType::Pipe,
+                "pipe:[900]",
+            )
+        };
+        let paired = OpenFileInfo {
+            node: "901".into(),
+            ..fixtures::file(
+                FdType::Numbered(7, FdMode::ReadWrite),
+                FileType::Pipe,
+                "pipe:[901]",
+            )
+        };
+        // Its other end is in a process the filters leave out.
+        let hidden_peer = OpenFileInfo {
+            node: "902".into(),
+            ..fixtures::file(
+                FdType::Numbered(10, FdMode::ReadWrite),
+                FileType::Pipe,
+                "pipe:[902]",
+            )
+        };
+        let transient = OpenFileInfo {
+            node: "78".into(),
+            ..fixtures::file(
+                FdType::Numbered(8, FdMode::ReadWrite),
+                FileType::Reg,
+                "/tmp/scratch (deleted)",
+            )
+        };
+
+        let before = vec![
+            fixtures::process(
+                10,
+                "proc10",
+                vec![
+                    wait.clone(),
+                    deleted.clone(),
+                    orphan.clone(),
+                    paired.clone(),
+                    lookalike.clone(),
+                    hidden_peer.clone(),
+                ],
+            ),
+            fixtures::process(20, "proc20", vec![paired.clone()]),
+            fixtures::process(30, "proc30", vec![deleted.clone()]),
+        ];
+        let after = vec![
+            fixtures::process(
+                10,
+                "proc10",
+                vec![
+                    wait,
+                    deleted.clone(),
+                    orphan,
+                    paired.clone(),
+                    transient,
+                    lookalike,
+                    hidden_peer.clone(),
+                ],
+            ),
+            fixtures::process(20, "proc20", vec![paired]),
+            fixtures::process(30, "proc30", vec![deleted]),
+        ];
+        let mut everyone = after.clone();
+        everyone.push(fixtures::process(40, "proc40", vec![hidden_peer]));
+
+        let suspects: Vec<(u32, String, LeakKind)> = classify_leaks(&before, &after, &everyone)
+            .into_iter()
+            .map(|s| (s.pid, s.fd, s.kind))
+            .collect();
+        assert_eq!(
+            suspects,
+            vec![
+                (10, "4u".to_string(), LeakKind::CloseWait),
+                (10, "5u".to_string(), LeakKind::Deleted),
+                (10, "6u".to_string(), LeakKind::OrphanPipe),
+                (30, "5u".to_string(), LeakKind::Deleted),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_min_connections_with_state() {
+        let socket = |fd, state| {
+            let remote = format!("10.0.0.2:{}", 5000 + fd);
+            fixtures::tcp_socket(fd, "10.0.0.1:80", &remote, state)
+        };
+        let mut leaky: Vec<OpenFileInfo> =
+            (0..50).map(|fd| socket(fd, TcpState::CloseWait)).collect();
+        leaky.extend((50..55).map(|fd| socket(fd, TcpState::Established)));
+        // A name that merely ends like the state doesn't count.
+        leaky.extend((55..58).map(|fd| OpenFileInfo {
+            name: format!("/tmp/sock{} (CLOSE_WAIT)", fd),
+            ..socket(fd, TcpState::Established)
+        }));
+        let busy: Vec<OpenFileInfo> = (0..30)
+            .map(|fd| socket(fd, TcpState::Established))
+            .collect();
+        let few: Vec<OpenFileInfo> = (0..5).map(|fd| socket(fd, TcpState::CloseWait)).collect();
+        let procs = vec![
+            fixtures::process(10, "proc10", few),
+            fixtures::process(20, "proc20", busy),
+            fixtures::process(30, "proc30", leaky),
+        ];
+
+        let state = TcpState::from_name("close_wait").unwrap();
+        let found: Vec<(usize, u32)> = connection_counts(procs.clone(), 10, Some(&state))
+            .into_iter()
+            .map(|(count, p)| (count, p.pid))
+            .collect();
+        assert_eq!(found, vec![(50, 30)]);
+
+        let procs_for_report = procs.clone();
+        let any: Vec<(usize, u32)> = connection_counts(procs, 10, None)
+            .into_iter()
+            .map(|(count, p)| (count, p.pid))
+            .collect();
+        assert_eq!(any, vec![(58, 30), (30, 20)]);
+
+        let mut out = Vec::new();
+        print_connection_report(
+            &mut out,
+            &connection_counts(procs_for_report, 10, Some(&state)),
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), 2);
+        assert!(out.lines().nth(1).unwrap().ends_with(" proc30"));
+    }
+
+    #[test]
+    fn test_watch_events_enter_and_release() {
+        let holder = |pid| {
+            fixtures::process(
+                pid,
+                &format!("proc{}", pid),
+                vec![fixtures::file(
+                    FdType::Numbered(3, FdMode::ReadWrite),
+                    FileType::Reg,
+                    "/etc/x",
+                )],
+            )
+        };
+        let entering = |pid| WatchEvent::Entering {
+            pid,
+            comm: format!("proc{}", pid),
+            user: "root".into(),
+        };
+
+        assert_eq!(watch_events(&[], &[holder(1)]), vec![entering(1)]);
+        assert_eq!(
+            watch_events(&[holder(1)], &[holder(1), holder(2)]),
+            vec![entering(2)]
+        );
+        // One of two holders closing it is not a release.
+        assert_eq!(watch_events(&[holder(1), holder(2)], &[holder(2)]), vec![]);
+        assert_eq!(watch_events(&[holder(2)], &[]), vec![WatchEvent::Released]);
+        assert_eq!(watch_events(&[], &[]), vec![]);
+        assert_eq!(
+            watch_event_line("/etc/x", &entering(7)),
+            "entering /etc/x 7 (proc7, root)"
+        );
+    }
+
+    #[test]
+    fn test_self_check_results_flag_mismatches() {
+        let probe = SelfCheckProbe {
+            fd: 5,
+            path: "/usr/bin/loof".into(),
+            size: 100,
+            cwd: "/home/u".into(),
+        };
+        let mut exe = OpenFileInfo {
+            size_off: Some(100),
+            ..fixtures::file(
+                FdType::Numbered(5, FdMode::ReadWrite),
+                FileType::Reg,
+                "/usr/bin/loof",
+            )
+        };
+        let cwd = fixtures::file(FdType::Cwd, FileType::Dir, "/home/u");
+
+        let good = self_check_results(&[cwd.clone(), exe.clone()], &probe);
+        assert_eq!(good.len(), 4);
+        assert!(good.iter().all(|(ok, _)| *ok), "{:?}", good);
+
+        // A garbled path, as a wrong struct layout would produce.
+        exe.name = "\u{1}ar/loof".into();
+        let bad = self_check_results(&[cwd, exe], &probe);
+        assert_eq!(bad.iter().filter(|(ok, _)| !ok).count(), 1);
+        assert!(self_check_results(&[], &probe).iter().all(|(ok, _)| !ok));
+    }
+}
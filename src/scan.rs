@@ -0,0 +1,907 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use crate::cli::{CliArgs, FileSortKey, SortKey};
+use crate::error::Result;
+use crate::filter::FilterConfig;
+use crate::model::{FdType, OpenFileInfo, ProcessInfo};
+use crate::platform::PlatformProvider;
+use crate::trace;
+
+/// Options controlling how a scan is assembled, independent of filtering
+/// and output formatting.
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Process ordering; `None` keeps the provider's discovery order.
+    pub sort: Option<SortKey>,
+    /// Order output by an explicit `-p` include list instead of `sort`.
+    /// Set when the user did not ask for a specific `--sort` key.
+    pub pid_list_order: bool,
+    /// Drop processes whose PID was reused during the scan instead of
+    /// keeping them with a warning.
+    pub strict: bool,
+    /// Collapse entries that refer to the same file (same device, inode and
+    /// name), keeping the most informative FD label.
+    pub dedup: bool,
+    /// Omit the `rtd` entry when the process root is plain `/`; a chroot'd
+    /// root is still listed.
+    pub hide_root: bool,
+    /// Prefix file paths of a chroot'd process that lie outside its root
+    /// with that root (see [`prefix_root`]).
+    pub prefix_root: bool,
+    /// Prefix names of files on NFS mounts with `nfs:`, since their device
+    /// numbers are anonymous and don't identify the server.
+    pub mark_nfs: bool,
+    /// Write each matching process out as soon as it is complete (see
+    /// [`scan_each`]) instead of collecting and sorting the whole scan.
+    /// Used for `-t` unless `--sort` or a `-p` list asks for an order.
+    pub stream: bool,
+    /// Reorder each process's files as it is emitted (`--sort-files`).
+    pub sort_files: Option<FileSortKey>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            sort: Some(SortKey::Pid),
+            pid_list_order: true,
+            strict: false,
+            dedup: false,
+            hide_root: false,
+            prefix_root: false,
+            mark_nfs: false,
+            stream: false,
+            sort_files: None,
+        }
+    }
+}
+
+impl ScanOptions {
+    /// Build `ScanOptions` from parsed CLI arguments.
+    pub fn from_cli(args: &CliArgs) -> Self {
+        let sort = if args.no_sort {
+            None
+        } else {
+            Some(args.sort.unwrap_or(SortKey::Pid))
+        };
+        ScanOptions {
+            sort,
+            pid_list_order: args.sort.is_none(),
+            strict: args.strict,
+            dedup: args.dedup,
+            hide_root: args.hide_root,
+            prefix_root: args.prefix_root,
+            mark_nfs: args.mark_nfs,
+            stream: args.terse && args.sort.is_none() && args.pid.is_none(),
+            sort_files: args.sort_files,
+        }
+    }
+}
+
+/// The outcome of a scan: matching processes plus bookkeeping used for
+/// diagnostics.
+#[derive(Debug, Default)]
+pub struct ScanResult {
+    pub processes: Vec<ProcessInfo>,
+    /// Processes whose open files could not be read (permission denied or
+    /// exited mid-scan).
+    pub skipped: usize,
+    /// Processes whose PID changed identity while their files were read.
+    /// Dropped in strict mode, otherwise kept and reported.
+    pub reused: usize,
+    /// Processes whose root (`rtd`) isn't `/` and that list paths outside
+    /// it, which may be relative to that root. Always zero when
+    /// `--prefix-root` rewrote them.
+    pub chrooted: usize,
+    /// Processes that passed all filters; `processes.len()` unless they
+    /// were streamed with [`scan_each`].
+    pub matched: usize,
+}
+
+impl ScanResult {
+    /// Human-readable warnings describing anything the scan had to skip.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.skipped > 0 {
+            warnings.push(format!(
+                "can't read open files of {} process(es) (permission denied or exited)",
+                self.skipped
+            ));
+        }
+        if self.reused > 0 {
+            warnings.push(format!(
+                "{} process(es) exited and had their PID reused during the scan; \
+                 their open files may belong to another process (use --strict to drop them)",
+                self.reused
+            ));
+        }
+        if self.chrooted > 0 {
+            warnings.push(format!(
+                "{} process(es) run under a root other than / and list paths outside it, \
+                 which may be relative to that root (use --prefix-root to prefix them)",
+                self.chrooted
+            ));
+        }
+        if self.matched == 0 {
+            warnings.push("no matching processes".to_string());
+        }
+        warnings
+    }
+
+    /// A suggestion to re-run with privileges, when the scan skipped
+    /// processes and `euid` is not root.
+    pub fn privilege_hint(&self, euid: u32) -> Option<&'static str> {
+        (self.skipped > 0 && euid != 0).then_some("Run with sudo to see all processes.")
+    }
+}
+
+/// List processes, apply process- and file-level filters, and populate each
+/// surviving process's open files.
+pub fn scan(
+    provider: &dyn PlatformProvider,
+    filter_config: &FilterConfig,
+    opts: &ScanOptions,
+) -> Result<ScanResult> {
+    let mut processes = Vec::new();
+    let mut result = scan_each(provider, filter_config, opts, &mut |proc| {
+        processes.push(proc);
+        Ok(())
+    })?;
+
+    // Step 3: Order the result so output is stable across runs.
+    if let Some(key) = opts.sort {
+        let pid_list = filter_config
+            .pids
+            .as_ref()
+            .map(|f| f.include.as_slice())
+            .filter(|list| opts.pid_list_order && !list.is_empty());
+        match pid_list {
+            Some(list) => sort_by_pid_list(&mut processes, list),
+            None => sort_processes(&mut processes, key),
+        }
+        if opts.sort_files.is_none() {
+            for proc in &mut processes {
+                sort_files_by_fd(&mut proc.open_files);
+            }
+        }
+    }
+
+    result.processes = processes;
+    Ok(result)
+}
+
+/// Like [`scan`], but hand each matching process to `emit` as soon as its
+/// open files are read and filtered, in discovery order and unsorted
+/// (though `--sort-files` still orders each process's files). The
+/// returned `ScanResult` has the counters but no processes.
+pub fn scan_each(
+    provider: &dyn PlatformProvider,
+    filter_config: &FilterConfig,
+    opts: &ScanOptions,
+    emit: &mut dyn FnMut(ProcessInfo) -> Result<()>,
+) -> Result<ScanResult> {
+    let mut processes = {
+        let mut span = trace::span("list_processes");
+        let processes = provider.list_processes()?;
+        span.record("count", processes.len());
+        processes
+    };
+
+    // Step 1: Filter processes by process-level criteria.
+    processes.retain(|p| filter_config.matches_process(p));
+
+    // Step 2: For each matching process, get open files and apply file-level filters.
+    let has_file_filters = filter_config.has_file_filters();
+    let nfs_devices: HashSet<String> = if opts.mark_nfs {
+        provider
+            .list_mounts()?
+            .into_iter()
+            .filter(|m| m.fstype.starts_with("nfs"))
+            .map(|m| m.device)
+            .collect()
+    } else {
+        HashSet::new()
+    };
+    let mut result = ScanResult::default();
+
+    for mut proc in processes {
+        // Populate open files from the platform provider. The start time is
+        // read on both sides so a PID recycled mid-read can be detected.
+        let started = provider.start_time(proc.pid);
+        let readable = {
+            let mut span = trace::span("list_open_files");
+            span.record("pid", proc.pid);
+            match provider.list_open_files(proc.pid) {
+                Ok(files) => {
+                    span.record("files", files.len());
+                    proc.open_files = files;
+                    true
+                }
+                Err(_) => {
+                    // Permission denied or process gone -- count it for the
+                    // warning summary; it is still listed, without files.
+                    result.skipped += 1;
+                    false
+                }
+            }
+        };
+        if readable {
+            if provider.start_time(proc.pid) != started {
+                result.reused += 1;
+                if opts.strict {
+                    continue;
+                }
+            }
+            if let Some(root) = foreign_root(&proc.open_files) {
+                if opts.prefix_root {
+                    prefix_root(&mut proc.open_files, &root);
+                } else if proc.open_files.iter().any(|f| outside_root(f, &root)) {
+                    result.chrooted += 1;
+                }
+            }
+        }
+
+        if !filter_config.matches_cwd(&proc.open_files) {
+            continue;
+        }
+
+        // Apply file-level filters if any are active.
+        if has_file_filters {
+            proc.open_files.retain(|f| filter_config.matches_file(f));
+        }
+        if opts.dedup {
+            dedup_files(&mut proc.open_files);
+        }
+        if opts.hide_root {
+            proc.open_files
+                .retain(|f| !(f.fd == FdType::Rtd && f.name == "/"));
+        }
+        if !nfs_devices.is_empty() {
+            mark_nfs(&mut proc.open_files, &nfs_devices);
+        }
+        // If file-level filters are active, drop processes with no matching files.
+        if has_file_filters && proc.open_files.is_empty() {
+            continue;
+        }
+
+        if let Some(key) = opts.sort_files {
+            sort_files(&mut proc.open_files, key);
+        }
+
+        result.matched += 1;
+        emit(proc)?;
+    }
+
+    Ok(result)
+}
+
+/// The process root from its `rtd` entry, when it isn't plain `/`.
+fn foreign_root(files: &[OpenFileInfo]) -> Option<String> {
+    files
+        .iter()
+        .find(|f| f.fd == FdType::Rtd)
+        .map(|f| f.name.trim_end_matches('/'))
+        .filter(|root| !root.is_empty())
+        .map(str::to_string)
+}
+
+/// Whether `file` has an absolute name outside `root`. The kernel usually
+/// resolves names from our root (Linux does for a chroot), so only these
+/// can be relative to the process's root; the `rtd` entry never is.
+fn outside_root(file: &OpenFileInfo, root: &str) -> bool {
+    file.fd != FdType::Rtd
+        && file.name.starts_with('/')
+        && !file
+            .name
+            .strip_prefix(root)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Prefix every absolute file name outside `root` with it. Names already
+/// under `root` are from our side and are left alone.
+pub fn prefix_root(files: &mut [OpenFileInfo], root: &str) {
+    for f in files.iter_mut() {
+        if outside_root(f, root) {
+            f.name = format!("{}{}", root, f.name);
+        }
+    }
+}
+
+/// Prefix the name of every file on one of `nfs_devices` with `nfs:`.
+pub fn mark_nfs(files: &mut [OpenFileInfo], nfs_devices: &HashSet<String>) {
+    for f in files.iter_mut() {
+        if nfs_devices.contains(&f.device) {
+            f.name.insert_str(0, "nfs:");
+        }
+    }
+}
+
+/// Sort processes by `key`, falling back to PID so ties are deterministic.
+pub fn sort_processes(processes: &mut [ProcessInfo], key: SortKey) {
+    match key {
+        SortKey::Pid => processes.sort_by_key(|p| p.pid),
+        SortKey::Command => processes.sort_by(|a, b| a.comm.cmp(&b.comm).then(a.pid.cmp(&b.pid))),
+        SortKey::User => processes.sort_by(|a, b| a.user.cmp(&b.user).then(a.pid.cmp(&b.pid))),
+        SortKey::Fds => processes.sort_by(|a, b| {
+            b.open_files
+                .len()
+                .cmp(&a.open_files.len())
+                .then(a.pid.cmp(&b.pid))
+        }),
+    }
+}
+
+/// Sort processes into the order their PIDs appear in `list`. Processes not
+/// in the list (selected by another OR'd filter) follow, by PID.
+pub fn sort_by_pid_list(processes: &mut [ProcessInfo], list: &[u32]) {
+    processes.sort_by_key(|p| {
+        let pos = list.iter().position(|&pid| pid == p.pid);
+        (pos.unwrap_or(usize::MAX), p.pid)
+    });
+}
+
+/// Sort open files the way lsof lists them: cwd, rtd, txt and memory
+/// mappings first, then numbered FDs ascending. The sort is stable, so
+/// multiple `mem` entries keep their mapping order.
+pub fn sort_files_by_fd(files: &mut [OpenFileInfo]) {
+    files.sort_by_key(|f| fd_order(&f.fd));
+}
+
+/// Order two files by `key`, ascending or `descending`, as the TUI table
+/// does. Files without a size count as size 0 and numeric inodes compare
+/// as numbers. Ties fall back to lsof's FD order so they are deterministic.
+pub fn compare_files(
+    a: &OpenFileInfo,
+    b: &OpenFileInfo,
+    key: FileSortKey,
+    descending: bool,
+) -> Ordering {
+    let directed = |order: Ordering| if descending { order.reverse() } else { order };
+    let fd = || fd_order(&a.fd).cmp(&fd_order(&b.fd));
+    let primary = match key {
+        FileSortKey::Size => directed(a.size_off.unwrap_or(0).cmp(&b.size_off.unwrap_or(0))),
+        FileSortKey::Fd => directed(fd()),
+        FileSortKey::Name => directed(a.name.cmp(&b.name)),
+        FileSortKey::Type => directed(a.file_type.to_string().cmp(&b.file_type.to_string())),
+        FileSortKey::Node => directed(match (a.node.parse::<u64>(), b.node.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => a.node.cmp(&b.node),
+        }),
+    };
+    primary.then_with(fd)
+}
+
+/// Sort one process's files for `--sort-files`: largest first for `size`,
+/// with files that have no size after the rest, and ascending for the
+/// other keys (see [`compare_files`]).
+pub fn sort_files(files: &mut [OpenFileInfo], key: FileSortKey) {
+    let descending = key == FileSortKey::Size;
+    files.sort_by(|a, b| {
+        let unsized_last = match key {
+            FileSortKey::Size => a.size_off.is_none().cmp(&b.size_off.is_none()),
+            _ => Ordering::Equal,
+        };
+        unsized_last.then_with(|| compare_files(a, b, key, descending))
+    });
+}
+
+/// Merge entries that refer to the same file, identified by device, inode
+/// and name. A memory mapping is dropped when the file is also open through
+/// a numbered FD or as cwd/rtd/txt, and repeated mappings collapse into the
+/// first. Numbered FDs are never merged with each other: dup'd descriptors
+/// are distinct handles.
+pub fn dedup_files(files: &mut Vec<OpenFileInfo>) {
+    let rank = |fd: &FdType| match fd {
+        FdType::Numbered(..) => 2,
+        FdType::Cwd | FdType::Rtd | FdType::Txt => 1,
+        FdType::Mem | FdType::Mmap => 0,
+    };
+    let key = |f: &OpenFileInfo| (f.device.clone(), f.node.clone(), f.name.clone());
+
+    let mut best: HashMap<(String, String, String), u8> = HashMap::new();
+    for f in files.iter() {
+        let r = best.entry(key(f)).or_insert(0);
+        *r = (*r).max(rank(&f.fd));
+    }
+    let mut seen_mapping = HashSet::new();
+    files.retain(|f| {
+        let r = rank(&f.fd);
+        if r > 0 {
+            return true;
+        }
+        let k = key(f);
+        best[&k] == 0 && seen_mapping.insert(k)
+    });
+}
+
+pub fn fd_order(fd: &FdType) -> (u8, u32) {
+    match fd {
+        FdType::Cwd => (0, 0),
+        FdType::Rtd => (1, 0),
+        FdType::Txt => (2, 0),
+        FdType::Mem => (3, 0),
+        FdType::Mmap => (4, 0),
+        FdType::Numbered(n, _) => (5, *n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::PidFilter;
+    use crate::model::{fixtures, FdMode, FileType, MountInfo};
+    use crate::output::OutputFormatter;
+    use crate::platform::mock::MockProvider;
+
+    #[test]
+    fn test_default_order_is_pid_ascending() {
+        let provider = MockProvider::new(vec![
+            fixtures::process(300, "c", vec![]),
+            fixtures::process(100, "a", vec![]),
+            fixtures::process(200, "b", vec![]),
+        ]);
+        let procs = scan(&provider, &FilterConfig::default(), &ScanOptions::default())
+            .unwrap()
+            .processes;
+        let pids: Vec<u32> = procs.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![100, 200, 300]);
+    }
+
+    #[test]
+    fn test_no_sort_keeps_discovery_order() {
+        let provider = MockProvider::new(vec![
+            fixtures::process(300, "c", vec![]),
+            fixtures::process(100, "a", vec![]),
+        ]);
+        let opts = ScanOptions {
+            sort: None,
+            ..Default::default()
+        };
+        let procs = scan(&provider, &FilterConfig::default(), &opts)
+            .unwrap()
+            .processes;
+        let pids: Vec<u32> = procs.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![300, 100]);
+    }
+
+    #[test]
+    fn test_sort_files_applies_to_streamed_and_sorted_scans() {
+        let file =
+            |fd, name| fixtures::file(FdType::Numbered(fd, FdMode::Read), FileType::Reg, name);
+        let provider = MockProvider::new(vec![fixtures::process(
+            1,
+            "a",
+            vec![file(3, "/b"), file(4, "/a")],
+        )]);
+        let opts = ScanOptions {
+            sort_files: Some(FileSortKey::Name),
+            ..Default::default()
+        };
+        let names = |proc: &ProcessInfo| -> Vec<String> {
+            proc.open_files.iter().map(|f| f.name.clone()).collect()
+        };
+
+        let mut streamed = Vec::new();
+        scan_each(&provider, &FilterConfig::default(), &opts, &mut |proc| {
+            streamed.push(names(&proc));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(streamed, vec![vec!["/a", "/b"]]);
+
+        let procs = scan(&provider, &FilterConfig::default(), &opts)
+            .unwrap()
+            .processes;
+        assert_eq!(names(&procs[0]), vec!["/a", "/b"]);
+    }
+
+    #[test]
+    fn test_pid_list_order_is_followed() {
+        let provider = MockProvider::new(vec![
+            fixtures::process(1, "a", vec![]),
+            fixtures::process(2, "b", vec![]),
+            fixtures::process(3, "c", vec![]),
+        ]);
+        let config = FilterConfig {
+            pids: Some(PidFilter {
+                include: vec![3, 1, 2],
+                exclude: vec![],
+            }),
+            ..Default::default()
+        };
+        let procs = scan(&provider, &config, &ScanOptions::default())
+            .unwrap()
+            .processes;
+        let pids: Vec<u32> = procs.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![3, 1, 2]);
+
+        // An explicit --sort key wins over the -p order.
+        let opts = ScanOptions {
+            sort: Some(SortKey::Pid),
+            pid_list_order: false,
+            ..Default::default()
+        };
+        let procs = scan(&provider, &config, &opts).unwrap().processes;
+        let pids: Vec<u32> = procs.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_streamed_terse_output_is_incremental() {
+        use std::sync::atomic::Ordering;
+
+        let provider = MockProvider::new(vec![
+            fixtures::process(30, "c", vec![]),
+            fixtures::process(10, "a", vec![]),
+            fixtures::process(20, "b", vec![]),
+        ]);
+        let formatter = OutputFormatter {
+            terse: true,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        // (bytes written so far, open-file reads so far) at each emit.
+        let mut seen = Vec::new();
+        let result = scan_each(
+            &provider,
+            &FilterConfig::default(),
+            &ScanOptions::default(),
+            &mut |proc| {
+                formatter.write_terse_pid(&mut out, &proc)?;
+                seen.push((out.len(), provider.open_file_reads.load(Ordering::Relaxed)));
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        // Each PID is written right after its own files are read, before
+        // the next process is touched, and in discovery order.
+        assert_eq!(seen, vec![(3, 1), (6, 2), (9, 3)]);
+        assert_eq!(String::from_utf8(out).unwrap(), "30\n10\n20\n");
+        assert_eq!(result.matched, 3);
+        assert!(result.processes.is_empty());
+        assert!(result.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_unreadable_process_is_counted() {
+        let mut provider = MockProvider::new(vec![
+            fixtures::process(1, "a", vec![]),
+            fixtures::process(2, "b", vec![]),
+        ]);
+        provider.unreadable = vec![2];
+        let result = scan(&provider, &FilterConfig::default(), &ScanOptions::default()).unwrap();
+        assert_eq!(result.skipped, 1);
+        assert!(result.warnings()[0].contains("1 process(es)"));
+    }
+
+    #[test]
+    fn test_privilege_hint_only_for_non_root_with_skips() {
+        let mut provider = MockProvider::new(vec![
+            fixtures::process(1, "a", vec![]),
+            fixtures::process(2, "b", vec![]),
+        ]);
+        let clean = scan(&provider, &FilterConfig::default(), &ScanOptions::default()).unwrap();
+        assert_eq!(clean.privilege_hint(1000), None);
+
+        provider.unreadable = vec![2];
+        let result = scan(&provider, &FilterConfig::default(), &ScanOptions::default()).unwrap();
+        assert_eq!(
+            result.privilege_hint(1000),
+            Some("Run with sudo to see all processes.")
+        );
+        assert_eq!(result.privilege_hint(0), None);
+    }
+
+    #[test]
+    fn test_reused_pid_flagged_or_dropped() {
+        let mut provider = MockProvider::new(vec![
+            fixtures::process(
+                1,
+                "a",
+                vec![fixtures::file(FdType::Cwd, FileType::Reg, "/")],
+            ),
+            fixtures::process(
+                2,
+                "b",
+                vec![fixtures::file(FdType::Cwd, FileType::Reg, "/")],
+            ),
+        ]);
+        provider.reused = vec![2];
+
+        let result = scan(&provider, &FilterConfig::default(), &ScanOptions::default()).unwrap();
+        assert_eq!(result.reused, 1);
+        assert_eq!(result.processes.len(), 2);
+        assert!(result.warnings().iter().any(|w| w.contains("--strict")));
+
+        let opts = ScanOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let result = scan(&provider, &FilterConfig::default(), &opts).unwrap();
+        assert_eq!(result.reused, 1);
+        let pids: Vec<u32> = result.processes.iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![1]);
+    }
+
+    #[test]
+    fn test_suppressed_warnings_write_nothing() {
+        let mut provider = MockProvider::new(vec![fixtures::process(1, "a", vec![])]);
+        provider.unreadable = vec![1];
+        let config = FilterConfig {
+            pids: Some(PidFilter {
+                include: vec![99],
+                exclude: vec![],
+            }),
+            ..Default::default()
+        };
+        let result = scan(&provider, &config, &ScanOptions::default()).unwrap();
+        let warnings = result.warnings();
+        assert!(!warnings.is_empty());
+
+        let quiet = OutputFormatter {
+            suppress_warnings: true,
+            ..Default::default()
+        };
+        let mut err = Vec::new();
+        quiet.write_warnings(&mut err, &warnings).unwrap();
+        assert!(err.is_empty());
+
+        let loud = OutputFormatter::default();
+        let mut err = Vec::new();
+        loud.write_warnings(&mut err, &warnings).unwrap();
+        assert!(String::from_utf8(err)
+            .unwrap()
+            .starts_with("loof: WARNING: "));
+    }
+
+    #[test]
+    fn test_files_sorted_by_fd() {
+        let mut files = vec![
+            fixtures::file(FdType::Numbered(7, FdMode::Read), FileType::Reg, "/b"),
+            fixtures::file(FdType::Mem, FileType::Reg, "/lib/x.so"),
+            fixtures::file(FdType::Numbered(2, FdMode::Write), FileType::Reg, "/a"),
+            fixtures::file(FdType::Txt, FileType::Reg, "/bin/app"),
+            fixtures::file(FdType::Cwd, FileType::Reg, "/"),
+            fixtures::file(FdType::Rtd, FileType::Reg, "/"),
+        ];
+        sort_files_by_fd(&mut files);
+        let order: Vec<String> = files.iter().map(|f| f.fd.to_string()).collect();
+        assert_eq!(order, vec!["cwd", "rtd", "txt", "mem", "2w", "7r"]);
+    }
+
+    #[test]
+    fn test_compare_files_by_size_then_fd() {
+        let sized = |fd, size| OpenFileInfo {
+            size_off: size,
+            ..fixtures::file(fd, FileType::Reg, "/f")
+        };
+        let mut files = vec![
+            sized(FdType::Numbered(5, FdMode::Read), None),
+            sized(FdType::Numbered(4, FdMode::Read), Some(10)),
+            sized(FdType::Cwd, Some(4096)),
+            sized(FdType::Numbered(3, FdMode::Read), Some(10)),
+        ];
+        sort_files(&mut files, FileSortKey::Size);
+        let order: Vec<String> = files.iter().map(|f| f.fd.to_string()).collect();
+        assert_eq!(order, vec!["cwd", "3r", "4r", "5r"]);
+
+        let a = fixtures::file(FdType::Numbered(9, FdMode::Read), FileType::Reg, "/a");
+        let b = fixtures::file(FdType::Numbered(1, FdMode::Read), FileType::Reg, "/b");
+        assert_eq!(
+            compare_files(&a, &b, FileSortKey::Name, false),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_files(&a, &b, FileSortKey::Name, true),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_files(&a, &b, FileSortKey::Fd, false),
+            Ordering::Greater
+        );
+
+        // The table's ascending size order counts no size as 0.
+        files.sort_by(|a, b| compare_files(a, b, FileSortKey::Size, false));
+        let order: Vec<String> = files.iter().map(|f| f.fd.to_string()).collect();
+        assert_eq!(order, vec!["5r", "3r", "4r", "cwd"]);
+
+        let inode = |node: &str| OpenFileInfo {
+            node: node.to_string(),
+            ..a.clone()
+        };
+        assert_eq!(
+            compare_files(&inode("9"), &inode("10"), FileSortKey::Node, false),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_dedup_collapses_mapped_and_open_file() {
+        let shared = |fd| OpenFileInfo {
+            device: "8,1".to_string(),
+            node: "4242".to_string(),
+            ..fixtures::file(fd, FileType::Reg, "/var/lib/app.db")
+        };
+        let files = vec![
+            shared(FdType::Mem),
+            shared(FdType::Numbered(3, FdMode::Read)),
+            shared(FdType::Mem),
+            fixtures::file(FdType::Mem, FileType::Reg, "/lib/libc.so"),
+            fixtures::file(FdType::Mem, FileType::Reg, "/lib/libc.so"),
+        ];
+        let provider = MockProvider::new(vec![fixtures::process(1, "a", files)]);
+
+        // Both entries are kept by default.
+        let procs = scan(&provider, &FilterConfig::default(), &ScanOptions::default())
+            .unwrap()
+            .processes;
+        assert_eq!(procs[0].open_files.len(), 5);
+
+        let opts = ScanOptions {
+            dedup: true,
+            ..Default::default()
+        };
+        let procs = scan(&provider, &FilterConfig::default(), &opts)
+            .unwrap()
+            .processes;
+        let listed: Vec<(String, &str)> = procs[0]
+            .open_files
+            .iter()
+            .map(|f| (f.fd.to_string(), f.name.as_str()))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("mem".to_string(), "/lib/libc.so"),
+                ("3r".to_string(), "/var/lib/app.db"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hide_root_omits_plain_rtd_only() {
+        let provider = MockProvider::new(vec![
+            fixtures::process(
+                1,
+                "a",
+                vec![
+                    fixtures::file(FdType::Cwd, FileType::Reg, "/"),
+                    fixtures::file(FdType::Rtd, FileType::Reg, "/"),
+                ],
+            ),
+            fixtures::process(
+                2,
+                "jailed",
+                vec![fixtures::file(FdType::Rtd, FileType::Reg, "/srv/jail")],
+            ),
+        ]);
+        let opts = ScanOptions {
+            hide_root: true,
+            ..Default::default()
+        };
+        let procs = scan(&provider, &FilterConfig::default(), &opts)
+            .unwrap()
+            .processes;
+        let fds: Vec<String> = procs[0]
+            .open_files
+            .iter()
+            .map(|f| f.fd.to_string())
+            .collect();
+        assert_eq!(fds, vec!["cwd"]);
+        assert_eq!(procs[1].open_files[0].name, "/srv/jail");
+
+        let procs = scan(&provider, &FilterConfig::default(), &ScanOptions::default())
+            .unwrap()
+            .processes;
+        assert_eq!(procs[0].open_files.len(), 2);
+    }
+
+    #[test]
+    fn test_chrooted_process_paths_are_annotated() {
+        let provider = MockProvider::new(vec![
+            fixtures::process(
+                1,
+                "host",
+                vec![
+                    fixtures::file(FdType::Rtd, FileType::Reg, "/"),
+                    fixtures::file(FdType::Cwd, FileType::Reg, "/etc"),
+                ],
+            ),
+            fixtures::process(
+                2,
+                "jailed",
+                vec![
+                    fixtures::file(FdType::Rtd, FileType::Reg, "/srv/jail"),
+                    fixtures::file(FdType::Cwd, FileType::Reg, "/etc"),
+                    fixtures::file(FdType::Txt, FileType::Reg, "/bin/sh"),
+                    fixtures::file(
+                        FdType::Numbered(3, FdMode::Read),
+                        FileType::Reg,
+                        "/srv/jail/var/log/app.log",
+                    ),
+                ],
+            ),
+            // Every name is already from our root, as Linux reports a chroot.
+            fixtures::process(
+                3,
+                "resolved",
+                vec![
+                    fixtures::file(FdType::Rtd, FileType::Reg, "/srv/jail"),
+                    fixtures::file(FdType::Cwd, FileType::Reg, "/srv/jail/etc"),
+                    fixtures::file(FdType::Txt, FileType::Reg, "/srv/jail/bin/sh"),
+                ],
+            ),
+        ]);
+        let result = scan(&provider, &FilterConfig::default(), &ScanOptions::default()).unwrap();
+        assert_eq!(result.chrooted, 1);
+        assert!(result.warnings()[0].contains("root other than /"));
+        assert_eq!(result.processes[1].open_files[0].name, "/etc");
+
+        let opts = ScanOptions {
+            prefix_root: true,
+            ..Default::default()
+        };
+        let result = scan(&provider, &FilterConfig::default(), &opts).unwrap();
+        assert_eq!(result.chrooted, 0);
+        let names = |p: &ProcessInfo| -> Vec<String> {
+            p.open_files.iter().map(|f| f.name.clone()).collect()
+        };
+        assert_eq!(names(&result.processes[0]), vec!["/etc", "/"]);
+        assert_eq!(
+            names(&result.processes[1]),
+            vec![
+                "/srv/jail/etc",
+                "/srv/jail",
+                "/srv/jail/bin/sh",
+                "/srv/jail/var/log/app.log",
+            ]
+        );
+        assert_eq!(
+            names(&result.processes[2]),
+            vec!["/srv/jail/etc", "/srv/jail", "/srv/jail/bin/sh"]
+        );
+        let sibling = fixtures::file(FdType::Cwd, FileType::Dir, "/srv/jailbin");
+        assert!(outside_root(&sibling, "/srv/jail"));
+    }
+
+    #[test]
+    fn test_mark_nfs_labels_files_on_nfs_mounts() {
+        let mut on_nfs = fixtures::file(
+            FdType::Numbered(3, FdMode::Read),
+            FileType::Reg,
+            "/net/home/a",
+        );
+        on_nfs.device = "0,52".into();
+        let mut local = fixtures::file(FdType::Numbered(4, FdMode::Read), FileType::Reg, "/tmp/b");
+        local.device = "254,0".into();
+        let mut provider = MockProvider::new(vec![fixtures::process(1, "a", vec![on_nfs, local])]);
+        provider.mounts = vec![
+            MountInfo {
+                device: "0,52".into(),
+                mount_point: "/net/home".into(),
+                fstype: "nfs4".into(),
+                options: vec![],
+            },
+            MountInfo {
+                device: "254,0".into(),
+                mount_point: "/".into(),
+                fstype: "ext4".into(),
+                options: vec![],
+            },
+        ];
+        let opts = ScanOptions {
+            mark_nfs: true,
+            ..Default::default()
+        };
+        let procs = scan(&provider, &FilterConfig::default(), &opts)
+            .unwrap()
+            .processes;
+        let names: Vec<&str> = procs[0]
+            .open_files
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["nfs:/net/home/a", "/tmp/b"]);
+    }
+}
@@ -0,0 +1,149 @@
+//! Experimental daemon mode (`--serve PATH`).
+//!
+//! Listens on a Unix socket and answers every connection with one fresh
+//! scan as a JSON document, then closes it. Nothing is kept between
+//! connections.
+
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+
+use crate::error::{LoofError, Result};
+use crate::filter::FilterConfig;
+use crate::output::OutputFormatter;
+use crate::platform::PlatformProvider;
+use crate::scan::{scan, ScanOptions};
+
+/// Serve scans on `path` until the process is killed. A stale socket left
+/// by a previous run is replaced; a socket something still answers on, or
+/// any other existing file, is an error.
+pub fn serve(
+    path: &Path,
+    provider: &dyn PlatformProvider,
+    filter_config: &FilterConfig,
+    scan_opts: &ScanOptions,
+    formatter: &OutputFormatter,
+) -> Result<()> {
+    if let Ok(meta) = std::fs::symlink_metadata(path) {
+        if !meta.file_type().is_socket() {
+            return Err(LoofError::Platform(format!(
+                "{} exists and is not a socket",
+                path.display()
+            )));
+        }
+        if UnixStream::connect(path).is_ok() {
+            return Err(LoofError::Platform(format!(
+                "{} is in use by a running server",
+                path.display()
+            )));
+        }
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = handle(stream, provider, filter_config, scan_opts, formatter) {
+                    formatter.print_warnings(&[format!("--serve: {}", e)]);
+                }
+            }
+            Err(e) => formatter.print_warnings(&[format!("--serve: accept failed: {}", e)]),
+        }
+    }
+    Ok(())
+}
+
+/// Answer one connection: scan, write the JSON document and a newline.
+fn handle(
+    mut stream: UnixStream,
+    provider: &dyn PlatformProvider,
+    filter_config: &FilterConfig,
+    scan_opts: &ScanOptions,
+    formatter: &OutputFormatter,
+) -> Result<()> {
+    let result = scan(provider, filter_config, scan_opts)?;
+    formatter.write_json(&mut stream, &result.processes)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures;
+    use crate::model::ProcessInfo;
+    use crate::platform::mock::MockProvider;
+    use std::io::Read;
+
+    /// Start `serve` on `path` in the background and connect once it is
+    /// listening.
+    fn serve_in_background(path: &Path, provider: MockProvider) -> UnixStream {
+        let server_path = path.to_path_buf();
+        std::thread::spawn(move || {
+            serve(
+                &server_path,
+                &provider,
+                &FilterConfig::default(),
+                &ScanOptions::default(),
+                &OutputFormatter::default(),
+            )
+        });
+        for _ in 0..200 {
+            if let Ok(stream) = UnixStream::connect(path) {
+                return stream;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        panic!("server never listened on {}", path.display());
+    }
+
+    #[test]
+    fn test_connection_receives_json_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("loof.sock");
+        // Left behind by an earlier run: nothing listens on it any more.
+        drop(UnixListener::bind(&path).unwrap());
+
+        let provider = MockProvider::new(vec![ProcessInfo {
+            ppid: Some(1),
+            ..fixtures::process(42, "srv \"x\"", Vec::new())
+        }]);
+        let mut client = serve_in_background(&path, provider);
+
+        let mut body = String::new();
+        client.read_to_string(&mut body).unwrap();
+        let expected = format!(
+            "{{\"schema_version\":1,\"tool_version\":\"{}\",\"processes\":[\
+             {{\"pid\":42,\"ppid\":1,\"command\":\"srv \\\"x\\\"\",\"user\":\"root\",\
+             \"uid\":0,\"files\":[]}}]}}\n",
+            env!("CARGO_PKG_VERSION"),
+        );
+        assert_eq!(body, expected);
+
+        // A second server must not take over the live socket.
+        let err = serve(
+            &path,
+            &MockProvider::new(vec![]),
+            &FilterConfig::default(),
+            &ScanOptions::default(),
+            &OutputFormatter::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("in use"), "{}", err);
+    }
+
+    #[test]
+    fn test_serve_refuses_to_replace_regular_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("loof.sock");
+        std::fs::write(&path, "").unwrap();
+        let err = serve(
+            &path,
+            &MockProvider::new(vec![]),
+            &FilterConfig::default(),
+            &ScanOptions::default(),
+            &OutputFormatter::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("not a socket"));
+    }
+}
@@ -0,0 +1,154 @@
+//! Opt-in timing spans for diagnosing slow scans.
+//!
+//! Built only with the `trace` cargo feature and enabled at runtime by
+//! `--trace` or a non-empty `RUST_LOG`/`LOOF_TRACE`. Each span prints one
+//! line to stderr when dropped:
+//!
+//! ```text
+//! loof: trace: list_open_files pid=812 files=37 elapsed=0.412ms
+//! ```
+//!
+//! Without the feature `Span` is a zero-sized type and every method is an
+//! empty inline function, so instrumented code compiles to nothing.
+
+use std::fmt::Display;
+
+#[cfg(feature = "trace")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "trace")]
+use std::time::Instant;
+
+#[cfg(feature = "trace")]
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(all(feature = "trace", test))]
+thread_local! {
+    static CAPTURE: std::cell::RefCell<Option<Vec<String>>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Turn tracing on when `flag` is set or the environment asks for it.
+#[cfg(feature = "trace")]
+pub fn init(flag: bool) {
+    let env = ["RUST_LOG", "LOOF_TRACE"]
+        .iter()
+        .any(|var| std::env::var_os(var).is_some_and(|v| !v.is_empty()));
+    ENABLED.store(flag || env, Ordering::Relaxed);
+}
+
+#[cfg(feature = "trace")]
+fn enabled() -> bool {
+    #[cfg(test)]
+    if CAPTURE.with(|c| c.borrow().is_some()) {
+        return true;
+    }
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// A timed region. Fields recorded with [`Span::record`] are printed along
+/// with the elapsed time when the span is dropped.
+pub struct Span {
+    #[cfg(feature = "trace")]
+    active: Option<(&'static str, Instant, String)>,
+}
+
+/// Start a span named `name`.
+#[inline]
+pub fn span(name: &'static str) -> Span {
+    #[cfg(feature = "trace")]
+    {
+        Span {
+            active: enabled().then(|| (name, Instant::now(), String::new())),
+        }
+    }
+    #[cfg(not(feature = "trace"))]
+    {
+        let _ = name;
+        Span {}
+    }
+}
+
+impl Span {
+    /// Attach `key=value` to the span's output line.
+    #[inline]
+    pub fn record(&mut self, key: &'static str, value: impl Display) {
+        #[cfg(feature = "trace")]
+        if let Some((_, _, fields)) = &mut self.active {
+            use std::fmt::Write;
+            let _ = write!(fields, " {}={}", key, value);
+        }
+        #[cfg(not(feature = "trace"))]
+        let _ = (key, value);
+    }
+}
+
+#[cfg(feature = "trace")]
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some((name, start, fields)) = self.active.take() else {
+            return;
+        };
+        let line = format!(
+            "loof: trace: {}{} elapsed={:.3}ms",
+            name,
+            fields,
+            start.elapsed().as_secs_f64() * 1000.0
+        );
+        #[cfg(test)]
+        if CAPTURE.with(|c| {
+            c.borrow_mut()
+                .as_mut()
+                .map(|v| v.push(line.clone()))
+                .is_some()
+        }) {
+            return;
+        }
+        eprintln!("{}", line);
+    }
+}
+
+/// Run `f` with tracing enabled on this thread and return the span lines it
+/// emitted instead of printing them.
+#[cfg(all(feature = "trace", test))]
+pub fn capture(f: impl FnOnce()) -> Vec<String> {
+    CAPTURE.with(|c| *c.borrow_mut() = Some(Vec::new()));
+    f();
+    CAPTURE.with(|c| c.borrow_mut().take().unwrap_or_default())
+}
+
+#[cfg(all(feature = "trace", test))]
+mod tests {
+    use super::*;
+    use crate::filter::FilterConfig;
+    use crate::model::{FileType, OpenFileInfo, ProcessInfo};
+    use crate::platform::mock::MockProvider;
+    use crate::scan::{scan, ScanOptions};
+
+    #[test]
+    fn test_scan_emits_spans() {
+        let file = OpenFileInfo {
+            fd: crate::model::FdType::Cwd,
+            file_type: FileType::Dir,
+            name: "/".to_string(),
+            ..Default::default()
+        };
+        let provider = MockProvider::new(vec![ProcessInfo {
+            pid: 7,
+            ppid: Some(1),
+            command: "a".to_string(),
+            comm: "a".to_string(),
+            user: "root".to_string(),
+            open_files: vec![file],
+            ..Default::default()
+        }]);
+        let lines = capture(|| {
+            scan(&provider, &FilterConfig::default(), &ScanOptions::default()).unwrap();
+        });
+        assert!(lines
+            .iter()
+            .any(|l| l.starts_with("loof: trace: list_processes count=1 elapsed=")));
+        assert!(lines
+            .iter()
+            .any(|l| l.contains("list_open_files pid=7 files=1")));
+    }
+}
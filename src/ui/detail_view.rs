@@ -8,6 +8,7 @@ use super::theme;
 use super::widgets;
 use crate::app::state::DetailTab;
 use crate::app::AppState;
+use crate::output::short_container_id;
 
 pub fn render(frame: &mut Frame, state: &mut AppState, area: Rect) {
     let chunks = Layout::default()
@@ -23,7 +24,7 @@ pub fn render(frame: &mut Frame, state: &mut AppState, area: Rect) {
     render_header(frame, state, chunks[0]);
     render_tab_bar(frame, state, chunks[1]);
     render_content(frame, state, chunks[2]);
-    render_status(frame, chunks[3]);
+    render_status(frame, state, chunks[3]);
 }
 
 fn render_header(frame: &mut Frame, state: &AppState, area: Rect) {
@@ -32,18 +33,23 @@ fn render_header(frame: &mut Frame, state: &AppState, area: Rect) {
         None => return,
     };
 
-    let lines = vec![Line::from(vec![
+    let mut spans = vec![
         Span::styled(" PID ", theme::header_style()),
         Span::styled(format!("{}", proc.pid), theme::normal_style()),
         Span::styled("  CMD ", theme::header_style()),
-        Span::styled(&proc.comm, theme::normal_style()),
+        Span::styled(state.display_command(proc), theme::normal_style()),
         Span::styled("  USER ", theme::header_style()),
         Span::styled(&proc.user, theme::normal_style()),
-        Span::styled(
-            format!("  FDs {}", proc.open_files.len()),
-            theme::status_style(),
-        ),
-    ])];
+    ];
+    if let Some(ref id) = proc.container {
+        spans.push(Span::styled("  CONTAINER ", theme::header_style()));
+        spans.push(Span::styled(short_container_id(id), theme::normal_style()));
+    }
+    spans.push(Span::styled(
+        format!("  FDs {}", proc.open_files.len()),
+        theme::status_style(),
+    ));
+    let lines = vec![Line::from(spans)];
 
     frame.render_widget(Paragraph::new(lines), area);
 }
@@ -87,10 +93,23 @@ fn render_content(frame: &mut Frame, state: &mut AppState, area: Rect) {
     }
 }
 
-fn render_status(frame: &mut Frame, area: Rect) {
-    let status = Paragraph::new(Line::from(Span::styled(
-        "  Tab: switch tabs | j/k: scroll | Ctrl+Y: yank | Ctrl+E: export | Esc: back | q: quit",
+fn render_status(frame: &mut Frame, state: &AppState, area: Rect) {
+    let mut spans = Vec::new();
+    if let Some(ref banner) = state.detail_banner {
+        spans.push(Span::styled(
+            format!("  [{}]", banner),
+            theme::search_style(),
+        ));
+    }
+    if let Some(filter) = state.type_filter {
+        spans.push(Span::styled(
+            format!("  [filter: {}]", filter.label()),
+            theme::search_style(),
+        ));
+    }
+    spans.push(Span::styled(
+        "  Tab: switch tabs | j/k: scroll | g/G: top/end | f/d/s/n: filter type | r: refresh files | F5: refresh all | a: argv | c: columns | 1-5: sort | o: open | y: copy path | Ctrl+Y: yank | Ctrl+E: export | Esc: back | q: quit",
         theme::status_style(),
-    )));
-    frame.render_widget(status, area);
+    ));
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
 }
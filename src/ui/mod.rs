@@ -3,14 +3,74 @@ pub mod search_view;
 pub mod theme;
 pub mod widgets;
 
-use crate::app::state::ViewMode;
+use crate::app::state::{ExportFormat, ViewMode};
 use crate::app::AppState;
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use ratatui::Frame;
 
 pub fn render(frame: &mut Frame, state: &mut AppState) {
     let area = frame.area();
     match state.mode {
-        ViewMode::Search => search_view::render(frame, state),
+        ViewMode::Search | ViewMode::FilterInput => search_view::render(frame, state),
         ViewMode::Detail => detail_view::render(frame, state, area),
+        ViewMode::ExportMenu => {
+            detail_view::render(frame, state, area);
+            render_export_menu(frame, state, area);
+        }
     }
+    render_toast(frame, state, area);
+}
+
+/// Draw the export format menu centered over the detail view.
+fn render_export_menu(frame: &mut Frame, state: &AppState, area: Rect) {
+    let lines: Vec<Line> = ExportFormat::ALL
+        .iter()
+        .map(|format| {
+            let style = if *format == state.export_format {
+                theme::selected_style()
+            } else {
+                theme::normal_style()
+            };
+            Line::from(Span::styled(format!(" {:<18}", format.label()), style))
+        })
+        .collect();
+    let width = 22.min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let menu = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Export as ")
+        .title_bottom(" Enter: export | Esc ");
+    frame.render_widget(Clear, menu);
+    frame.render_widget(Paragraph::new(lines).block(block), menu);
+}
+
+/// Draw the status toast, if any, over the right end of the bottom line.
+fn render_toast(frame: &mut Frame, state: &AppState, area: Rect) {
+    let Some((ref message, _, kind)) = state.status_message else {
+        return;
+    };
+    let text = format!(" {} ", message);
+    let width = (text.chars().count() as u16).min(area.width);
+    if width == 0 || area.height == 0 {
+        return;
+    }
+    let toast = Rect {
+        x: area.right() - width,
+        y: area.bottom() - 1,
+        width,
+        height: 1,
+    };
+    frame.render_widget(Clear, toast);
+    frame.render_widget(
+        Paragraph::new(Span::styled(text, theme::status_kind_style(kind))),
+        toast,
+    );
 }
@@ -5,49 +5,125 @@ use ratatui::widgets::{List, ListItem, Paragraph};
 use ratatui::Frame;
 
 use super::theme;
+use super::widgets::truncate_to_width;
+use crate::app::state::ViewMode;
 use crate::app::AppState;
+use crate::model::process::fd_usage_label;
 
 pub fn render(frame: &mut Frame, state: &mut AppState) {
     let area = frame.area();
 
-    // Layout: main list area at top, search input + status at bottom
+    // Layout: main list area at top, preview + search input + status at bottom
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Min(3),    // Process list (takes remaining space)
+            Constraint::Length(2), // Preview pane
             Constraint::Length(1), // Search input line
             Constraint::Length(1), // Status line
         ])
         .split(area);
 
     render_process_list(frame, state, chunks[0]);
-    render_search_input(frame, state, chunks[1]);
-    render_status_line(frame, state, chunks[2]);
+    render_preview(frame, state, chunks[1]);
+    render_search_input(frame, state, chunks[2]);
+    render_status_line(frame, state, chunks[3]);
+}
+
+fn render_preview(frame: &mut Frame, state: &AppState, area: Rect) {
+    let preview = match &state.preview {
+        Some(p) if state.highlighted_process().map(|h| h.pid) == Some(p.pid) => p,
+        _ => return,
+    };
+
+    let lines = vec![
+        Line::from(vec![
+            Span::styled(" PID ", theme::header_style()),
+            Span::styled(preview.pid.to_string(), theme::normal_style()),
+            Span::styled("  USER ", theme::header_style()),
+            Span::styled(&preview.user, theme::normal_style()),
+            Span::styled("  PPID ", theme::header_style()),
+            Span::styled(
+                preview
+                    .ppid
+                    .map(|p| p.to_string())
+                    .unwrap_or_else(|| "-".into()),
+                theme::normal_style(),
+            ),
+            Span::styled("  FDs ", theme::header_style()),
+            Span::styled(
+                preview
+                    .fd_count
+                    .map(|c| fd_usage_label(c, preview.fd_limit))
+                    .unwrap_or_else(|| "?".into()),
+                theme::normal_style(),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled(" EXE ", theme::header_style()),
+            Span::styled(
+                preview.exe.clone().unwrap_or_else(|| "-".into()),
+                theme::status_style(),
+            ),
+        ]),
+    ];
+
+    frame.render_widget(Paragraph::new(lines), area);
 }
 
 fn render_process_list(frame: &mut Frame, state: &mut AppState, area: Rect) {
-    // Build header line
-    let header_text = format!(
-        "  {:<8} {:<20} {:<12} {:>6}",
-        "PID", "COMMAND", "USER", "FDs"
-    );
+    // Build header line. The full command line goes last so it can use the
+    // remaining width.
+    let header_text = if state.show_full_command {
+        format!("  {:<8} {:<12} {:>11}  {}", "PID", "USER", "FDs", "COMMAND")
+    } else {
+        format!(
+            "  {:<8} {:<20} {:<12} {:>11}",
+            "PID", "COMMAND", "USER", "FDs"
+        )
+    };
     let header_line = Line::from(Span::styled(header_text, theme::header_style()));
 
-    // Build list items from filtered indices
+    // Build list items from filtered indices, highlighting our own processes
+    let own_uid = users::get_current_uid();
     let items: Vec<ListItem> = state
         .filtered_indices
         .iter()
         .map(|&idx| {
             let proc = &state.all_processes[idx];
-            let fd_count = proc.fd_count();
-            let line_text = format!(
-                "  {:<8} {:<20} {:<12} {:>6}",
-                proc.pid,
-                truncate_str(&proc.comm, 20),
-                truncate_str(&proc.user, 12),
-                fd_count,
+            let row_style = theme::process_style(proc, own_uid);
+            let fds = Span::styled(
+                format!("{:>11}", proc.fd_usage()),
+                theme::fd_usage_style(proc, row_style),
             );
-            ListItem::new(Line::from(Span::styled(line_text, theme::normal_style())))
+            let spans = if state.show_full_command {
+                vec![
+                    Span::styled(
+                        format!(
+                            "  {:<8} {:<12} ",
+                            proc.pid,
+                            truncate_to_width(&proc.user, 12)
+                        ),
+                        row_style,
+                    ),
+                    fds,
+                    Span::styled(format!("  {}", state.display_command(proc)), row_style),
+                ]
+            } else {
+                vec![
+                    Span::styled(
+                        format!(
+                            "  {:<8} {:<20} {:<12} ",
+                            proc.pid,
+                            truncate_to_width(state.display_command(proc), 20),
+                            truncate_to_width(&proc.user, 12),
+                        ),
+                        row_style,
+                    ),
+                    fds,
+                ]
+            };
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -64,6 +140,15 @@ fn render_process_list(frame: &mut Frame, state: &mut AppState, area: Rect) {
     let header_paragraph = Paragraph::new(header_line);
     frame.render_widget(header_paragraph, list_chunks[0]);
 
+    if let Some(message) = state.empty_message() {
+        let hint = Paragraph::new(Line::from(Span::styled(
+            format!("  {}", message),
+            theme::status_style(),
+        )));
+        frame.render_widget(hint, list_chunks[1]);
+        return;
+    }
+
     // Render the scrollable list
     let list = List::new(items)
         .highlight_style(theme::selected_style())
@@ -73,29 +158,39 @@ fn render_process_list(frame: &mut Frame, state: &mut AppState, area: Rect) {
 }
 
 fn render_search_input(frame: &mut Frame, state: &AppState, area: Rect) {
-    let input_text = Line::from(vec![
-        Span::styled("> ", theme::search_style().add_modifier(Modifier::BOLD)),
-        Span::styled(&state.search_input, theme::search_style()),
-        Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)),
-    ]);
+    let cursor = Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK));
+    let input_text = if let ViewMode::FilterInput = state.mode {
+        Line::from(vec![
+            Span::styled(": ", theme::search_style().add_modifier(Modifier::BOLD)),
+            Span::styled(&state.filter_expr, theme::search_style()),
+            cursor,
+        ])
+    } else {
+        let mut spans = vec![
+            Span::styled("> ", theme::search_style().add_modifier(Modifier::BOLD)),
+            Span::styled(&state.search_input, theme::search_style()),
+            cursor,
+        ];
+        if !state.filter_expr.is_empty() {
+            spans.push(Span::styled(
+                format!("  [:{}]", state.filter_expr),
+                theme::status_style(),
+            ));
+        }
+        Line::from(spans)
+    };
 
     let input = Paragraph::new(input_text);
     frame.render_widget(input, area);
 }
 
 fn render_status_line(frame: &mut Frame, state: &AppState, area: Rect) {
-    let status_text = format!("  {}/{}", state.match_count, state.total_count);
+    let mut status_text = format!("  {}/{}", state.match_count, state.total_count);
+    if let Some(err) = &state.filter_error {
+        status_text.push_str(&format!("  filter: {}", err));
+    } else if let ViewMode::FilterInput = state.mode {
+        status_text.push_str("  Enter: keep filter | Esc: clear");
+    }
     let status = Paragraph::new(Line::from(Span::styled(status_text, theme::status_style())));
     frame.render_widget(status, area);
 }
-
-/// Truncate a string to fit within a given width, appending ".." if truncated.
-fn truncate_str(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else if max_len > 2 {
-        format!("{}..", &s[..max_len - 2])
-    } else {
-        s[..max_len].to_string()
-    }
-}
@@ -1,52 +1,325 @@
+use std::cell::Cell;
+
 use ratatui::style::{Color, Modifier, Style};
 
-use crate::model::FileType;
+use crate::app::state::StatusKind;
+use crate::cli::ThemeName;
+use crate::model::{FileType, ProcState, ProcessInfo};
 
-pub fn header_style() -> Style {
+/// A color scheme for the TUI. The `*_style` functions below read the
+/// theme installed with [`set_theme`] (dark unless `--theme` says otherwise).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub header: Style,
+    pub selected: Style,
+    pub normal: Style,
+    pub own_process: Style,
+    pub zombie: Style,
+    /// Processes stopped by a signal or tracer.
+    pub stopped: Style,
+    /// An FD count close to the process's soft limit.
+    pub fd_near_limit: Style,
+    pub search: Style,
+    pub status: Style,
+    pub toast_info: Style,
+    pub toast_warn: Style,
+    pub toast_error: Style,
+    pub reg: Style,
+    pub dir: Style,
+    pub link: Style,
+    pub chr: Style,
+    pub blk: Style,
+    /// FIFOs and pipes.
+    pub pipe: Style,
+    /// Generic and Unix-domain sockets.
+    pub sock: Style,
+    pub ipv4: Style,
+    pub ipv6: Style,
+    /// Kernel objects (kqueue, systm).
+    pub kernel: Style,
+    pub unknown: Style,
+}
+
+fn fg(color: Color) -> Style {
+    Style::default().fg(color)
+}
+
+fn toast(color: Color) -> Style {
     Style::default()
-        .fg(Color::Cyan)
+        .fg(Color::Black)
+        .bg(color)
         .add_modifier(Modifier::BOLD)
 }
 
+impl Theme {
+    /// Light text for dark terminal backgrounds (the default).
+    pub fn dark() -> Self {
+        Theme {
+            header: fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            selected: fg(Color::White)
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+            normal: fg(Color::White),
+            own_process: fg(Color::LightYellow),
+            zombie: fg(Color::LightRed).add_modifier(Modifier::BOLD),
+            stopped: fg(Color::LightMagenta),
+            fd_near_limit: fg(Color::LightRed).add_modifier(Modifier::BOLD),
+            search: fg(Color::Green),
+            status: fg(Color::DarkGray),
+            toast_info: toast(Color::Green),
+            toast_warn: toast(Color::Yellow),
+            toast_error: toast(Color::Red),
+            reg: fg(Color::White),
+            dir: fg(Color::Blue).add_modifier(Modifier::BOLD),
+            link: fg(Color::Magenta),
+            chr: fg(Color::Yellow),
+            blk: fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            pipe: fg(Color::LightRed),
+            sock: fg(Color::LightGreen),
+            ipv4: fg(Color::Green),
+            ipv6: fg(Color::Cyan),
+            kernel: fg(Color::DarkGray),
+            unknown: fg(Color::Gray),
+        }
+    }
+
+    /// Dark text that stays readable on white or pale backgrounds.
+    pub fn light() -> Self {
+        Theme {
+            header: fg(Color::Blue).add_modifier(Modifier::BOLD),
+            selected: fg(Color::Black)
+                .bg(Color::Gray)
+                .add_modifier(Modifier::BOLD),
+            normal: fg(Color::Black),
+            own_process: fg(Color::Magenta),
+            zombie: fg(Color::Red).add_modifier(Modifier::BOLD),
+            stopped: fg(Color::Magenta).add_modifier(Modifier::ITALIC),
+            fd_near_limit: fg(Color::Red).add_modifier(Modifier::BOLD),
+            search: fg(Color::Blue),
+            status: fg(Color::DarkGray),
+            toast_info: toast(Color::LightGreen),
+            toast_warn: toast(Color::LightYellow),
+            toast_error: toast(Color::LightRed),
+            reg: fg(Color::Black),
+            dir: fg(Color::Blue).add_modifier(Modifier::BOLD),
+            link: fg(Color::Magenta),
+            chr: fg(Color::Red),
+            blk: fg(Color::Red).add_modifier(Modifier::BOLD),
+            pipe: fg(Color::Magenta).add_modifier(Modifier::BOLD),
+            sock: fg(Color::Green),
+            ipv4: fg(Color::Green).add_modifier(Modifier::BOLD),
+            ipv6: fg(Color::Blue),
+            kernel: fg(Color::DarkGray),
+            unknown: fg(Color::DarkGray),
+        }
+    }
+
+    /// No colors at all, only bold/reverse, for monochrome terminals or
+    /// when colors clash with everything.
+    pub fn mono() -> Self {
+        let plain = Style::default();
+        let bold = plain.add_modifier(Modifier::BOLD);
+        let reversed = plain.add_modifier(Modifier::REVERSED);
+        Theme {
+            header: bold,
+            selected: reversed.add_modifier(Modifier::BOLD),
+            normal: plain,
+            own_process: bold,
+            zombie: bold.add_modifier(Modifier::CROSSED_OUT),
+            stopped: plain.add_modifier(Modifier::DIM),
+            fd_near_limit: reversed,
+            search: bold,
+            status: plain.add_modifier(Modifier::DIM),
+            toast_info: reversed,
+            toast_warn: reversed,
+            toast_error: reversed.add_modifier(Modifier::BOLD),
+            reg: plain,
+            dir: bold,
+            link: plain.add_modifier(Modifier::ITALIC),
+            chr: plain,
+            blk: bold,
+            pipe: plain,
+            sock: plain,
+            ipv4: plain,
+            ipv6: plain,
+            kernel: plain.add_modifier(Modifier::DIM),
+            unknown: plain.add_modifier(Modifier::DIM),
+        }
+    }
+
+    pub fn preset(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Theme::dark(),
+            ThemeName::Light => Theme::light(),
+            ThemeName::Mono => Theme::mono(),
+        }
+    }
+
+    /// Style for each file type to visually distinguish entries.
+    pub fn file_type_style(&self, ft: &FileType) -> Style {
+        match ft {
+            FileType::Reg => self.reg,
+            FileType::Dir => self.dir,
+            FileType::Link => self.link,
+            FileType::Chr => self.chr,
+            FileType::Blk => self.blk,
+            FileType::Fifo | FileType::Pipe => self.pipe,
+            FileType::Sock | FileType::Unix => self.sock,
+            FileType::IPv4 => self.ipv4,
+            FileType::IPv6 => self.ipv6,
+            FileType::Kqueue | FileType::Systm => self.kernel,
+            FileType::Unknown(_) => self.unknown,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<Theme> = Cell::new(Theme::dark());
+}
+
+/// Install the theme used by the style functions (the TUI draws on one
+/// thread, so this is set once before it starts).
+pub fn set_theme(theme: Theme) {
+    CURRENT.with(|t| t.set(theme));
+}
+
+fn current() -> Theme {
+    CURRENT.with(Cell::get)
+}
+
+pub fn header_style() -> Style {
+    current().header
+}
+
 pub fn selected_style() -> Style {
-    Style::default()
-        .bg(Color::DarkGray)
-        .fg(Color::White)
-        .add_modifier(Modifier::BOLD)
+    current().selected
 }
 
 pub fn normal_style() -> Style {
-    Style::default().fg(Color::White)
+    current().normal
+}
+
+/// Processes owned by the user running the TUI.
+pub fn own_process_style() -> Style {
+    current().own_process
+}
+
+/// Style for a process-list row: zombies and stopped processes stand out
+/// first, then processes owned by `own_uid` are highlighted.
+pub fn process_style(proc: &ProcessInfo, own_uid: u32) -> Style {
+    match proc.state {
+        Some(ProcState::Zombie) => current().zombie,
+        Some(ProcState::Stopped) => current().stopped,
+        _ if proc.uid == own_uid => own_process_style(),
+        _ => normal_style(),
+    }
+}
+
+/// Style for the FDs cell of a process-list row: flagged when the process
+/// is near its FD limit, otherwise the row's own style.
+pub fn fd_usage_style(proc: &ProcessInfo, row: Style) -> Style {
+    if proc.near_fd_limit() {
+        current().fd_near_limit
+    } else {
+        row
+    }
 }
 
 pub fn search_style() -> Style {
-    Style::default().fg(Color::Green)
+    current().search
 }
 
 pub fn status_style() -> Style {
-    Style::default().fg(Color::DarkGray)
+    current().status
+}
+
+/// Style for a status toast of the given severity.
+pub fn status_kind_style(kind: StatusKind) -> Style {
+    let theme = current();
+    match kind {
+        StatusKind::Info => theme.toast_info,
+        StatusKind::Warn => theme.toast_warn,
+        StatusKind::Error => theme.toast_error,
+    }
 }
 
 /// Color style for each file type to visually distinguish entries.
 pub fn file_type_style(ft: &FileType) -> Style {
-    match ft {
-        FileType::Reg => Style::default().fg(Color::White),
-        FileType::Dir => Style::default()
-            .fg(Color::Blue)
-            .add_modifier(Modifier::BOLD),
-        FileType::Link => Style::default().fg(Color::Magenta),
-        FileType::Chr => Style::default().fg(Color::Yellow),
-        FileType::Blk => Style::default()
-            .fg(Color::Yellow)
-            .add_modifier(Modifier::BOLD),
-        FileType::Fifo => Style::default().fg(Color::LightRed),
-        FileType::Pipe => Style::default().fg(Color::LightRed),
-        FileType::Sock => Style::default().fg(Color::LightGreen),
-        FileType::Unix => Style::default().fg(Color::LightGreen),
-        FileType::IPv4 => Style::default().fg(Color::Green),
-        FileType::IPv6 => Style::default().fg(Color::Cyan),
-        FileType::Kqueue => Style::default().fg(Color::DarkGray),
-        FileType::Systm => Style::default().fg(Color::DarkGray),
-        FileType::Unknown(_) => Style::default().fg(Color::Gray),
+    current().file_type_style(ft)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_style_highlights_own_uid() {
+        let proc = |uid, state| ProcessInfo {
+            pid: 1,
+            state,
+            uid,
+            ..Default::default()
+        };
+        assert_eq!(process_style(&proc(1000, None), 1000), own_process_style());
+        assert_eq!(process_style(&proc(0, None), 1000), normal_style());
+        assert_ne!(own_process_style(), normal_style());
+
+        let dark = Theme::dark();
+        let zombie = proc(1000, Some(ProcState::Zombie));
+        assert_eq!(process_style(&zombie, 1000), dark.zombie);
+        let stopped = proc(0, Some(ProcState::Stopped));
+        assert_eq!(process_style(&stopped, 1000), dark.stopped);
+    }
+
+    #[test]
+    fn test_fd_usage_style_flags_near_limit() {
+        use crate::model::{FdMode, FdType, OpenFileInfo};
+
+        let file = |n| OpenFileInfo {
+            fd: FdType::Numbered(n, FdMode::Read),
+            file_type: FileType::Reg,
+            ..Default::default()
+        };
+        let mut proc = ProcessInfo {
+            pid: 1,
+            fd_limit: Some((10, 20)),
+            open_files: (0..7).map(file).collect(),
+            ..Default::default()
+        };
+        let row = normal_style();
+        assert_eq!(proc.fd_usage(), "7/10");
+        assert_eq!(fd_usage_style(&proc, row), row);
+        proc.open_files.push(file(7));
+        assert_eq!(fd_usage_style(&proc, row), Theme::dark().fd_near_limit);
+        proc.fd_limit = Some((u64::MAX, u64::MAX));
+        assert_eq!(proc.fd_usage(), "8/∞");
+        assert_eq!(fd_usage_style(&proc, row), row);
+        // cwd and friends never count, with or without a known limit.
+        proc.open_files.push(OpenFileInfo {
+            fd: FdType::Cwd,
+            ..file(0)
+        });
+        proc.fd_limit = None;
+        assert_eq!(proc.fd_usage(), "8");
+    }
+
+    #[test]
+    fn test_presets_differ_per_file_type() {
+        let (dark, light, mono) = (Theme::dark(), Theme::light(), Theme::mono());
+        for ft in [FileType::Reg, FileType::Chr, FileType::IPv4] {
+            assert_ne!(light.file_type_style(&ft), dark.file_type_style(&ft));
+        }
+        assert_eq!(mono.file_type_style(&FileType::Reg).fg, None);
+        assert_eq!(Theme::preset(ThemeName::Light), light);
+
+        set_theme(light);
+        assert_eq!(file_type_style(&FileType::Dir), light.dir);
+        set_theme(Theme::default());
     }
 }
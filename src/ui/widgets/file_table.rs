@@ -4,7 +4,9 @@ use ratatui::text::Span;
 use ratatui::widgets::{Cell, Row, Table};
 use ratatui::Frame;
 
+use crate::app::state::{FileColumns, FileSort};
 use crate::app::AppState;
+use crate::cli::FileSortKey;
 use crate::ui::theme;
 
 pub fn render(frame: &mut Frame, state: &mut AppState, area: Rect) {
@@ -13,45 +15,37 @@ pub fn render(frame: &mut Frame, state: &mut AppState, area: Rect) {
         None => return,
     };
 
-    let header = Row::new(vec![
-        Cell::from("FD"),
-        Cell::from("TYPE"),
-        Cell::from("DEVICE"),
-        Cell::from("SIZE/OFF"),
-        Cell::from("NODE"),
-        Cell::from("NAME"),
-    ])
+    let columns = columns(state.visible_columns);
+    let header = Row::new(
+        columns
+            .iter()
+            .map(|c| Cell::from(header_title(*c, state.file_sort))),
+    )
     .style(theme::header_style());
 
-    let rows: Vec<Row> = proc
-        .open_files
-        .iter()
+    let rows: Vec<Row> = state
+        .filtered_files(&proc.open_files)
+        .into_iter()
         .map(|f| {
             let style = theme::file_type_style(&f.file_type);
-            let size_str = f.size_off.map(format_size).unwrap_or_default();
-            let name_display = match &f.link_target {
-                Some(target) => format!("{} -> {}", f.name, target),
-                None => f.name.clone(),
-            };
-            Row::new(vec![
-                Cell::from(Span::styled(f.fd.to_string(), style)),
-                Cell::from(Span::styled(f.file_type.to_string(), style)),
-                Cell::from(f.device.clone()),
-                Cell::from(size_str),
-                Cell::from(f.node.clone()),
-                Cell::from(Span::styled(name_display, style)),
-            ])
+            Row::new(columns.iter().map(|c| match c {
+                Column::Fd => Cell::from(Span::styled(f.fd.to_string(), style)),
+                Column::Type => Cell::from(Span::styled(f.file_type.to_string(), style)),
+                Column::Device => Cell::from(f.device.clone()),
+                Column::Size => Cell::from(f.size_off.map(format_size).unwrap_or_default()),
+                Column::Node => Cell::from(f.node.clone()),
+                Column::Name => {
+                    let name_display = match &f.link_target {
+                        Some(target) => format!("{} -> {}", f.name, target),
+                        None => f.name.clone(),
+                    };
+                    Cell::from(Span::styled(name_display, style))
+                }
+            }))
         })
         .collect();
 
-    let widths = [
-        Constraint::Length(8),
-        Constraint::Length(7),
-        Constraint::Length(12),
-        Constraint::Length(10),
-        Constraint::Length(10),
-        Constraint::Min(20),
-    ];
+    let widths = widths(&columns);
 
     let table = Table::new(rows, widths)
         .header(header)
@@ -60,6 +54,81 @@ pub fn render(frame: &mut Frame, state: &mut AppState, area: Rect) {
     frame.render_stateful_widget(table, area, &mut state.file_table_state);
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Column {
+    Fd,
+    Type,
+    Device,
+    Size,
+    Node,
+    Name,
+}
+
+impl Column {
+    fn title(self) -> &'static str {
+        match self {
+            Column::Fd => "FD",
+            Column::Type => "TYPE",
+            Column::Device => "DEVICE",
+            Column::Size => "SIZE/OFF",
+            Column::Node => "NODE",
+            Column::Name => "NAME",
+        }
+    }
+
+    /// The sort key for this column; DEVICE isn't sortable.
+    fn sort_key(self) -> Option<FileSortKey> {
+        match self {
+            Column::Fd => Some(FileSortKey::Fd),
+            Column::Type => Some(FileSortKey::Type),
+            Column::Device => None,
+            Column::Size => Some(FileSortKey::Size),
+            Column::Node => Some(FileSortKey::Node),
+            Column::Name => Some(FileSortKey::Name),
+        }
+    }
+
+    fn width(self) -> Constraint {
+        match self {
+            Column::Fd => Constraint::Length(8),
+            Column::Type => Constraint::Length(7),
+            Column::Device => Constraint::Length(12),
+            Column::Size => Constraint::Length(10),
+            Column::Node => Constraint::Length(10),
+            Column::Name => Constraint::Min(20),
+        }
+    }
+}
+
+/// The table's columns, in display order, for the visible column set.
+fn columns(visible: FileColumns) -> Vec<Column> {
+    [
+        (Column::Fd, true),
+        (Column::Type, true),
+        (Column::Device, visible.shows(FileColumns::DEVICE)),
+        (Column::Size, visible.shows(FileColumns::SIZE)),
+        (Column::Node, visible.shows(FileColumns::NODE)),
+        (Column::Name, true),
+    ]
+    .into_iter()
+    .filter_map(|(column, shown)| shown.then_some(column))
+    .collect()
+}
+
+/// A header cell: the column title, with an arrow on the sorted column.
+fn header_title(column: Column, sort: Option<FileSort>) -> String {
+    match sort {
+        Some(s) if column.sort_key() == Some(s.key) => {
+            format!("{}{}", column.title(), if s.descending { "▼" } else { "▲" })
+        }
+        _ => column.title().to_string(),
+    }
+}
+
+fn widths(columns: &[Column]) -> Vec<Constraint> {
+    columns.iter().map(|c| c.width()).collect()
+}
+
 fn format_size(bytes: u64) -> String {
     if bytes < 1024 {
         format!("{}", bytes)
@@ -71,3 +140,50 @@ fn format_size(bytes: u64) -> String {
         format!("{:.1}G", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_presets_map_to_widths() {
+        assert_eq!(
+            widths(&columns(FileColumns::ALL)),
+            vec![
+                Constraint::Length(8),
+                Constraint::Length(7),
+                Constraint::Length(12),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Min(20),
+            ]
+        );
+        assert_eq!(
+            columns(FileColumns::NAME_FOCUSED),
+            vec![Column::Fd, Column::Type, Column::Size, Column::Name]
+        );
+        assert_eq!(
+            widths(&columns(FileColumns::MINIMAL)),
+            vec![
+                Constraint::Length(8),
+                Constraint::Length(7),
+                Constraint::Min(20)
+            ]
+        );
+        assert_eq!(
+            FileColumns::ALL.next_preset().next_preset().next_preset(),
+            FileColumns::ALL
+        );
+    }
+
+    #[test]
+    fn test_header_marks_sorted_column() {
+        let sort = Some(FileSort {
+            key: FileSortKey::Size,
+            descending: true,
+        });
+        assert_eq!(header_title(Column::Size, sort), "SIZE/OFF▼");
+        assert_eq!(header_title(Column::Name, sort), "NAME");
+        assert_eq!(header_title(Column::Device, None), "DEVICE");
+    }
+}
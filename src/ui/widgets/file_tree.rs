@@ -5,6 +5,7 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{List, ListItem, Paragraph};
 use ratatui::Frame;
 
+use crate::app::state::tree_dir_key;
 use crate::app::AppState;
 use crate::model::OpenFileInfo;
 use crate::ui::theme;
@@ -33,19 +34,9 @@ pub fn render(frame: &mut Frame, state: &mut AppState, area: Rect) {
     let mut tree: BTreeMap<String, Vec<TreeEntry>> = BTreeMap::new();
 
     for f in &proc.open_files {
-        let entry = make_tree_entry(f);
-        let path = &f.name;
-        if let Some(pos) = path.rfind('/') {
-            let dir = &path[..pos];
-            let dir_key = if dir.is_empty() {
-                "/".to_string()
-            } else {
-                dir.to_string()
-            };
-            tree.entry(dir_key).or_default().push(entry);
-        } else {
-            tree.entry("(other)".to_string()).or_default().push(entry);
-        }
+        tree.entry(tree_dir_key(&f.name))
+            .or_default()
+            .push(make_tree_entry(f));
     }
 
     let mut items: Vec<ListItem> = Vec::new();
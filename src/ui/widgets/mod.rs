@@ -2,3 +2,39 @@ pub mod file_table;
 pub mod file_tree;
 pub mod net_table;
 pub mod summary;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Shorten `s` to at most `width` terminal columns, ending it with `…` when
+/// anything was cut. Wide characters count as two columns.
+pub fn truncate_to_width(s: &str, width: usize) -> String {
+    if s.width() <= width {
+        return s.to_string();
+    }
+    let Some(budget) = width.checked_sub(1) else {
+        return String::new();
+    };
+    let mut used = 0;
+    let mut out: String = s
+        .chars()
+        .take_while(|c| {
+            used += c.width().unwrap_or(0);
+            used <= budget
+        })
+        .collect();
+    out.push('…');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(truncate_to_width("nginx", 8), "nginx");
+        assert_eq!(truncate_to_width("postgres", 5), "post…");
+        assert_eq!(truncate_to_width("日本語", 4), "日…");
+        assert_eq!(truncate_to_width("abc", 0), "");
+    }
+}
@@ -1,13 +1,20 @@
-use ratatui::layout::{Constraint, Rect};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::Modifier;
 use ratatui::text::Span;
-use ratatui::widgets::{Cell, Paragraph, Row, Table};
+use ratatui::widgets::{Cell, Paragraph, Row, Table, Wrap};
 use ratatui::Frame;
 
+use super::truncate_to_width;
 use crate::app::AppState;
 use crate::model::FileType;
 use crate::ui::theme;
 
+/// Widths of the FD and PROTO columns.
+const FD_WIDTH: u16 = 8;
+const PROTO_WIDTH: u16 = 7;
+/// Rows under the table for the selected endpoint in full.
+const DETAIL_HEIGHT: u16 = 2;
+
 pub fn render(frame: &mut Frame, state: &mut AppState, area: Rect) {
     let proc = match &state.selected_process {
         Some(p) => p,
@@ -41,6 +48,12 @@ pub fn render(frame: &mut Frame, state: &mut AppState, area: Rect) {
     ])
     .style(theme::header_style());
 
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(DETAIL_HEIGHT)])
+        .split(area);
+
+    let name_width = name_width(chunks[0].width);
     let rows: Vec<Row> = net_files
         .iter()
         .map(|f| {
@@ -48,14 +61,14 @@ pub fn render(frame: &mut Frame, state: &mut AppState, area: Rect) {
             Row::new(vec![
                 Cell::from(Span::styled(f.fd.to_string(), style)),
                 Cell::from(Span::styled(f.file_type.to_string(), style)),
-                Cell::from(Span::styled(f.name.clone(), style)),
+                Cell::from(Span::styled(truncate_to_width(&f.name, name_width), style)),
             ])
         })
         .collect();
 
     let widths = [
-        Constraint::Length(8),
-        Constraint::Length(7),
+        Constraint::Length(FD_WIDTH),
+        Constraint::Length(PROTO_WIDTH),
         Constraint::Min(30),
     ];
 
@@ -63,5 +76,43 @@ pub fn render(frame: &mut Frame, state: &mut AppState, area: Rect) {
         .header(header)
         .row_highlight_style(theme::selected_style().add_modifier(Modifier::BOLD));
 
-    frame.render_stateful_widget(table, area, &mut state.file_table_state);
+    frame.render_stateful_widget(table, chunks[0], &mut state.file_table_state);
+
+    // The NAME cell may be cut short; show the selected endpoint whole.
+    if let Some(f) = state
+        .file_table_state
+        .selected()
+        .and_then(|i| net_files.get(i))
+    {
+        let detail = Paragraph::new(Span::styled(format!(" {}", f.name), theme::status_style()))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(detail, chunks[1]);
+    }
+}
+
+/// Columns left for NAME in a table `table_width` wide, after FD, PROTO
+/// and the one-column gaps between them.
+fn name_width(table_width: u16) -> usize {
+    table_width.saturating_sub(FD_WIDTH + PROTO_WIDTH + 2) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_name_truncated_to_column() {
+        let name = format!(
+            "[{}]:443 -> [{}]:51234 (ESTABLISHED)",
+            "2001:db8:85a3::8a2e:370:7334",
+            "f".repeat(60)
+        );
+        assert_eq!(name.chars().count(), 120);
+        let width = name_width(80);
+        assert_eq!(width, 63);
+        let shown = truncate_to_width(&name, width);
+        assert_eq!(shown.chars().count(), width);
+        assert!(shown.ends_with('…'));
+        assert!(name.starts_with(shown.trim_end_matches('…')));
+    }
 }
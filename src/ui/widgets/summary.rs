@@ -76,7 +76,7 @@ pub fn render(frame: &mut Frame, state: &AppState, area: Rect) {
     }
 
     let mut sorted_types: Vec<_> = type_counts.into_iter().collect();
-    sorted_types.sort_by(|a, b| (b.1).0.cmp(&(a.1).0));
+    sorted_types.sort_by_key(|(_, (count, _))| std::cmp::Reverse(*count));
 
     let mut stat_lines: Vec<Line> = Vec::new();
     for (type_name, (count, style)) in &sorted_types {
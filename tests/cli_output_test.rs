@@ -1,4 +1,4 @@
-use assert_cmd::Command;
+use assert_cmd::cargo::cargo_bin_cmd;
 use std::process;
 
 // ---------------------------------------------------------------------------
@@ -179,9 +179,8 @@ fn test_cmd_width_flag() {
 
 #[test]
 fn test_flag_b_accepted() {
-    let output = Command::cargo_bin("loof")
-        .unwrap()
-        .args(&["-b", "-p", "1", "-t"])
+    let output = cargo_bin_cmd!("loof")
+        .args(["-b", "-p", "1", "-t"])
         .output()
         .expect("failed to run");
     // Just verify the flag is accepted (exit code 0 or normal output)
@@ -190,9 +189,8 @@ fn test_flag_b_accepted() {
 
 #[test]
 fn test_flag_x_accepted() {
-    let output = Command::cargo_bin("loof")
-        .unwrap()
-        .args(&["-x", "-p", "1", "-t"])
+    let output = cargo_bin_cmd!("loof")
+        .args(["-x", "-p", "1", "-t"])
         .output()
         .expect("failed to run");
     assert!(output.status.success() || !output.stderr.is_empty());
@@ -200,9 +198,8 @@ fn test_flag_x_accepted() {
 
 #[test]
 fn test_flag_s_upper_accepted() {
-    let output = Command::cargo_bin("loof")
-        .unwrap()
-        .args(&["-S", "-p", "1", "-t"])
+    let output = cargo_bin_cmd!("loof")
+        .args(["-S", "-p", "1", "-t"])
         .output()
         .expect("failed to run");
     assert!(output.status.success() || !output.stderr.is_empty());
@@ -210,9 +207,8 @@ fn test_flag_s_upper_accepted() {
 
 #[test]
 fn test_flag_l_upper_accepted() {
-    let output = Command::cargo_bin("loof")
-        .unwrap()
-        .args(&["-L", "-p", "1", "-t"])
+    let output = cargo_bin_cmd!("loof")
+        .args(["-L", "-p", "1", "-t"])
         .output()
         .expect("failed to run");
     assert!(output.status.success() || !output.stderr.is_empty());
@@ -224,11 +220,83 @@ fn test_flag_l_upper_accepted() {
 
 #[test]
 fn test_flag_t_upper_tcp_info() {
-    let output = Command::cargo_bin("loof")
-        .unwrap()
-        .args(&["-T", "-i", "-n", "-P", "-p", "1"])
+    let output = cargo_bin_cmd!("loof")
+        .args(["-T", "-i", "-n", "-P", "-p", "1"])
         .output()
         .expect("failed to run");
     // Verify the flag is accepted
     assert!(output.status.success() || !output.stderr.is_empty());
 }
+
+// ---------------------------------------------------------------------------
+// Report destination (--output)
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_summary_modes_write_to_output_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("report.txt");
+    let my_pid = process::id().to_string();
+    for mode in [
+        &["--dry-run"][..],
+        &["--count-only"],
+        &["-N"],
+        &["--capabilities"],
+        &["--list-types"],
+        &["--fields-help"],
+    ] {
+        let output = cargo_bin_cmd!("loof")
+            .args(mode)
+            .args(["-p", &my_pid, "--output"])
+            .arg(&path)
+            .output()
+            .expect("failed to run");
+        assert!(output.status.success(), "{:?} failed", mode);
+        assert!(output.stdout.is_empty(), "{:?} wrote to stdout", mode);
+        assert!(!std::fs::read_to_string(&path).unwrap().is_empty());
+    }
+}
+
+#[test]
+fn test_exit_code_match_errors_exit_two() {
+    let output = cargo_bin_cmd!("loof")
+        .args(["--exit-code-match", "-p", "abc"])
+        .output()
+        .expect("failed to run");
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = cargo_bin_cmd!("loof")
+        .args(["--exit-code-match", "--port", "80"])
+        .output()
+        .expect("failed to run");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+}
+
+#[test]
+fn test_cwd_under_rejected_in_fd_count_modes() {
+    for mode in [&["--top", "3"][..], &["--count-only"], &["--zero-fds"]] {
+        let output = cargo_bin_cmd!("loof")
+            .args(mode)
+            .args(["--cwd-under", "/nonexistent"])
+            .output()
+            .expect("failed to run");
+        assert!(!output.status.success(), "{:?} accepted --cwd-under", mode);
+        assert!(output.stdout.is_empty());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+    }
+}
+
+#[test]
+fn test_fd_count_modes_reject_file_filters() {
+    for mode in [&["--top", "5"][..], &["--count-only"], &["--zero-fds"]] {
+        let output = cargo_bin_cmd!("loof")
+            .args(mode)
+            .args(["-i", ":443"])
+            .output()
+            .expect("failed to run");
+        assert_eq!(output.status.code(), Some(1), "{:?} accepted -i", mode);
+        assert!(output.stdout.is_empty());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("not file filters"));
+    }
+}